@@ -0,0 +1,76 @@
+//! An example plugin: `coin` flips a coin, `shout` shouts the rest-field.
+//!
+//! Build it with `cargo build --example coin_plugin` and copy the library from
+//! `target/debug/examples` into the bot's `plugin_path`.
+
+use std::ffi::{ c_char, CStr, CString };
+
+#[repr(C)]
+pub struct PluginV1 {
+    abi_version: u32,
+    name: *const c_char,
+    commands: *const *const c_char,
+    whatis: extern "C" fn(name: *const c_char) -> *mut c_char,
+    call: extern "C" fn(request: *const c_char) -> *mut c_char,
+    free_string: extern "C" fn(s: *mut c_char),
+}
+
+// raw pointers are not `Sync`, so statics holding them need a wrapper
+struct Names<const N: usize>([*const c_char; N]);
+unsafe impl<const N: usize> Sync for Names<N> {}
+unsafe impl Sync for PluginV1 {}
+
+static COMMANDS: Names<3> = Names([c"coin".as_ptr(), c"shout".as_ptr(), std::ptr::null()]);
+
+static PLUGIN: PluginV1 = PluginV1 {
+    abi_version: 1,
+    name: c"coin".as_ptr(),
+    commands: &COMMANDS.0 as *const _ as *const *const c_char,
+    whatis, call, free_string,
+};
+
+#[no_mangle]
+pub extern "C" fn nombot_plugin_v1() -> *const PluginV1 { &PLUGIN }
+
+fn to_c(s: String) -> *mut c_char {
+    CString::new(s.replace('\0', "")).expect("NULs were removed").into_raw()
+}
+
+extern "C" fn whatis(name: *const c_char) -> *mut c_char {
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+    to_c(match &name[..] {
+        "coin" => "Flip a coin".into(),
+        "shout" => "Shout the rest-field".into(),
+        _ => String::new(),
+    })
+}
+
+/// `nom/shout [times=<n>]/<text>`
+fn shout(request: &serde_json::Value) -> String {
+    let rest = request["rest"].as_str().unwrap_or("").trim();
+    if rest.is_empty() {
+        return String::new();
+    }
+    let times = request["args"].as_array().into_iter().flatten()
+        .find(|a| a["key"] == "times")
+        .and_then(|a| a["value"].as_str()?.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, 10);
+    vec![format!("{}!", rest.to_uppercase()); times].join(" ")
+}
+
+extern "C" fn call(request: *const c_char) -> *mut c_char {
+    let request = unsafe { CStr::from_ptr(request) }.to_string_lossy();
+    let request: serde_json::Value = serde_json::from_str(&request).unwrap_or_default();
+    to_c(match request["name"].as_str() {
+        Some("coin") => if rand::random() { "heads" } else { "tails" }.into(),
+        Some("shout") => shout(&request),
+        _ => String::new(),
+    })
+}
+
+extern "C" fn free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
@@ -0,0 +1,27 @@
+//! Run with `cargo +nightly fuzz run nom_args` from the repository root. The same properties are
+//! checked by the proptests in `src/nom_args.rs`, which run under `cargo test`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// nombot is a binary crate, so the parser is included by path
+#[allow(dead_code)]
+#[path = "../../src/nom_args.rs"]
+mod nom_args;
+
+fuzz_target!(|s: &str| {
+    let none = |_: &str| None;
+    let parsed = nom_args::parse_template(s);
+    if let Ok(t) = &parsed {
+        let _ = t.expand(&|_| Some("x".into()), &mut |_| Some("y".into()));
+    }
+    let _ = nom_args::parse_script(s);
+
+    // whatever parses, unparses into text that parses the same
+    let Ok(c) = parsed.and_then(|t| t.expand(&none, &mut |_| None)) else { return };
+    if let Ok(u) = nom_args::unparse(&c) {
+        let reparsed = nom_args::parse_template(&u).and_then(|t| t.expand(&none, &mut |_| None));
+        assert_eq!(reparsed, Ok(c), "{u:?}");
+    }
+});
@@ -0,0 +1,618 @@
+# Messages in English, the fallback for every other locale.
+
+## shared
+whatis-default = (nothing appropriate)
+error-no-rest = does not accept a rest-field
+error-guild-only = only available in servers
+error-not-saved = the change is in effect but could not be saved
+
+## subcommands
+usage-heading = ## Usage
+usage-help = Print help (this message)
+subcommand-guild-admin = requires the Manage Server permission
+subcommand-owner = only bot owners may do this
+subcommand-note-guild-admin = (requires the Manage Server permission)
+subcommand-note-owner = (bot owners only)
+subcommand-unknown = unknown subcommand { $name }; available: { $available }
+subcommand-expected = expected a subcommand; available: { $available }
+subcommand-key-repeated = { $key }= is given more than once
+subcommand-no-keys = does not accept { $key }=
+subcommand-unknown-key = unknown key { $key }; expected: { $expected }
+subcommand-unexpected-arg = unexpected argument { $arg }
+subcommand-expected-arg = expected <{ $arg }>
+subcommand-expected-key = expected { $key }=<{ $key }>
+
+## arguments
+literal-bad-key = { $key } must be { $expected }, not { $value }
+literal-bad = expected { $expected }, not { $value }
+literal-integer = an integer
+literal-number = a number
+literal-boolean = a boolean (yes/no/true/false/1/0)
+literal-duration = a duration such as 10m or 2h30m (units: w, d, h, m, s)
+literal-mention = a mention or an ID
+literal-list = a comma-separated list of { $item }
+literal-text = text
+option-unknown = unrecognized option --{ $name }; available options: { $available }
+option-no-value = --{ $name } expects a value
+option-unknown-short = unrecognized flag shorthand { $char }; available shorthands: { $available }
+
+## parser
+parse-at-column = At column { $col } (byte { $byte }) after prefix: { $why }
+parse-at-byte = At byte { $byte } after prefix: { $why }
+parse-unknown-variable = unknown variable ${ $name }; write \${ $name } for a literal '$'
+parse-no-substitution = command substitution is not available here
+parse-script-open = expected '{"{"}' to begin a script
+parse-command-after-and = expected a command after '&&'
+parse-after-script = unexpected text after '{"}"}' closing the script
+parse-script-empty = a script needs at least one command
+parse-script-close = expected '{"}"}' to close the script
+parse-command-before-and = expected a command before '&&'
+parse-script-too-long = a script may run at most { $max } commands
+parse-substitution-close = expected ')' to close '$('
+parse-rest = expected a <rest> region beginning with '/'
+parse-equals = expected '=' for key-value pair
+parse-code-open = expected '```' to begin a code block
+parse-code-close = expected '```' to close the code block
+parse-missing-string = missing string
+parse-quote-open = quoted string does not begin with quote
+parse-naked-escape = \(whitespace), \=, \/, \( and \) are only available in naked strings
+parse-bad-escape = unsupported escape sequence \{ $char }
+parse-quote-close = unterminated quoted string
+parse-heredoc-open = expected '<<' and a tag to begin a heredoc
+parse-heredoc-close = expected a line beginning with { $tag } to end the heredoc
+parse-quoted-escape = \n and \t are only available in quoted strings
+parse-escape-end = missing operand after escape character '\'
+parse-naked-char = invalid character to appear in a naked string ({ $char })
+parse-substitution-depth = '$(' cannot nest more than { $max } deep
+parse-variable-brace = expected a variable name and '{"}"}' after '${"{"}'
+parse-ident = expected an identifier, which begins with [0-9A-Za-z_-]
+parse-spaces = expected whitespace
+
+## evaluation
+eval-unknown-command = Command { $name } does not exist
+eval-disabled = Command { $name } is disabled in this server
+eval-not-in-channel = Command { $name } is not available in this channel
+eval-too-many-substitutions = a message may run at most { $max } substitutions
+eval-substitution-output = substitutions may produce at most { $max } bytes
+eval-substitution-failed = $({ $name }) failed: { $why }
+
+## nom
+nom-whatis = Nommers. ('!' for more enthusiasm, '.' for less)
+nom-conflict = cannot have both `!`s and `.`s
+nom-bad-char = unrecognized character { $char }
+nom-no-keywords = does not accept keyword arguments
+nom-no-code = does not accept code blocks
+
+## echo
+echo-whatis = Echo-back the rest-field
+echo-help =
+    ## Examples
+    Let nombot say hello world
+    ```
+    nom/echo/Hello, world!
+    ```
+    Let nombot say aehmttw
+    ```
+    nom/echo sorted=yes/matthew
+    ```
+    Let nombot say world hello on two lines
+    ```
+    nom/echo reversed=1 scope=line/Hello,
+    world!
+    ```
+    Let nombot say the words sorted, with 9 before 10 and as in a Swedish dictionary
+    ```
+    nom/echo -sn scope=word collation=sv/zebra äpple a10 a9
+    ```
+    Short hands are supported, and empty lines are ignored when using line scope
+    ```
+    nom/echo -srl/
+    Bananas
+    Apples
+    Cherries
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/echo help
+    ```
+    Echo-back the rest-field, transformed according to options
+    ```
+    nom/echo [-sSrRgcwl] sorted=[yes|no] reversed=[yes|no] scope=[grapheme|char|word|sentence|line]
+    nom/echo [--[no-]sorted] [--[no-]reversed] [--scope grapheme|char|word|sentence|line]
+    ```
+    Graphemes, the default scope, are what reads as one character, like 🇯🇵 or 👍🏽; `char` is a code point. Sorting goes by code point unless an option for collation is given
+    ```
+    nom/echo [-ni] collation=<locale> numeric=[yes|no] ignore-case=[yes|no]
+    nom/echo [--collation <locale>] [--[no-]numeric] [--[no-]ignore-case]
+    ```
+    `collation` is a locale like `de` or `sv`; `-n` (`numeric`) sorts 9 before 10 and `-i` (`ignore-case`) ignores case, in your locale unless `collation` names another.
+echo-refuse-bomb = nombot refuses to `nom/echo`-bomb this channel. :/
+echo-empty = nombot cannot send an empty message. :/
+echo-positional = does not accept non-flag positional arguments; use the rest-field instead.
+echo-option-repeated = the '{ $option }' option is set multiple times
+echo-bad-scope = the 'scope' option has value { $scopes }
+echo-unknown-option = unrecognized option { $option }; available options: sorted, reversed, scope, collation, numeric, ignore-case
+echo-no-code = does not accept code blocks as arguments; use the rest-field instead.
+
+## help
+help-whatis = Print all existing commands
+help-whatis-alias = Alias of `help`
+help-no-arguments = does not accept arguments (yet)
+help-script = A script of this server
+help-empty = There is no help. (???)
+help-scripts = Run several commands with one reply as `nom/{"{"}nom !; echo/done{"}"}`; `&&` instead of `;` skips the rest after a failure.
+complete-whatis = Show how a partial command in the rest-field could go on
+complete-no-arguments = does not accept arguments; use the rest-field
+complete-none = No completions.
+
+## set
+set-whatis = Set variables for `$name` expansion
+set-whatis-unset = Remove variables
+set-help =
+    ## Examples
+    Set a variable for yourself, then use it
+    ```
+    nom/set snack=cookies
+    nom/echo/I like $snack
+    ```
+    Set a variable for everyone in this channel
+    ```
+    nom/set -c topic="lunch plans"
+    ```
+    Set a multi-line variable with a heredoc, which ends at a line beginning with its tag
+    ```
+    nom/set poem=<<END
+    roses are red
+    cookies are sweet
+    END
+    ```
+    Use the output of another command with `$(...)`
+    ```
+    nom/set snack=$(echo -s/cookies)
+    ```
+    Write `\$` (or use single quotes) for a literal `$`
+    ```
+    nom/echo '$5' is \$5
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/set help
+    ```
+    List the variables visible to you here
+    ```
+    nom/set
+    ```
+    Set or remove your variables, or the channel's with `-c` (or `--channel`; in servers this needs the Manage Channels or Manage Server permission)
+    ```
+    nom/set [-c] <name>=<value>*
+    nom/unset [-c] <name>*
+    ```
+    Built-in variables: $author, $channel, $now. Your variables take precedence over the channel's.
+set-bad-channel = --channel must be { $expected }, not { $value }
+set-see-help = see `nom/set help`
+set-bad-name = { $name } is not a variable name ([A-Za-z_][0-9A-Za-z_]*)
+set-builtin = ${ $name } is built-in
+set-too-long = ${ $name } is longer than { $max } bytes
+set-listed-user = ${ $name } = { $value } (you)
+set-listed-channel = ${ $name } = { $value } (channel)
+set-none = No variables are set.
+set-done = Done.
+set-too-many = at most { $max } { $max ->
+    [one] variable
+   *[other] variables
+} can be set
+set-channel-denied = changing the channel's variables requires the Manage Channels permission
+
+## admin
+admin-whatis = Manage the bot (owners only)
+admin-modules-about = List the known modules and whether they are loaded
+admin-enable-about = Load a module without restarting the bot
+admin-disable-about = Unload a module without restarting the bot
+admin-reload-about = Reload a module without restarting the bot
+admin-scan-plugins-about = Make the new plugins in the plugin directory known as modules (they still need `enable-module`)
+admin-module-loaded = { $module } - loaded
+admin-module-not-loaded = { $module } - not loaded
+admin-refuse-unload = refusing to unload the `admin` module
+admin-no-plugin-path = no plugin directory is configured
+admin-plugin-found = found { $name }
+admin-plugin-taken = { $path }: a module named { $name } already exists
+admin-no-new-plugins = No new plugins.
+admin-loaded = Loaded `{ $module }` ({ $count ->
+    [one] 1 command
+   *[other] { $count } commands
+}: { $names }).
+admin-unloaded = Unloaded `{ $module }` ({ $count ->
+    [one] 1 command
+   *[other] { $count } commands
+}: { $names }).
+registry-duplicate-module = module { $module } is already known
+registry-unknown-module = module { $module } does not exist
+registry-already-loaded = module { $module } is already loaded
+registry-not-loaded = module { $module } is not loaded
+registry-conflict = command { $name } is already bound by module { $module }
+
+## locale
+locale-whatis = Choose the language nombot replies in
+locale-examples =
+    ## Examples
+    Let nombot reply to you in Japanese
+    ```
+    nom/locale set ja
+    ```
+locale-available = Available locales: { $available }. The default is { $default }.
+locale-show-about = Show the locale nombot replies to you in
+locale-set-about = Choose your locale
+locale-unset-about = Follow the server's locale again
+locale-set-server-about = Choose the locale for everyone in this server who has not chosen one
+locale-unset-server-about = Go back to the default locale in this server
+locale-current = Your locale is `{ $locale }`.
+locale-set = Your locale is now `{ $locale }`.
+locale-server-set = This server's locale is now `{ $locale }`.
+locale-unknown = unknown locale { $locale }; available: { $available }
+locale-unset = Your locale now follows the server's.
+locale-server-unset = This server's locale is back to the default.
+
+## config
+config-whatis = Enable, disable or restrict commands in this server
+config-examples =
+    ## Examples
+    Disable `echo` in this server
+    ```
+    nom/config disable=echo
+    ```
+    Only allow `nom` in two channels
+    ```
+    nom/config restrict=nom/#general #snacks
+    ```
+config-notes = `disable=<command>`, `enable=`, `restrict=` and `unrestrict=` are short for the subcommands of `nom/config command`. Aliases of a command are always changed together.
+config-show-about = Show this server's command settings (also `nom/config` alone)
+config-disable-about = Disable a command in this server
+config-enable-about = Enable a disabled command again
+config-restrict-about = Only allow a command in the given channels
+config-unrestrict-about = Allow a command in every channel again
+config-disabled = Disabled: { $names }
+config-restricted = `{ $name }` only in: { $channels }
+config-unrestricted = All commands are enabled everywhere in this server.
+config-no-command = command { $name } does not exist
+config-refuse-lock = refusing to lock the `config` command
+config-expected-channels = expected channels in the rest-field
+config-updated = Updated `{ $names }`.
+
+## script
+script-whatis = Manage this server's scripted commands
+script-examples =
+    ## Examples
+    Add a command `greet` to this server
+    ```
+    nom/script set name=greet/`Hello, {"${"}cmd.rest{"}"}!`
+    ```
+    Then use it
+    ```
+    nom/greet/world
+    ```
+script-notes = Scripts are written in Rhai (https://rhai.rs). A script sees a constant `cmd` with fields `name`, `args`, `kw`, `rest`, `author` and `channel`, and its value is the reply. The source may be wrapped in a code block.
+script-list-about = List this server's scripts
+script-show-about = Show the source of a script
+script-set-about = Add or replace a script
+script-remove-about = Remove a script
+script-unavailable = scripts are unavailable
+script-none = This server has no scripts.
+script-by = `{ $name }` by <@{ $author }>
+script-not-found = no script named { $name }
+script-bad-name = { $name } is not a valid command name
+script-taken = { $name } is already a command
+script-saved = Saved `{ $name }`.
+script-removed = Removed `{ $name }`.
+script-set-not-saved = the script is in effect but could not be saved
+script-remove-not-saved = the script is removed but the change could not be saved
+script-no-command = Command { $name } does not exist
+script-failed = the script failed
+
+## roll
+roll-whatis = Roll dice, e.g. `4d6kh3 + 2`
+roll-help =
+    ## Examples
+    Roll four six-sided dice and keep the highest three, plus two exploding eight-sided dice and 5
+    ```
+    nom/roll/4d6kh3 + 2d8! + 5
+    ```
+    Roll with advantage, and label the rolls
+    ```
+    nom/roll/d20adv + 3 to hit, 2d6r1 + 3 damage
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/roll help
+    ```
+    Roll dice; separate several rolls with commas or new lines, each optionally followed by a label
+    ```
+    nom/roll/<NdS[modifiers]|number> [+|- ...] [label]
+    ```
+    Modifiers: `khN`/`klN` keep the highest/lowest N dice, `!` rolls again on the highest face, `rN` rerolls (once) dice showing N or less, `adv`/`dis` roll a single die twice and keep the higher/lower. `d%` is `d100`.
+roll-no-arguments = does not accept arguments; put the dice in the rest-field
+roll-no-dice = expected dice like `2d6` in the rest-field
+roll-too-many-rolls = at most { $max } rolls at once
+roll-too-many-dice = at most { $max } dice at once, not { $dice }
+roll-too-many-terms = at most { $max } terms per roll
+roll-unexpected = unexpected { $char } after `{ $term }`
+roll-number-too-large = numbers are at most { $max }, not { $number }
+roll-expected-term-not = expected dice like `2d6` or a number, not { $char }
+roll-expected-term = expected dice like `2d6` or a number
+roll-expected-sides = expected the number of sides after `d`
+roll-dice-per-term = from 1 to { $max } dice per term, not { $count }
+roll-sides = dice have from 1 to { $max } sides, not { $sides }
+roll-advantage-single = `adv` and `dis` apply to a single die
+roll-explode-repeated = `!` is given more than once
+roll-explode-one-sided = one-sided dice cannot explode
+roll-expected-reroll = expected the highest face to reroll after `r`
+roll-reroll-repeated = `r` is given more than once
+roll-reroll-every-face = cannot reroll every face of a d{ $sides }
+roll-keep-repeated = only one of `kh`, `kl`, `adv` and `dis` per term
+roll-keep-too-many = cannot keep { $keep } of { $count } dice
+
+## remind
+remind-whatis = Get reminded of something later
+remind-help =
+    ## Examples
+    Get reminded here in two and a half hours
+    ```
+    nom/remind in=2h30m/stand-up notes
+    ```
+    Get reminded by DM at a time in a time zone
+    ```
+    nom/remind --dm at="2026-11-01 09:00" tz=Europe/Berlin/renew the passport
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/remind help
+    ```
+    Set a reminder, delivered in this channel or by DM with `-d` (or `--dm`)
+    ```
+    nom/remind [-d] in=<duration>/<text>
+    nom/remind [-d] at=<time> [tz=<time zone>]/<text>
+    ```
+    List your pending reminders, or cancel one
+    ```
+    nom/remind list
+    nom/remind cancel <id>
+    ```
+    Durations are like `1w2d3h4m5s`. Times are like `2026-11-01 09:00`, or `09:00` for the next 9 o'clock; they are in UTC unless `tz` names a time zone of the tz database.
+remind-bad-dm = --dm must be { $expected }, not { $value }
+remind-not-found = you have no reminder #{ $id }
+remind-expected-subcommand = expected `in=`, `at=`, `list` or `cancel`; see `nom/remind help`
+remind-expected-text = expected what to remind of in the rest-field
+remind-too-long = reminders are at most { $max } characters long
+remind-no-rest = only reminders accept a rest-field
+remind-too-far-ahead = reminders can be set at most a year ahead
+remind-in-zero = in= must be more than nothing
+remind-unknown-tz = unknown time zone { $tz }; use a name like Europe/Berlin
+remind-bad-time = expected a time like "2026-11-01 09:00" or "09:00", not { $at }
+remind-passed = { $at } has already passed
+remind-no-such-time = { $time } does not exist in { $tz } (the clocks go forward)
+remind-too-many = at most { $max } reminders can be pending
+remind-by-dm = by DM
+remind-in-channel = in <#{ $channel }>
+remind-none = You have no reminders.
+remind-cancelled = Cancelled reminder `#{ $id }`.
+remind-cancel-not-saved = the reminder is cancelled but the change could not be saved
+remind-set = Reminder `#{ $id }` is set for <t:{ $due }:F> (<t:{ $due }:R>).
+remind-set-not-saved = the reminder is set but could not be saved, so it is lost if the bot restarts
+remind-delivery = <@{ $user }>, you asked to be reminded: { $text }
+remind-delivery-late = <@{ $user }>, you asked to be reminded (late, it was due <t:{ $due }:R>): { $text }
+
+## poll
+poll-whatis = Ask a question and count the votes
+poll-help =
+    ## Examples
+    Ask which lunch it should be, for an hour
+    ```
+    nom/poll duration=1h/Lunch? | Pizza | Sushi | Tacos
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/poll help
+    ```
+    Post a poll with 2 to 10 options, open for a minute to a week (an hour by default)
+    ```
+    nom/poll [duration=<duration>]/<question> | <option> | <option>*
+    ```
+    List the open polls of this channel, or close one early (its author or a server admin)
+    ```
+    nom/poll list
+    nom/poll close <id>
+    ```
+    Vote by reacting with the number of an option; a new vote replaces the old one. The results are posted when the poll closes.
+poll-expected-question = expected `<question> | <option> | <option>` in the rest-field
+poll-question-too-long = the question is longer than { $max } characters
+poll-option-count = from 2 to { $max } options, not { $count }
+poll-empty-option = options must not be empty
+poll-option-too-long = options are at most { $max } characters long
+poll-not-open = there is no open poll #{ $id } in this channel
+poll-duration-range = polls are open from a minute to a week
+poll-see-help = see `nom/poll help`
+poll-no-rest = only new polls accept a rest-field
+poll-none = No polls are open in this channel.
+poll-listed = `#{ $id }` { $question } (closes <t:{ $closes }:R>, { $link })
+poll-close-denied = only its author or a server admin can close a poll early
+poll-just-closed = poll #{ $id } has just closed
+poll-close-not-saved = the poll is closed but the change could not be saved
+poll-too-many = at most { $max } polls can be open in a channel
+poll-unavailable = polls are unavailable
+poll-post-failed = cannot post the poll
+poll-posted =
+    📊 **{ $question }**
+    { $options }
+    Vote with a reaction, one vote each. Closes <t:{ $closes }:R>; poll `#{ $id }`.
+poll-results-none = 📊 **{ $question }** has closed without votes.
+poll-results =
+    📊 **{ $question }** has closed with { $total ->
+        [one] 1 vote
+       *[other] { $total } votes
+    }:
+    { $lines }
+
+## quote
+quote-whatis = Save messages and recall them
+quote-help =
+    ## Examples
+    Save a message: reply to it with
+    ```
+    nom/quote add
+    ```
+    Recall a quote of someone, or one mentioning cookies
+    ```
+    nom/quote by=@someone
+    nom/quote search/cookies
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/quote help
+    ```
+    Save the message replied to as a quote; reacting to a message with { $emoji } does the same
+    ```
+    nom/quote add
+    ```
+    Recall a random quote, quote #<id>, a random quote by someone, or a random quote containing all of the words
+    ```
+    nom/quote
+    nom/quote id=<id>
+    nom/quote by=<user>
+    nom/quote search/<words>
+    ```
+    Quotes are saved per server.
+quote-expected-words = expected the words to search for in the rest-field
+quote-not-found = there is no quote #{ $id }
+quote-by-user = by= must be a user
+quote-see-help = see `nom/quote help`
+quote-no-rest = only `search` accepts a rest-field
+quote-guild-only = quotes are only saved in servers
+quote-expected-reply = reply to the message to save with `nom/quote add`, or react to it with { $emoji }
+quote-no-text = there is no text to quote
+quote-already = that message is already quote #{ $id }
+quote-too-many = at most { $max } quotes can be saved
+quote-saved = Saved as quote `#{ $id }`.
+quote-not-saved = the quote could not be saved
+quote-none = there are no quotes yet; save one with `nom/quote add` or { $emoji }
+quote-none-by = there are no quotes by them
+quote-none-found = no quote contains all of the words
+quote-shown =
+    { $text }
+    — **{ $name }**, <t:{ $time }:D> · [jump]({ $link }) · `#{ $id }`
+quote-also = Also { $ids }.
+quote-also-more = Also { $ids }, and { $count } more.
+
+## sed
+sed-whatis = Correct your last message with a regex
+sed-help =
+    ## Examples
+    Fix a typo in your last message
+    ```
+    nom/s/teh/the/g
+    ```
+    Swap two words, with another delimiter
+    ```
+    nom/sed/s|(\w+) (\w+)|\2 \1|
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/sed help
+    ```
+    Post your last message in this channel with the first match of a regex replaced, or every match with the flag `g`; the flag `i` ignores case
+    ```
+    nom/s/<regex>/<replacement>/[flags]
+    nom/sed/s<delimiter><regex><delimiter><replacement><delimiter>[flags]
+    ```
+    In the replacement, `&` is the whole match and `\1` to `\9` are groups. Write `\$` for a `$` in the regex, as in any rest-field. Commands are not counted as messages.
+sed-expected-s = expected `s/<regex>/<replacement>/`
+sed-expected-delimiter = expected a delimiter like `/` after `s`
+sed-expected-fields = expected `s{ $d }<regex>{ $d }<replacement>{ $d }[flags]`
+sed-no-group = there is no group { $group } in the regex
+sed-empty = the regex is empty
+sed-too-long = the regex is longer than { $max } characters
+sed-unknown-flag = unknown flag { $flag }; the flags are `g` and `i`
+sed-too-big = the regex is too big
+sed-invalid = invalid regex: { $error }
+sed-no-match = the regex does not match your last message
+sed-result-too-long = the result is longer than { $max } characters
+sed-see-help = see `nom/sed help`
+sed-no-recent = you have no recent message in this channel
+sed-meant = **{ $name }** meant: { $text }
+
+## tr
+tr-whatis = Transform the rest-field: case, ciphers, encodings and more
+tr-help =
+    ## Examples
+    Shout
+    ```
+    nom/tr upper/nom nom nom
+    ```
+    Shuffle the words the same way every time, then mock them
+    ```
+    nom/tr -w seed=42 shuffle mock/the quick brown fox
+    ```
+    Decode a secret
+    ```
+    nom/tr unbase64 rot13/QWJ6IGFieg==
+    ```
+    ## Usage
+    Print help (this message)
+    ```
+    nom/tr help
+    ```
+    Apply transforms to the rest-field, from left to right
+    ```
+    nom/tr [-gcwl] [scope=<scope>] [seed=<n>] [collation=<locale>] [-ni] <transform>+/<text>
+    nom/tr [--scope <scope>] [--seed <n>] [--collation <locale>] [--[no-]numeric] [--[no-]ignore-case] <transform>+/<text>
+    ```
+    `scope` is what `reverse`, `sort`, `shuffle` and `dedupe` rearrange: graphemes (the default; what reads as one character, like 🇯🇵), chars (code points), words, sentences, or lines. `seed` makes `shuffle` and `zalgo` repeatable. `sort` goes by code point unless `collation` names a locale like `de` or `sv`; `-n` (`numeric`) sorts 9 before 10 and `-i` (`ignore-case`) ignores case, in your locale unless `collation` names another.
+    ## Transforms
+tr-unknown-transform = unknown transform { $name }; see `nom/tr help`
+tr-option-repeated = the '{ $option }' option is set multiple times
+tr-bad-scope = the 'scope' option is one of { $scopes }
+tr-negative-seed = the 'seed' option must not be negative
+tr-unknown-option = unrecognized option { $option }; available options: scope, seed, collation, numeric, ignore-case
+tr-no-code = does not accept code blocks as arguments; use the rest-field instead
+tr-no-transforms = expected at least one transform; see `nom/tr help`
+tr-too-many = at most { $max } transforms at a time
+tr-grew-too-long = the text grew too long
+tr-too-long = the result is longer than { $max } characters
+tr-empty = the result is empty
+tr-refuse-commands = nombot does not transform its own commands into commands
+transform-reverse = reverse the order of the units
+transform-sort = sort the units
+transform-shuffle = shuffle the units
+transform-dedupe = drop repeated units
+transform-upper = UPPERCASE
+transform-lower = lowercase
+transform-title = Title Case
+transform-mock = mOcKiNg CaSe
+transform-leet = l337sp34k
+transform-zalgo = add combining marks
+transform-rot13 = rotate letters by 13
+transform-morse = to Morse code
+transform-unmorse = from Morse code
+transform-base64 = to base64
+transform-unbase64 = from base64
+transform-hex = to hex bytes
+transform-unhex = from hex bytes
+transform-url = percent-encode
+transform-unurl = percent-decode
+transform-codepoint-options = numeric and ignore-case need a locale collation
+transform-bad-locale = { $tag } is not a locale like `en` or `de-CH`
+transform-no-collator = cannot collate for { $tag }: { $error }
+transform-not-utf8 = the decoded bytes are not UTF-8 text
+transform-base64-digit = { $char } is not a base64 digit
+transform-base64-end = base64 text cannot end with a single digit
+transform-hex-pairs = hex bytes are pairs of digits
+transform-hex-byte = { $pair } is not a hex byte
+transform-url-digits = '%' must be followed by two hex digits
+transform-url-byte = %{ $hex } is not a percent-encoded byte
+transform-no-morse = { $char } has no Morse code
+transform-not-morse = { $code } is not Morse code
@@ -0,0 +1,606 @@
+# 日本語のメッセージ。ここにないものは英語で表示されます。
+
+## shared
+whatis-default = （説明はありません）
+error-no-rest = 残りフィールドは受け付けません
+error-guild-only = サーバー内でのみ使えます
+error-not-saved = 変更は有効になりましたが、保存できませんでした
+
+## subcommands
+usage-heading = ## 使い方
+usage-help = ヘルプ（このメッセージ）を表示
+subcommand-guild-admin = 「サーバー管理」権限が必要です
+subcommand-owner = ボットのオーナーのみ実行できます
+subcommand-note-guild-admin = （「サーバー管理」権限が必要）
+subcommand-note-owner = （ボットのオーナーのみ）
+subcommand-unknown = 不明なサブコマンド { $name } です。利用可能: { $available }
+subcommand-expected = サブコマンドを指定してください。利用可能: { $available }
+subcommand-key-repeated = { $key }= が複数回指定されています
+subcommand-no-keys = { $key }= は受け付けません
+subcommand-unknown-key = 不明なキー { $key } です。指定できるキー: { $expected }
+subcommand-unexpected-arg = 余分な引数 { $arg } があります
+subcommand-expected-arg = <{ $arg }> を指定してください
+subcommand-expected-key = { $key }=<{ $key }> を指定してください
+
+## arguments
+literal-bad-key = { $key } は { $expected } で指定してください（{ $value } ではなく）
+literal-bad = { $expected } を指定してください（{ $value } ではなく）
+literal-integer = 整数
+literal-number = 数値
+literal-boolean = 真偽値（yes/no/true/false/1/0）
+literal-duration = 10m や 2h30m のような時間（単位: w, d, h, m, s）
+literal-mention = メンションまたは ID
+literal-list = カンマ区切りの { $item } のリスト
+literal-text = テキスト
+option-unknown = 不明なオプション --{ $name } です。利用可能なオプション: { $available }
+option-no-value = --{ $name } には値が必要です
+option-unknown-short = 不明な短縮フラグ { $char } です。利用可能な短縮フラグ: { $available }
+
+## parser
+parse-at-column = プレフィックスの後の { $col } 文字目（{ $byte } バイト目）: { $why }
+parse-at-byte = プレフィックスの後の { $byte } バイト目: { $why }
+parse-unknown-variable = 不明な変数 ${ $name } です。'$' そのものを書くには \${ $name } と書いてください
+parse-no-substitution = ここではコマンド置換は使えません
+parse-script-open = スクリプトは '{"{"}' で始めてください
+parse-command-after-and = '&&' の後にコマンドが必要です
+parse-after-script = スクリプトを閉じる '{"}"}' の後に余分なテキストがあります
+parse-script-empty = スクリプトには少なくとも 1 つのコマンドが必要です
+parse-script-close = スクリプトを閉じる '{"}"}' が必要です
+parse-command-before-and = '&&' の前にコマンドが必要です
+parse-script-too-long = スクリプトで実行できるコマンドは { $max } 個までです
+parse-substitution-close = '$(' を閉じる ')' が必要です
+parse-rest = '/' で始まる残りフィールドが必要です
+parse-equals = キーと値の組には '=' が必要です
+parse-code-open = コードブロックは '```' で始めてください
+parse-code-close = コードブロックを閉じる '```' が必要です
+parse-missing-string = 文字列がありません
+parse-quote-open = 引用符付きの文字列が引用符で始まっていません
+parse-naked-escape = \(空白)、\=、\/、\( と \) は引用符なしの文字列でのみ使えます
+parse-bad-escape = 対応していないエスケープシーケンス \{ $char } です
+parse-quote-close = 引用符付きの文字列が閉じられていません
+parse-heredoc-open = ヒアドキュメントは '<<' とタグで始めてください
+parse-heredoc-close = ヒアドキュメントを終えるには { $tag } で始まる行が必要です
+parse-quoted-escape = \n と \t は引用符付きの文字列でのみ使えます
+parse-escape-end = エスケープ文字 '\' の後に文字がありません
+parse-naked-char = 引用符なしの文字列に使えない文字（{ $char }）があります
+parse-substitution-depth = '$(' は { $max } 段までしか入れ子にできません
+parse-variable-brace = '${"{"}' の後には変数名と '{"}"}' が必要です
+parse-ident = 識別子が必要です（[0-9A-Za-z_-] で始まります）
+parse-spaces = 空白が必要です
+
+## evaluation
+eval-unknown-command = コマンド { $name } は存在しません
+eval-disabled = コマンド { $name } はこのサーバーで無効になっています
+eval-not-in-channel = コマンド { $name } はこのチャンネルでは使えません
+eval-too-many-substitutions = 1 つのメッセージで実行できる置換は { $max } 個までです
+eval-substitution-output = 置換の出力は合計 { $max } バイトまでです
+eval-substitution-failed = $({ $name }) が失敗しました: { $why }
+
+## nom
+nom-whatis = もぐもぐ。（'!' でもっと元気に、'.' で控えめに）
+nom-conflict = `!` と `.` は同時に使えません
+nom-bad-char = 認識できない文字 { $char } があります
+nom-no-keywords = キーワード引数は受け付けません
+nom-no-code = コードブロックは受け付けません
+
+## echo
+echo-whatis = 残りフィールドをそのまま返す
+echo-help =
+    ## 使用例
+    nombot に hello world と言わせる
+    ```
+    nom/echo/Hello, world!
+    ```
+    nombot に aehmttw と言わせる
+    ```
+    nom/echo sorted=yes/matthew
+    ```
+    nombot に world と hello を2行で言わせる
+    ```
+    nom/echo reversed=1 scope=line/Hello,
+    world!
+    ```
+    単語を並べ替えて言わせる。9 を 10 より前に、スウェーデン語の辞書順で
+    ```
+    nom/echo -sn scope=word collation=sv/zebra äpple a10 a9
+    ```
+    短縮形も使えます。行単位のときは空行が無視されます
+    ```
+    nom/echo -srl/
+    Bananas
+    Apples
+    Cherries
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/echo help
+    ```
+    残りフィールドをオプションに従って変換して返す
+    ```
+    nom/echo [-sSrRgcwl] sorted=[yes|no] reversed=[yes|no] scope=[grapheme|char|word|sentence|line]
+    nom/echo [--[no-]sorted] [--[no-]reversed] [--scope grapheme|char|word|sentence|line]
+    ```
+    既定の単位である書記素（grapheme）は、🇯🇵 や 👍🏽 のように1文字に見えるものです。`char` はコードポイントです。照合のオプションを指定しない限り、コードポイント順に並べ替えます
+    ```
+    nom/echo [-ni] collation=<locale> numeric=[yes|no] ignore-case=[yes|no]
+    nom/echo [--collation <locale>] [--[no-]numeric] [--[no-]ignore-case]
+    ```
+    `collation` は `de` や `sv` などのロケールです。`-n`（`numeric`）は 9 を 10 より前に並べ、`-i`（`ignore-case`）は大文字と小文字を区別しません。`collation` を指定しない場合はあなたのロケールに従います。
+echo-refuse-bomb = nombot はこのチャンネルを `nom/echo` で埋め尽くしません。:/
+echo-empty = nombot は空のメッセージを送れません。:/
+echo-positional = フラグ以外の位置引数は受け付けません。残りフィールドを使ってください。
+echo-option-repeated = オプション '{ $option }' が複数回指定されています
+echo-bad-scope = オプション 'scope' の値は { $scopes } のいずれかです
+echo-unknown-option = 不明なオプション { $option } です。使えるオプション: sorted, reversed, scope, collation, numeric, ignore-case
+echo-no-code = コードブロックは引数として受け付けません。残りフィールドを使ってください。
+
+## help
+help-whatis = すべてのコマンドを表示
+help-whatis-alias = `help` の別名
+help-no-arguments = 引数は（まだ）受け付けません
+help-script = このサーバーのスクリプト
+help-empty = ヘルプがありません。(???)
+help-scripts = `nom/{"{"}nom !; echo/done{"}"}` のように複数のコマンドをまとめて実行できます。`;` の代わりに `&&` を使うと、失敗した時点で残りを実行しません。
+complete-whatis = 残りフィールドの途中までのコマンドの続きを表示
+complete-no-arguments = 引数は受け付けません。残りフィールドを使ってください
+complete-none = 補完候補はありません。
+
+## set
+set-whatis = `$name` で展開される変数を設定
+set-whatis-unset = 変数を削除
+set-help =
+    ## 使用例
+    自分用の変数を設定して使う
+    ```
+    nom/set snack=cookies
+    nom/echo/I like $snack
+    ```
+    このチャンネルの全員に見える変数を設定する
+    ```
+    nom/set -c topic="lunch plans"
+    ```
+    ヒアドキュメントで複数行の変数を設定する。タグで始まる行で終わります
+    ```
+    nom/set poem=<<END
+    roses are red
+    cookies are sweet
+    END
+    ```
+    `$(...)` で他のコマンドの出力を使う
+    ```
+    nom/set snack=$(echo -s/cookies)
+    ```
+    `$` そのものを書くには `\$` と書く（またはシングルクォートを使う）
+    ```
+    nom/echo '$5' is \$5
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/set help
+    ```
+    ここで見える変数を一覧表示
+    ```
+    nom/set
+    ```
+    自分の変数、または `-c`（`--channel`）でチャンネルの変数を設定・削除する（サーバーでは「チャンネルの管理」または「サーバー管理」権限が必要）
+    ```
+    nom/set [-c] <name>=<value>*
+    nom/unset [-c] <name>*
+    ```
+    組み込み変数: $author, $channel, $now。自分の変数はチャンネルの変数より優先されます。
+set-bad-channel = --channel は { $expected } で指定してください（{ $value } ではなく）
+set-see-help = `nom/set help` を参照してください
+set-bad-name = { $name } は変数名ではありません（[A-Za-z_][0-9A-Za-z_]*）
+set-builtin = ${ $name } は組み込み変数です
+set-too-long = ${ $name } が { $max } バイトを超えています
+set-listed-user = ${ $name } = { $value }（あなた）
+set-listed-channel = ${ $name } = { $value }（チャンネル）
+set-none = 変数は設定されていません。
+set-done = 完了しました。
+set-too-many = 設定できる変数は { $max } 個までです
+set-channel-denied = チャンネルの変数を変更するには「チャンネルの管理」権限が必要です
+
+## admin
+admin-whatis = ボットを管理（オーナーのみ）
+admin-modules-about = 既知のモジュールと読み込み状態を一覧表示
+admin-enable-about = ボットを再起動せずにモジュールを読み込む
+admin-disable-about = ボットを再起動せずにモジュールを解除する
+admin-reload-about = ボットを再起動せずにモジュールを読み込み直す
+admin-scan-plugins-about = プラグインディレクトリの新しいプラグインをモジュールとして登録する（読み込むには `enable-module` が必要）
+admin-module-loaded = { $module } - 読み込み済み
+admin-module-not-loaded = { $module } - 未読み込み
+admin-refuse-unload = `admin` モジュールは解除できません
+admin-no-plugin-path = プラグインディレクトリが設定されていません
+admin-plugin-found = { $name } を見つけました
+admin-plugin-taken = { $path }: { $name } という名前のモジュールは既に存在します
+admin-no-new-plugins = 新しいプラグインはありません。
+admin-loaded = `{ $module }` を読み込みました（{ $count } 個のコマンド: { $names }）。
+admin-unloaded = `{ $module }` を解除しました（{ $count } 個のコマンド: { $names }）。
+registry-duplicate-module = モジュール { $module } はすでに登録されています
+registry-unknown-module = モジュール { $module } は存在しません
+registry-already-loaded = モジュール { $module } はすでに読み込まれています
+registry-not-loaded = モジュール { $module } は読み込まれていません
+registry-conflict = コマンド { $name } はすでにモジュール { $module } が使っています
+
+## locale
+locale-whatis = nombot が返信する言語を選ぶ
+locale-examples =
+    ## 使用例
+    nombot に日本語で返信させる
+    ```
+    nom/locale set ja
+    ```
+locale-available = 利用可能なロケール: { $available }。既定は { $default } です。
+locale-show-about = nombot があなたに返信するロケールを表示
+locale-set-about = 自分のロケールを選ぶ
+locale-unset-about = サーバーのロケールに従うよう戻す
+locale-set-server-about = ロケールを選んでいないこのサーバーの全員のロケールを選ぶ
+locale-unset-server-about = このサーバーのロケールを既定に戻す
+locale-current = あなたのロケールは `{ $locale }` です。
+locale-set = あなたのロケールを `{ $locale }` に設定しました。
+locale-server-set = このサーバーのロケールを `{ $locale }` に設定しました。
+locale-unknown = 不明なロケール { $locale } です。利用可能: { $available }
+locale-unset = あなたのロケールはサーバーの設定に従います。
+locale-server-unset = このサーバーのロケールを既定に戻しました。
+
+## config
+config-whatis = このサーバーでコマンドを有効・無効にしたり、使えるチャンネルを制限したりする
+config-examples =
+    ## 使用例
+    このサーバーで `echo` を無効にする
+    ```
+    nom/config disable=echo
+    ```
+    `nom` を2つのチャンネルだけで使えるようにする
+    ```
+    nom/config restrict=nom/#general #snacks
+    ```
+config-notes = `disable=<command>`、`enable=`、`restrict=`、`unrestrict=` は `nom/config command` のサブコマンドの短縮形です。コマンドの別名は常にまとめて変更されます。
+config-show-about = このサーバーのコマンド設定を表示（`nom/config` だけでも可）
+config-disable-about = このサーバーでコマンドを無効にする
+config-enable-about = 無効にしたコマンドを再び有効にする
+config-restrict-about = コマンドを指定したチャンネルだけで使えるようにする
+config-unrestrict-about = コマンドをすべてのチャンネルで使えるように戻す
+config-disabled = 無効: { $names }
+config-restricted = `{ $name }` を使えるチャンネル: { $channels }
+config-unrestricted = このサーバーではすべてのコマンドがどこでも有効です。
+config-no-command = コマンド { $name } は存在しません
+config-refuse-lock = `config` コマンドは制限できません
+config-expected-channels = 残りフィールドにチャンネルを指定してください
+config-updated = `{ $names }` を更新しました。
+
+## script
+script-whatis = このサーバーのスクリプトコマンドを管理
+script-examples =
+    ## 使用例
+    このサーバーにコマンド `greet` を追加する
+    ```
+    nom/script set name=greet/`Hello, {"${"}cmd.rest{"}"}!`
+    ```
+    使ってみる
+    ```
+    nom/greet/world
+    ```
+script-notes = スクリプトは Rhai（https://rhai.rs）で書きます。スクリプトからは `name`、`args`、`kw`、`rest`、`author`、`channel` のフィールドを持つ定数 `cmd` が見え、スクリプトの値が返信になります。ソースはコードブロックで囲んでも構いません。
+script-list-about = このサーバーのスクリプトを一覧表示
+script-show-about = スクリプトのソースを表示
+script-set-about = スクリプトを追加または置き換える
+script-remove-about = スクリプトを削除
+script-unavailable = スクリプトは利用できません
+script-none = このサーバーにはスクリプトがありません。
+script-by = <@{ $author }> の `{ $name }`
+script-not-found = { $name } という名前のスクリプトはありません
+script-bad-name = { $name } はコマンド名として使えません
+script-taken = { $name } は既にコマンドです
+script-saved = `{ $name }` を保存しました。
+script-removed = `{ $name }` を削除しました。
+script-set-not-saved = スクリプトは有効になりましたが、保存できませんでした
+script-remove-not-saved = スクリプトは削除されましたが、変更を保存できませんでした
+script-no-command = コマンド { $name } は存在しません
+script-failed = スクリプトが失敗しました
+
+## roll
+roll-whatis = ダイスを振る（例: `4d6kh3 + 2`）
+roll-help =
+    ## 使用例
+    6面ダイス4個を振って高い3個を残し、爆発する8面ダイス2個と 5 を足す
+    ```
+    nom/roll/4d6kh3 + 2d8! + 5
+    ```
+    有利で振り、ロールにラベルを付ける
+    ```
+    nom/roll/d20adv + 3 to hit, 2d6r1 + 3 damage
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/roll help
+    ```
+    ダイスを振る。複数のロールはカンマか改行で区切り、それぞれの後にラベルを付けられます
+    ```
+    nom/roll/<NdS[modifiers]|number> [+|- ...] [label]
+    ```
+    修飾子: `khN`/`klN` は高い/低い N 個を残し、`!` は最大の目が出たらもう一度振り、`rN` は N 以下の目を（1回だけ）振り直し、`adv`/`dis` は1個のダイスを2回振って高い/低い方を残します。`d%` は `d100` です。
+roll-no-arguments = 引数は受け付けません。ダイスは残りフィールドに書いてください
+roll-no-dice = 残りフィールドに `2d6` のようなダイスを書いてください
+roll-too-many-rolls = 一度に振れるのは { $max } ロールまでです
+roll-too-many-dice = 一度に振れるダイスは { $max } 個までです（{ $dice } 個ではなく）
+roll-too-many-terms = 1回のロールの項は { $max } 個までです
+roll-unexpected = `{ $term }` の後に予期しない { $char } があります
+roll-number-too-large = 数は { $max } までです（{ $number } ではなく）
+roll-expected-term-not = `2d6` のようなダイスか数を書いてください（{ $char } ではなく）
+roll-expected-term = `2d6` のようなダイスか数を書いてください
+roll-expected-sides = `d` の後に面の数を書いてください
+roll-dice-per-term = 1項のダイスは 1 から { $max } 個までです（{ $count } 個ではなく）
+roll-sides = ダイスの面は 1 から { $max } までです（{ $sides } ではなく）
+roll-advantage-single = `adv` と `dis` は1個のダイスにだけ使えます
+roll-explode-repeated = `!` が複数回指定されています
+roll-explode-one-sided = 1面ダイスは爆発できません
+roll-expected-reroll = `r` の後に振り直す最大の目を書いてください
+roll-reroll-repeated = `r` が複数回指定されています
+roll-reroll-every-face = d{ $sides } のすべての目を振り直すことはできません
+roll-keep-repeated = `kh`、`kl`、`adv`、`dis` は1項に1つだけです
+roll-keep-too-many = { $count } 個のダイスから { $keep } 個は残せません
+
+## remind
+remind-whatis = 後で何かをリマインドしてもらう
+remind-help =
+    ## 使用例
+    2時間半後にここでリマインドしてもらう
+    ```
+    nom/remind in=2h30m/stand-up notes
+    ```
+    タイムゾーンの時刻に DM でリマインドしてもらう
+    ```
+    nom/remind --dm at="2026-11-01 09:00" tz=Europe/Berlin/renew the passport
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/remind help
+    ```
+    リマインダーを設定する。このチャンネルに届きます。`-d`（または `--dm`）を付けると DM で届きます
+    ```
+    nom/remind [-d] in=<duration>/<text>
+    nom/remind [-d] at=<time> [tz=<time zone>]/<text>
+    ```
+    保留中のリマインダーを一覧表示するか、1つを取り消す
+    ```
+    nom/remind list
+    nom/remind cancel <id>
+    ```
+    期間は `1w2d3h4m5s` のように書きます。時刻は `2026-11-01 09:00`、または次の9時なら `09:00` のように書きます。`tz` で tz データベースのタイムゾーンを指定しない限り UTC です。
+remind-bad-dm = --dm は { $expected } でなければなりません（{ $value } ではなく）
+remind-not-found = リマインダー #{ $id } はありません
+remind-expected-subcommand = `in=`、`at=`、`list`、`cancel` のいずれかを指定してください。`nom/remind help` を参照してください
+remind-expected-text = リマインドする内容を残りフィールドに書いてください
+remind-too-long = リマインダーは { $max } 文字までです
+remind-no-rest = 残りフィールドを受け付けるのはリマインダーの設定だけです
+remind-too-far-ahead = リマインダーは1年先までしか設定できません
+remind-in-zero = in= は 0 より長くしてください
+remind-unknown-tz = 不明なタイムゾーン { $tz } です。Europe/Berlin のような名前を使ってください
+remind-bad-time = "2026-11-01 09:00" や "09:00" のような時刻を書いてください（{ $at } ではなく）
+remind-passed = { $at } は既に過ぎています
+remind-no-such-time = { $time } は { $tz } に存在しません（時計が進められます）
+remind-too-many = 保留できるリマインダーは { $max } 個までです
+remind-by-dm = DM で
+remind-in-channel = <#{ $channel }> で
+remind-none = リマインダーはありません。
+remind-cancelled = リマインダー `#{ $id }` を取り消しました。
+remind-cancel-not-saved = リマインダーは取り消されましたが、変更を保存できませんでした
+remind-set = リマインダー `#{ $id }` を <t:{ $due }:F>（<t:{ $due }:R>）に設定しました。
+remind-set-not-saved = リマインダーは設定されましたが保存できなかったため、ボットが再起動すると失われます
+remind-delivery = <@{ $user }>、リマインドです: { $text }
+remind-delivery-late = <@{ $user }>、リマインドです（遅れました。予定は <t:{ $due }:R> でした）: { $text }
+
+## poll
+poll-whatis = 質問して票を数える
+poll-help =
+    ## 使用例
+    1時間、お昼に何を食べるか聞く
+    ```
+    nom/poll duration=1h/Lunch? | Pizza | Sushi | Tacos
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/poll help
+    ```
+    2〜10個の選択肢がある投票を投稿する。期間は1分から1週間まで（既定は1時間）
+    ```
+    nom/poll [duration=<duration>]/<question> | <option> | <option>*
+    ```
+    このチャンネルで受付中の投票を一覧表示するか、1つを早めに締め切る（作成者かサーバー管理者のみ）
+    ```
+    nom/poll list
+    nom/poll close <id>
+    ```
+    選択肢の番号のリアクションで投票します。新しい票は前の票を置き換えます。結果は投票の締め切り時に投稿されます。
+poll-expected-question = 残りフィールドに `<question> | <option> | <option>` を書いてください
+poll-question-too-long = 質問が { $max } 文字を超えています
+poll-option-count = 選択肢は 2 から { $max } 個までです（{ $count } 個ではなく）
+poll-empty-option = 選択肢を空にすることはできません
+poll-option-too-long = 選択肢は { $max } 文字までです
+poll-not-open = このチャンネルに受付中の投票 #{ $id } はありません
+poll-duration-range = 投票の期間は1分から1週間までです
+poll-see-help = `nom/poll help` を参照してください
+poll-no-rest = 残りフィールドを受け付けるのは新しい投票だけです
+poll-none = このチャンネルで受付中の投票はありません。
+poll-listed = `#{ $id }` { $question }（<t:{ $closes }:R> に締め切り、{ $link }）
+poll-close-denied = 投票を早めに締め切れるのは作成者かサーバー管理者だけです
+poll-just-closed = 投票 #{ $id } はちょうど締め切られました
+poll-close-not-saved = 投票は締め切られましたが、変更を保存できませんでした
+poll-too-many = 1つのチャンネルで受付できる投票は { $max } 個までです
+poll-unavailable = 投票は利用できません
+poll-post-failed = 投票を投稿できません
+poll-posted =
+    📊 **{ $question }**
+    { $options }
+    リアクションで投票してください（1人1票）。<t:{ $closes }:R> に締め切ります。投票 `#{ $id }`。
+poll-results-none = 📊 **{ $question }** は票がないまま締め切られました。
+poll-results =
+    📊 **{ $question }** は { $total } 票で締め切られました:
+    { $lines }
+
+## quote
+quote-whatis = メッセージを保存して呼び出す
+quote-help =
+    ## 使用例
+    メッセージを保存する: そのメッセージに次の返信をする
+    ```
+    nom/quote add
+    ```
+    誰かの引用、またはクッキーに触れた引用を呼び出す
+    ```
+    nom/quote by=@someone
+    nom/quote search/cookies
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/quote help
+    ```
+    返信先のメッセージを引用として保存する。メッセージに { $emoji } でリアクションしても同じです
+    ```
+    nom/quote add
+    ```
+    ランダムな引用、引用 #<id>、誰かのランダムな引用、またはすべての単語を含むランダムな引用を呼び出す
+    ```
+    nom/quote
+    nom/quote id=<id>
+    nom/quote by=<user>
+    nom/quote search/<words>
+    ```
+    引用はサーバーごとに保存されます。
+quote-expected-words = 検索する単語を残りフィールドに書いてください
+quote-not-found = 引用 #{ $id } はありません
+quote-by-user = by= はユーザーでなければなりません
+quote-see-help = `nom/quote help` を参照してください
+quote-no-rest = 残りフィールドを受け付けるのは `search` だけです
+quote-guild-only = 引用はサーバーでのみ保存されます
+quote-expected-reply = 保存するメッセージに `nom/quote add` で返信するか、{ $emoji } でリアクションしてください
+quote-no-text = 引用するテキストがありません
+quote-already = そのメッセージは既に引用 #{ $id } です
+quote-too-many = 保存できる引用は { $max } 個までです
+quote-saved = 引用 `#{ $id }` として保存しました。
+quote-not-saved = 引用を保存できませんでした
+quote-none = まだ引用がありません。`nom/quote add` か { $emoji } で保存してください
+quote-none-by = その人の引用はありません
+quote-none-found = すべての単語を含む引用はありません
+quote-shown =
+    { $text }
+    — **{ $name }**、<t:{ $time }:D> · [元のメッセージ]({ $link }) · `#{ $id }`
+quote-also = ほかに { $ids }。
+quote-also-more = ほかに { $ids }、さらに { $count } 件。
+
+## sed
+sed-whatis = 最後のメッセージを正規表現で訂正する
+sed-help =
+    ## 使用例
+    最後のメッセージのタイプミスを直す
+    ```
+    nom/s/teh/the/g
+    ```
+    別の区切り文字で2つの単語を入れ替える
+    ```
+    nom/sed/s|(\w+) (\w+)|\2 \1|
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/sed help
+    ```
+    このチャンネルでの最後のメッセージを、正規表現の最初の一致（フラグ `g` ならすべての一致）を置き換えて投稿する。フラグ `i` は大文字と小文字を区別しません
+    ```
+    nom/s/<regex>/<replacement>/[flags]
+    nom/sed/s<delimiter><regex><delimiter><replacement><delimiter>[flags]
+    ```
+    置換文字列では `&` が一致全体、`\1` から `\9` がグループです。ほかの残りフィールドと同じく、正規表現の `$` は `\$` と書いてください。コマンドはメッセージとして数えません。
+sed-expected-s = `s/<regex>/<replacement>/` を書いてください
+sed-expected-delimiter = `s` の後に `/` のような区切り文字を書いてください
+sed-expected-fields = `s{ $d }<regex>{ $d }<replacement>{ $d }[flags]` を書いてください
+sed-no-group = 正規表現にグループ { $group } はありません
+sed-empty = 正規表現が空です
+sed-too-long = 正規表現が { $max } 文字を超えています
+sed-unknown-flag = 不明なフラグ { $flag } です。フラグは `g` と `i` です
+sed-too-big = 正規表現が大きすぎます
+sed-invalid = 無効な正規表現です: { $error }
+sed-no-match = 正規表現が最後のメッセージに一致しません
+sed-result-too-long = 結果が { $max } 文字を超えています
+sed-see-help = `nom/sed help` を参照してください
+sed-no-recent = このチャンネルに最近のメッセージがありません
+sed-meant = **{ $name }** の訂正: { $text }
+
+## tr
+tr-whatis = 残りフィールドを変換する: 大文字小文字、暗号、エンコードなど
+tr-help =
+    ## 使用例
+    叫ぶ
+    ```
+    nom/tr upper/nom nom nom
+    ```
+    単語を毎回同じようにシャッフルしてから、からかい口調にする
+    ```
+    nom/tr -w seed=42 shuffle mock/the quick brown fox
+    ```
+    秘密を解読する
+    ```
+    nom/tr unbase64 rot13/QWJ6IGFieg==
+    ```
+    ## 使い方
+    ヘルプ（このメッセージ）を表示
+    ```
+    nom/tr help
+    ```
+    残りフィールドに変換を左から右へ適用する
+    ```
+    nom/tr [-gcwl] [scope=<scope>] [seed=<n>] [collation=<locale>] [-ni] <transform>+/<text>
+    nom/tr [--scope <scope>] [--seed <n>] [--collation <locale>] [--[no-]numeric] [--[no-]ignore-case] <transform>+/<text>
+    ```
+    `scope` は `reverse`、`sort`、`shuffle`、`dedupe` が並べ替える単位です: 書記素（既定。🇯🇵 のように1文字に見えるもの）、char（コードポイント）、単語、文、行。`seed` を指定すると `shuffle` と `zalgo` の結果が再現できます。`sort` は `collation` で `de` や `sv` のようなロケールを指定しない限りコードポイント順です。`-n`（`numeric`）は 9 を 10 より前に、`-i`（`ignore-case`）は大文字と小文字を区別せずに並べます。`collation` で別のロケールを指定しない限り、あなたのロケールを使います。
+    ## 変換
+tr-unknown-transform = 不明な変換 { $name } です。`nom/tr help` を参照してください
+tr-option-repeated = オプション '{ $option }' が複数回指定されています
+tr-bad-scope = オプション 'scope' は { $scopes } のいずれかです
+tr-negative-seed = オプション 'seed' は負にできません
+tr-unknown-option = 不明なオプション { $option } です。使えるオプション: scope, seed, collation, numeric, ignore-case
+tr-no-code = コードブロックは引数として受け付けません。残りフィールドを使ってください
+tr-no-transforms = 変換を1つ以上指定してください。`nom/tr help` を参照してください
+tr-too-many = 一度に使える変換は { $max } 個までです
+tr-grew-too-long = テキストが長くなりすぎました
+tr-too-long = 結果が { $max } 文字を超えています
+tr-empty = 結果が空です
+tr-refuse-commands = nombot は自分のコマンドをコマンドに変換しません
+transform-reverse = 単位の順序を逆にする
+transform-sort = 単位を並べ替える
+transform-shuffle = 単位をシャッフルする
+transform-dedupe = 繰り返された単位を取り除く
+transform-upper = 大文字（UPPERCASE）
+transform-lower = 小文字（lowercase）
+transform-title = 単語の先頭を大文字（Title Case）
+transform-mock = からかい口調（mOcKiNg CaSe）
+transform-leet = リート（l337sp34k）
+transform-zalgo = 結合文字を加える
+transform-rot13 = 文字を 13 ずらす
+transform-morse = モールス信号に
+transform-unmorse = モールス信号から
+transform-base64 = base64 に
+transform-unbase64 = base64 から
+transform-hex = 16進バイトに
+transform-unhex = 16進バイトから
+transform-url = パーセントエンコード
+transform-unurl = パーセントデコード
+transform-codepoint-options = numeric と ignore-case にはロケールの照合順序が必要です
+transform-bad-locale = { $tag } は `en` や `de-CH` のようなロケールではありません
+transform-no-collator = { $tag } の照合順序を使えません: { $error }
+transform-not-utf8 = デコードしたバイト列が UTF-8 テキストではありません
+transform-base64-digit = { $char } は base64 の文字ではありません
+transform-base64-end = base64 テキストは1文字だけで終わることはできません
+transform-hex-pairs = 16進バイトは2桁ずつです
+transform-hex-byte = { $pair } は16進バイトではありません
+transform-url-digits = '%' の後には16進数字が2つ必要です
+transform-url-byte = %{ $hex } はパーセントエンコードされたバイトではありません
+transform-no-morse = { $char } にはモールス信号がありません
+transform-not-morse = { $code } はモールス信号ではありません
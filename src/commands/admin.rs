@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use crate::defn::command::{
+    Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::{ CommandMapTmk, ConfigTmk };
+use crate::defn::registry::load_module;
+use crate::defn::subcommand::{ self, Node, Permission, Resolved, Schema, Subcommand };
+use crate::i18n;
+use crate::nom_util as util;
+use crate::plugin;
+
+#[derive(Clone, Copy)]
+enum Sc { // subcommand
+    Modules,
+    Enable,
+    Disable,
+    Reload,
+    ScanPlugins,
+}
+
+const MODULE: Schema = Schema { args: &["module"], ..Schema::NONE };
+
+const SUBCOMMANDS: &[Subcommand<Sc>] = &[
+    Subcommand {
+        name: "modules", about: "admin-modules-about",
+        permission: Permission::Owner, schema: Schema::NONE, node: Node::Run(Sc::Modules),
+    },
+    Subcommand {
+        name: "enable-module", about: "admin-enable-about",
+        permission: Permission::Owner, schema: MODULE, node: Node::Run(Sc::Enable),
+    },
+    Subcommand {
+        name: "disable-module", about: "admin-disable-about",
+        permission: Permission::Owner, schema: MODULE, node: Node::Run(Sc::Disable),
+    },
+    Subcommand {
+        name: "reload-module", about: "admin-reload-about",
+        permission: Permission::Owner, schema: MODULE, node: Node::Run(Sc::Reload),
+    },
+    Subcommand {
+        name: "scan-plugins", about: "admin-scan-plugins-about",
+        permission: Permission::Owner, schema: Schema::NONE, node: Node::Run(Sc::ScanPlugins),
+    },
+];
+
+pub struct AdminHandler;
+
+#[async_trait]
+impl CommandHandler for AdminHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "admin-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["admin"]
+    }
+    fn spec(&self, _: &str) -> Spec { subcommand::spec(SUBCOMMANDS) }
+    async fn call(&self, cmd: Command, ctx: Context, msg: Message) {
+        let inv = match subcommand::resolve(SUBCOMMANDS, cmd, &ctx, &msg).await {
+            Ok(Resolved::Run(inv)) => inv,
+            Ok(Resolved::Help { usage, .. }) => {
+                let _: Option<_> = util::try_reply(&ctx, &msg, usage).await;
+                return;
+            }
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nadmin: {why}\n```")).await;
+                return;
+            }
+        };
+        let (config, cm) = {
+            let data = ctx.data.read().await;
+            (
+                data.get::<ConfigTmk>().expect("Config does not exist!").clone(),
+                data.get::<CommandMapTmk>().expect("Command map does not exist!").clone(),
+            )
+        };
+
+        let locale = util::locale(&ctx, &msg).await;
+        let modules_message = |key, m: &str, names: Vec<&str>| i18n::tr(&locale, key, &[
+            ("module", m.into()), ("count", names.len().into()), ("names", names.join(", ").into()),
+        ]);
+        let reply: Result<String, String> = match inv.action {
+            Sc::Modules => {
+                let cm = cm.read().await;
+                let v: Vec<_> = cm.modules()
+                    .map(|(m, loaded)| i18n::tr(&locale, if loaded { "admin-module-loaded" } else { "admin-module-not-loaded" }, &[("module", m.into())]))
+                    .collect();
+                Ok(format!("```\n{}\n```", v.join("\n")))
+            }
+            Sc::Disable if inv.param("module") == "admin" => Err(format!("```\nadmin: {}\n```", i18n::tr(&locale, "admin-refuse-unload", &[]))),
+            Sc::Disable => match cm.write().await.remove(inv.param("module")) {
+                Ok(names) => Ok(modules_message("admin-unloaded", inv.param("module"), names)),
+                Err(e) => Err(format!("```\nadmin: {}\n```", e.tr(&locale))),
+            },
+            Sc::ScanPlugins => match &config.plugin_path {
+                None => Err(format!("```\nadmin: {}\n```", i18n::tr(&locale, "admin-no-plugin-path", &[]))),
+                Some(dir) => match plugin::scan(dir) {
+                    Err(e) => Err(format!("```\nadmin: {e}\n```")),
+                    Ok(found) => {
+                        let mut cm = cm.write().await;
+                        let lines: Vec<_> = found.into_iter().map(|p| match p {
+                            // a new library whose module name is already taken is left alone, and
+                            // loaded again by a later scan once it is renamed
+                            Ok(p) => match cm.add_module(&p.name[..], p.factory()) {
+                                Ok(()) => i18n::tr(&locale, "admin-plugin-found", &[("name", p.name.clone().into())]),
+                                Err(_) => {
+                                    plugin::forget(&p.path);
+                                    i18n::tr(&locale, "admin-plugin-taken", &[
+                                        ("path", p.path.display().to_string().into()), ("name", p.name.clone().into()),
+                                    ])
+                                }
+                            },
+                            Err(e) => e,
+                        }).collect();
+                        if lines.is_empty() {
+                            Ok(i18n::tr(&locale, "admin-no-new-plugins", &[]))
+                        } else {
+                            Ok(format!("```\n{}\n```", lines.join("\n")))
+                        }
+                    }
+                },
+            },
+            Sc::Enable | Sc::Reload => {
+                let (m, replace) = (inv.param("module"), matches!(inv.action, Sc::Reload));
+                match load_module(&cm, ctx.data.clone(), m, replace, |n| config.is_enabled(n)).await {
+                    Ok(names) => Ok(modules_message("admin-loaded", m, names)),
+                    Err(e) => Err(format!("```\nadmin: {}\n```", e.tr(&locale))),
+                }
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use crate::complete;
+use crate::i18n;
+use crate::defn::command::{ Command, Context, Message, ClientData, CommandHandler };
+use crate::defn::globals::{ CommandMapTmk, GuildSettingsTmk, ScriptsTmk };
+use crate::nom_util as util;
+
+pub struct CompleteHandler;
+
+#[async_trait]
+impl CommandHandler for CompleteHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "complete-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["complete"]
+    }
+    async fn call(&self, Command { name: _, args, rest: input }: Command, ctx: Context, msg: Message) {
+        let locale = util::locale(&ctx, &msg).await;
+        if !args.is_empty() {
+            let why = i18n::tr(&locale, "complete-no-arguments", &[]);
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\ncomplete: {why}\n```")).await;
+            return;
+        }
+        let completion = {
+            let data = ctx.data.read().await;
+            let cm = data
+                .get::<CommandMapTmk>().expect("Command map does not exist!")
+                .read().await;
+            let settings = data
+                .get::<GuildSettingsTmk>().expect("Guild settings do not exist!")
+                .read().await;
+            let mut names: Vec<String> = cm.names().map(String::from).collect();
+            if let (Some(g), Some(scripts), Some(_)) = (msg.guild_id, data.get::<ScriptsTmk>(), cm.get("script")) {
+                names.extend(scripts.read().await.scripts.get(&g.0).into_iter().flat_map(|s| s.keys().cloned()));
+            }
+            names.retain(|n| settings.is_available(msg.guild_id, msg.channel_id, n));
+            names.sort();
+            let names: Vec<&str> = names.iter().map(|n| &n[..]).collect();
+            complete::complete(&input, &names, |n| cm.get(n).map(|h| h.spec(n)))
+        };
+        let reply = if completion.candidates.is_empty() {
+            i18n::tr(&locale, "complete-none", &[])
+        } else {
+            let head = &input[..completion.start];
+            let lines: Vec<_> = completion.candidates.iter().map(|c| format!("{head}{c}")).collect();
+            format!("```\n{}\n```", lines.join("\n"))
+        };
+        let _: Option<_> = util::try_reply(&ctx, &msg, reply).await;
+    }
+}
@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::{ CommandMapTmk, GuildSettingsTmk };
+use crate::defn::subcommand::{ self, Node, Permission, Resolved, Schema, Subcommand };
+use crate::i18n;
+use crate::nom_util as util;
+
+#[derive(Clone, Copy)]
+enum Sc { // subcommand
+    Show,
+    Disable,
+    Enable,
+    Restrict,
+    Unrestrict,
+}
+
+const COMMAND: Schema = Schema { args: &["command"], ..Schema::NONE };
+
+const SUBCOMMANDS: &[Subcommand<Sc>] = &[
+    Subcommand {
+        name: "show", about: "config-show-about",
+        permission: Permission::Everyone, schema: Schema::NONE, node: Node::Run(Sc::Show),
+    },
+    Subcommand {
+        name: "command", about: "", permission: Permission::GuildAdmin, schema: Schema::NONE,
+        node: Node::Sub(&[
+            Subcommand {
+                name: "disable", about: "config-disable-about",
+                permission: Permission::GuildAdmin, schema: COMMAND, node: Node::Run(Sc::Disable),
+            },
+            Subcommand {
+                name: "enable", about: "config-enable-about",
+                permission: Permission::GuildAdmin, schema: COMMAND, node: Node::Run(Sc::Enable),
+            },
+            Subcommand {
+                name: "restrict", about: "config-restrict-about",
+                permission: Permission::GuildAdmin, schema: Schema { rest: Some("channels"), ..COMMAND },
+                node: Node::Run(Sc::Restrict),
+            },
+            Subcommand {
+                name: "unrestrict", about: "config-unrestrict-about",
+                permission: Permission::GuildAdmin, schema: COMMAND, node: Node::Run(Sc::Unrestrict),
+            },
+        ]),
+    },
+];
+
+// `nom/config` alone shows the settings, and `nom/config disable=<command>` and the like are short
+// for `nom/config command disable <command>`.
+fn desugar(cmd: Command) -> Command {
+    match &cmd.args[..] {
+        [] if cmd.rest.is_empty() => Command { args: vec![Arg::Pos("show".into())], ..cmd },
+        [Arg::Kw(k, v)] if ["disable", "enable", "restrict", "unrestrict"].contains(&&k[..]) => {
+            let args = vec![Arg::Pos("command".into()), Arg::Pos(k.clone()), Arg::Pos(v.clone())];
+            Command { args, ..cmd }
+        }
+        _ => cmd,
+    }
+}
+
+pub struct ConfigHandler;
+
+#[async_trait]
+impl CommandHandler for ConfigHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "config-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["config"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        let mut spec = subcommand::spec(SUBCOMMANDS);
+        spec.keys = vec![("disable", &[]), ("enable", &[]), ("restrict", &[]), ("unrestrict", &[])];
+        spec
+    }
+    async fn call(&self, cmd: Command, ctx: Context, msg: Message) {
+        let locale = util::locale(&ctx, &msg).await;
+        let inv = match subcommand::resolve(SUBCOMMANDS, desugar(cmd), &ctx, &msg).await {
+            Ok(Resolved::Run(inv)) => inv,
+            Ok(Resolved::Help { path, usage }) => {
+                let help = match path.is_empty() {
+                    true => format!("{}\n{usage}\n{}", i18n::tr(&locale, "config-examples", &[]), i18n::tr(&locale, "config-notes", &[])),
+                    false => usage,
+                };
+                let _: Option<_> = util::try_reply(&ctx, &msg, help).await;
+                return;
+            }
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nconfig: {why}\n```")).await;
+                return;
+            }
+        };
+        let Some(guild_id) = msg.guild_id else {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nconfig: {}\n```", i18n::tr(&locale, "error-guild-only", &[]))).await;
+            return;
+        };
+        let store = {
+            let data = ctx.data.read().await;
+            data.get::<GuildSettingsTmk>().expect("Guild settings do not exist!").clone()
+        };
+
+        if let Sc::Show = inv.action {
+            let settings = store.read().await;
+            let reply = match settings.commands.get(&guild_id.0) {
+                Some(gc) if !gc.disabled.is_empty() || !gc.channels.is_empty() => {
+                    let mut lines = vec![];
+                    if !gc.disabled.is_empty() {
+                        let names = gc.disabled.iter().cloned().collect::<Vec<_>>().join(", ");
+                        lines.push(i18n::tr(&locale, "config-disabled", &[("names", names.into())]));
+                    }
+                    for (name, channels) in gc.channels.iter() {
+                        let channels: Vec<_> = channels.iter().map(|c| format!("<#{c}>")).collect();
+                        lines.push(i18n::tr(&locale, "config-restricted", &[("name", name.clone().into()), ("channels", channels.join(" ").into())]));
+                    }
+                    lines.join("\n")
+                }
+                _ => i18n::tr(&locale, "config-unrestricted", &[]),
+            };
+            let _: Option<_> = util::try_reply(&ctx, &msg, reply).await;
+            return;
+        }
+
+        // resolve the target command to every name bound to the same handler
+        let target = inv.param("command");
+        let names: Vec<&str> = {
+            let data = ctx.data.read().await;
+            let cm = data
+                .get::<CommandMapTmk>().expect("Command map does not exist!")
+                .read().await;
+            cm.aliases(target)
+        };
+        if names.is_empty() {
+            let why = i18n::tr(&locale, "config-no-command", &[("name", format!("{target:?}").into())]);
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nconfig: {why}\n```")).await;
+            return;
+        }
+        if names.contains(&"config") {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nconfig: {}\n```", i18n::tr(&locale, "config-refuse-lock", &[]))).await;
+            return;
+        }
+        let channels = match inv.action {
+            Sc::Restrict => {
+                let parsed: Option<Vec<u64>> = inv.rest.split_whitespace().map(|s| util::parse_channel(s).map(|c| c.0)).collect();
+                match parsed {
+                    Some(cs) if !cs.is_empty() => cs,
+                    _ => {
+                        let why = i18n::tr(&locale, "config-expected-channels", &[]);
+                        let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nconfig: {why}\n```")).await;
+                        return;
+                    }
+                }
+            }
+            _ => vec![],
+        };
+
+        let result = store.update(|settings| {
+            let gc = settings.commands.entry(guild_id.0).or_default();
+            for n in names.iter() {
+                match inv.action {
+                    Sc::Disable => { gc.disabled.insert(n.to_string()); }
+                    Sc::Enable => { gc.disabled.remove(*n); }
+                    Sc::Restrict => { gc.channels.insert(n.to_string(), channels.iter().copied().collect()); }
+                    Sc::Unrestrict => { gc.channels.remove(*n); }
+                    Sc::Show => unreachable!(),
+                }
+            }
+            if gc.disabled.is_empty() && gc.channels.is_empty() {
+                settings.commands.remove(&guild_id.0);
+            }
+        }).await;
+        let reply = match result {
+            Ok(()) => Ok(i18n::tr(&locale, "config-updated", &[("names", names.join("`, `").into())])),
+            Err(why) => {
+                tracing::error!(error = %why, "cannot save guild settings");
+                Err(format!("```\nconfig: {}\n```", i18n::tr(&locale, "error-not-saved", &[])))
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::defn::subcommand::Invocation;
+    use crate::nom_args;
+
+    fn select(s: &str) -> Invocation<Sc> {
+        match subcommand::select(SUBCOMMANDS, desugar(nom_args::parse(s).unwrap()), "en") {
+            Ok((Resolved::Run(inv), _)) => inv,
+            _ => panic!("{s}"),
+        }
+    }
+
+    #[test]
+    fn test_desugar() {
+        let inv = select("config disable=echo");
+        assert!(matches!(inv.action, Sc::Disable));
+        assert_eq!((&inv.path[..], inv.param("command")), (&["command", "disable"][..], "echo"));
+        let inv = select("config restrict=nom/#general #snacks");
+        assert!(matches!(inv.action, Sc::Restrict));
+        assert_eq!((inv.param("command"), &inv.rest[..]), ("nom", "#general #snacks"));
+        assert!(matches!(select("config unrestrict=nom").action, Sc::Unrestrict));
+        assert!(matches!(select("config command enable echo").action, Sc::Enable));
+        assert!(matches!(select("config").action, Sc::Show));
+    }
+}
@@ -1,64 +1,62 @@
 use async_trait::async_trait;
 use crate::defn::command::{
-    Arg, Command, Context, Message, ClientData,
+    Arg, Command, Context, Message, ClientData, Spec,
     CommandHandler,
 };
+use crate::i18n;
+use crate::nom_args::{ self, OptSpec };
 use crate::nom_util as util;
+use crate::transform::{ self, Collation, Scope, Transform };
 
-const HELP_STR: &'static str =
-"## Examples
-Let nombot say hello world
-```
-nom/echo/Hello, world!
-```
-Let nombot say aehmttw
-```
-nom/echo sorted=1/matthew
-```
-Let nombot say world hello on two lines
-```
-nom/echo reversed=1 scope=line/Hello,
-world!
-```
-Short hands are supported, and empty lines are ignored when using line scope
-```
-nom/echo -srl/
-Bananas
-Apples
-Cherries
-```
-## Usage
-Print help (this message)
-```
-nom/echo help
-```
-Echo-back the rest-field, transformed according to options
-```
-nom/echo [-sSrRlc] sorted=[0|1] reversed=[0|1] scope=[char|line]
-```";
+const OPTIONS: &[OptSpec] = &[
+    OptSpec { name: "sorted", shorts: &[('s', "yes"), ('S', "no")], switch: true },
+    OptSpec { name: "reversed", shorts: &[('r', "yes"), ('R', "no")], switch: true },
+    OptSpec { name: "scope", shorts: &[('l', "line"), ('c', "char"), ('g', "grapheme"), ('w', "word")], switch: false },
+    OptSpec { name: "collation", shorts: &[], switch: false },
+    OptSpec { name: "numeric", shorts: &[('n', "yes")], switch: true },
+    OptSpec { name: "ignore-case", shorts: &[('i', "yes")], switch: true },
+];
 
 pub struct EchoHandler;
 
 #[async_trait]
 impl CommandHandler for EchoHandler {
-    async fn whatis(&self, _: &str) -> String { "Echo-back the rest-field".into() }
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "echo-whatis", &[]) }
     async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
         vec!["echo"]
     }
+    fn spec(&self, _: &str) -> Spec {
+        Spec {
+            subcommands: vec![("help", Spec::default())],
+            keys: vec![
+                ("sorted", &["yes", "no"]), ("reversed", &["yes", "no"]), ("scope", Scope::NAMES),
+                ("collation", &[]), ("numeric", &["yes", "no"]), ("ignore-case", &["yes", "no"]),
+            ],
+            options: OPTIONS,
+        }
+    }
     async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
         // parse the command a second time for subcommands, flags, and options
-        enum Scope {
-            Lines,
-            Characters,
-        }
         enum Sc { // subcommand
-            Echo { sorted: bool, reversed: bool, scope: Scope },
+            Echo { sorted: bool, reversed: bool, scope: Scope, collation: Option<String>, numeric: bool, ignore_case: bool },
             Help,
             Error { why: String },
         }
+        let locale = util::locale(&ctx, &msg).await;
+        let args: Vec<Arg> = match nom_args::take_options(args, OPTIONS) {
+            // flags count the same as `key=value`
+            Ok((opts, args)) => opts.into_iter().map(|(k, v)| Arg::Kw(k.into(), v)).chain(args).collect(),
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```echo: {}```", why.tr(&locale))).await;
+                return;
+            }
+        };
         let mut sorted_opt = None;
         let mut reversed_opt = None;
         let mut scope_opt = None;
+        let mut collation_opt = None;
+        let mut numeric_opt = None;
+        let mut ignore_case_opt = None;
         let mut itr = args.iter();
         let sc = 'Z: loop {
             match itr.next() {
@@ -66,82 +64,64 @@ impl CommandHandler for EchoHandler {
                     break Sc::Echo {
                         sorted: sorted_opt.unwrap_or(false),
                         reversed: reversed_opt.unwrap_or(false),
-                        scope: scope_opt.unwrap_or(Scope::Characters),
+                        scope: scope_opt.unwrap_or(Scope::Grapheme),
+                        collation: collation_opt,
+                        numeric: numeric_opt.unwrap_or(false),
+                        ignore_case: ignore_case_opt.unwrap_or(false),
                     },
-                Some(Arg::Pos(s)) if s.chars().count() >= 1 && s.chars().nth(0).unwrap() == '-' => {
-                    let mut itr = s.chars();
-                    let _ = itr.next();
-                    for c in itr {
-                        match c {
-                            's' | 'S' if sorted_opt.is_some() =>
-                                break 'Z Sc::Error { why: "the 'sorted' option is set multiple times".into() },
-                            's' | 'S' => { sorted_opt = Some(c == 's'); }
-                            'r' | 'R' if reversed_opt.is_some() =>
-                                break 'Z Sc::Error { why: "the 'reversed' option is set multiple times".into() },
-                            'r' | 'R' => { reversed_opt = Some(c == 'r'); }
-                            'l' | 'c' if scope_opt.is_some() =>
-                                break 'Z Sc::Error { why: "the 'scope' option is set multiple times".into() },
-                            'l' | 'c' => {
-                                scope_opt = Some(if c == 'c' { Scope::Characters } else { Scope::Lines });
-                            }
-                            _ =>
-                                break 'Z Sc::Error {
-                                    why: format!("unrecognized flag shorthand {c:?}; available shorthands: sSrRlc"),
-                                },
-                        }
-                    }
-                }
                 Some(Arg::Pos(s)) if s == "help" => break Sc::Help,
                 Some(Arg::Pos(_)) =>
                     break Sc::Error {
-                        why: format!("does not accept non-flag positional arguments; use the rest-field instead."),
+                        why: i18n::tr(&locale, "echo-positional", &[]),
                     },
                 Some(Arg::Kw(k, _)) if k == "sorted" && sorted_opt.is_some() =>
-                    break Sc::Error { why: "the 'sorted' option is set multiple times".into() },
-                Some(Arg::Kw(k, v)) if k == "sorted" => {
-                    let s = v == "1";
-                    if s || v == "0" {
-                        sorted_opt = Some(s);
-                    } else {
-                        break 'Z Sc::Error { why: "the 'sorted' option is boolean (0 or 1)".into() };
-                    }
+                    break Sc::Error { why: i18n::tr(&locale, "echo-option-repeated", &[("option", "sorted".into())]) },
+                Some(a @ Arg::Kw(k, _)) if k == "sorted" => match a.literal() {
+                    Ok(s) => { sorted_opt = Some(s); }
+                    Err(why) => break 'Z Sc::Error { why: why.tr(&locale) },
                 }
                 Some(Arg::Kw(k, _)) if k == "reversed" && reversed_opt.is_some() =>
-                    break Sc::Error { why: "the 'reversed' option is set multiple times".into() },
-                Some(Arg::Kw(k, v)) if k == "reversed" => {
-                    let r = v == "1";
-                    if r || v == "0" {
-                        reversed_opt = Some(r);
-                    } else {
-                        break 'Z Sc::Error { why: "the 'reversed' option is boolean (0 or 1)".into() };
-                    }
+                    break Sc::Error { why: i18n::tr(&locale, "echo-option-repeated", &[("option", "reversed".into())]) },
+                Some(a @ Arg::Kw(k, _)) if k == "reversed" => match a.literal() {
+                    Ok(r) => { reversed_opt = Some(r); }
+                    Err(why) => break 'Z Sc::Error { why: why.tr(&locale) },
                 }
                 Some(Arg::Kw(k, _)) if k == "scope" && scope_opt.is_some() => {
-                    break Sc::Error { why: "the 'scope' option is set multiple times".into() };
+                    break Sc::Error { why: i18n::tr(&locale, "echo-option-repeated", &[("option", "scope".into())]) };
                 }
-                Some(Arg::Kw(k, v)) if k == "scope" => {
-                    let c = v == "char";
-                    if c || v == "line" {
-                        scope_opt = Some(if c { Scope::Characters } else { Scope::Lines });
-                    } else {
-                        break 'Z Sc::Error { why: "the 'scope' option has value 'char' or 'line'".into() };
-                    }
+                Some(Arg::Kw(k, v)) if k == "scope" => match Scope::from_name(v) {
+                    Some(s) => { scope_opt = Some(s); }
+                    None => break 'Z Sc::Error { why: i18n::tr(&locale, "echo-bad-scope", &[("scopes", Scope::NAMES.join(", ").into())]) },
+                }
+                Some(Arg::Kw(k, _)) if k == "collation" && collation_opt.is_some() =>
+                    break Sc::Error { why: i18n::tr(&locale, "echo-option-repeated", &[("option", "collation".into())]) },
+                Some(Arg::Kw(k, v)) if k == "collation" => { collation_opt = Some(v.clone()); }
+                Some(Arg::Kw(k, _)) if k == "numeric" && numeric_opt.is_some() =>
+                    break Sc::Error { why: i18n::tr(&locale, "echo-option-repeated", &[("option", "numeric".into())]) },
+                Some(a @ Arg::Kw(k, _)) if k == "numeric" => match a.literal() {
+                    Ok(n) => { numeric_opt = Some(n); }
+                    Err(why) => break 'Z Sc::Error { why: why.tr(&locale) },
+                }
+                Some(Arg::Kw(k, _)) if k == "ignore-case" && ignore_case_opt.is_some() =>
+                    break Sc::Error { why: i18n::tr(&locale, "echo-option-repeated", &[("option", "ignore-case".into())]) },
+                Some(a @ Arg::Kw(k, _)) if k == "ignore-case" => match a.literal() {
+                    Ok(i) => { ignore_case_opt = Some(i); }
+                    Err(why) => break 'Z Sc::Error { why: why.tr(&locale) },
                 }
                 Some(Arg::Kw(k, _)) =>
-                    break Sc::Error {
-                        why: format!("unrecognized option {k:?}; available options: sorted, reversed"),
-                    },
+                    break Sc::Error { why: i18n::tr(&locale, "echo-unknown-option", &[("option", format!("{k:?}").into())]) },
+                Some(Arg::Code { .. }) =>
+                    break Sc::Error { why: i18n::tr(&locale, "echo-no-code", &[]) },
             }
         };
 
         // act on the subcommands
         async fn make_echo_reply(ctx: Context, msg: Message, reply: String) {
-            let _: Option<_> = if msg.author.id == ctx.cache.current_user().id
-                && reply.len() >= "nom/echo".len() && &reply[.."nom/echo".len()] == "nom/echo"
-            {
-                util::try_reply(&ctx, &msg, "nombot refuses to `nom/echo`-bomb this channel. :/").await
-            } else if reply.len() == 0 {
-                util::try_reply(&ctx, &msg, "nombot cannot send an empty message. :/").await
+            let locale = util::locale(&ctx, &msg).await;
+            let _: Option<_> = if util::feeds_itself(&ctx, &msg, &reply).await {
+                util::try_reply_error(&ctx, &msg, i18n::tr(&locale, "echo-refuse-bomb", &[])).await
+            } else if reply.is_empty() {
+                util::try_reply_error(&ctx, &msg, i18n::tr(&locale, "echo-empty", &[])).await
             } else {
                 util::try_reply(&ctx, &msg, reply).await
             };
@@ -149,32 +129,25 @@ impl CommandHandler for EchoHandler {
 
         match sc {
             Sc::Error { why } =>
-                { let _: Option<_> = util::try_reply(&ctx, &msg, format!("```echo: {why}```")).await; }
+                { let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```echo: {why}```")).await; }
             Sc::Help =>
-                { let _: Option<_> = util::try_reply(&ctx, &msg, HELP_STR).await; }
-            Sc::Echo { sorted, reversed, scope: Scope::Characters } => {
-                let reply = match (sorted, reversed) {
-                    (false, false) => rest,
-                    (false, true) => rest.chars().rev().collect(),
-                    (true, false) => {
-                        let mut v: Vec<_> = rest.chars().collect();
-                        v.sort();
-                        v.into_iter().collect()
-                    }
-                    (true, true) => {
-                        let mut v: Vec<_> = rest.chars().collect();
-                        v.sort();
-                        v.into_iter().rev().collect()
+                { let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "echo-help", &[])).await; }
+            Sc::Echo { sorted, reversed, scope, collation, numeric, ignore_case } => {
+                let reply = Collation::new(collation.as_deref(), numeric, ignore_case, &locale).and_then(|c| match scope {
+                    Scope::Line => {
+                        let mut lines: Vec<&str> = rest.split('\n').filter(|l| !l.is_empty()).collect();
+                        if sorted { lines.sort_by(|a, b| c.compare(a, b)); }
+                        if reversed { lines.reverse(); }
+                        Ok(lines.join("\n"))
                     }
-                };
-                make_echo_reply(ctx, msg, reply).await;
-            }
-            Sc::Echo { sorted, reversed, scope: Scope::Lines } => {
-                let mut lines: Vec<&str> = rest.split('\n').filter(|l| l.len() >= 1).collect();
-                if sorted { lines.sort(); }
-                if reversed { lines.reverse(); }
-                let reply = lines.join("\n");
-                make_echo_reply(ctx, msg, reply).await;
+                    _ => [(sorted, Transform::Sort), (reversed, Transform::Reverse)].iter()
+                        .filter(|(on, _)| *on)
+                        .try_fold(rest.clone(), |text, (_, t)| t.apply(&text, scope, &c, &mut transform::rng(None))),
+                }).map_err(|why| why.tr(&locale));
+                match reply {
+                    Ok(reply) => make_echo_reply(ctx, msg, reply).await,
+                    Err(why) => { let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```echo: {why}```")).await; }
+                }
             }
         };
     }
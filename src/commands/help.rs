@@ -1,6 +1,7 @@
 use async_trait::async_trait;
+use crate::i18n;
 use crate::defn::command::{ Command, Context, Message, ClientData, CommandHandler };
-use crate::defn::globals::CommandMapTmk;
+use crate::defn::globals::{ CommandMapTmk, GuildSettingsTmk, ScriptsTmk };
 use crate::nom_util as util;
 
 pub struct HelpHandler;
@@ -15,11 +16,11 @@ fn right_pad(s: impl std::fmt::Display, n: usize) -> String {
 
 #[async_trait]
 impl CommandHandler for HelpHandler {
-    async fn whatis(&self, name: &str) -> String {
+    async fn whatis(&self, name: &str, locale: &str) -> String {
         if name == "h" {
-            "Alias of `help`".into()
+            i18n::tr(locale, "help-whatis-alias", &[])
         } else {
-            "Print all existing commands".into()
+            i18n::tr(locale, "help-whatis", &[])
         }
     }
     async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
@@ -29,12 +30,13 @@ impl CommandHandler for HelpHandler {
         // NOTE at this stage, `help` simply lists all the available commands
 
         // parse (validate) arguments
-        if args.len() != 0 {
-            let _: Option<_> = util::try_reply(&ctx, &msg, "```\nhelp: does not accept arguments (yet)\n```").await;
+        let locale = util::locale(&ctx, &msg).await;
+        if !args.is_empty() {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nhelp: {}\n```", i18n::tr(&locale, "help-no-arguments", &[]))).await;
             return;
         }
-        if rest.len() != 0 {
-            let _: Option<_> = util::try_reply(&ctx, &msg, "```\nhelp: does not accept a rest-field\n```").await;
+        if !rest.is_empty() {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nhelp: {}\n```", i18n::tr(&locale, "error-no-rest", &[]))).await;
             return;
         }
 
@@ -44,18 +46,34 @@ impl CommandHandler for HelpHandler {
             let cm = data
                 .get::<CommandMapTmk>().expect("Command map does not exist!")
                 .read().await;
-            let mut keys: Vec<&str> = cm.keys().map(|&s| s).collect();
-            let max_key_len = keys.iter().map(|k| k.len()).max().unwrap_or(0);
-            keys.sort();
-            for k in keys.into_iter() {
-                v.push(format!("{} - {}", right_pad(k, max_key_len), cm.get(k).unwrap().whatis(k).await));
+            // hide the commands that cannot be used here
+            let settings = data
+                .get::<GuildSettingsTmk>().expect("Guild settings do not exist!")
+                .read().await;
+            let mut entries = vec![];
+            for k in cm.names().filter(|k| settings.is_available(msg.guild_id, msg.channel_id, k)) {
+                entries.push((k.to_string(), cm.get(k).unwrap().whatis(k, &locale).await));
+            }
+            if let (Some(g), Some(scripts), Some(_)) = (msg.guild_id, data.get::<ScriptsTmk>(), cm.get("script")) {
+                let scripts = scripts.read().await;
+                for k in scripts.scripts.get(&g.0).into_iter().flat_map(|s| s.keys()) {
+                    if settings.is_available(msg.guild_id, msg.channel_id, k) {
+                        entries.push((k.clone(), i18n::tr(&locale, "help-script", &[])));
+                    }
+                }
+            }
+            let max_key_len = entries.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+            entries.sort();
+            for (k, w) in entries.into_iter() {
+                v.push(format!("{} - {}", right_pad(k, max_key_len), w));
             }
         }
 
-        if v.len() == 0 {
-            let _: Option<_> = util::try_reply(&ctx, &msg, "There is no help. (???)").await;
+        if v.is_empty() {
+            let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "help-empty", &[])).await;
         } else {
-            let _: Option<_> = util::try_reply(&ctx, &msg, format!("```\n{}\n```", v.join("\n"))).await;
+            let reply = format!("```\n{}\n```{}", v.join("\n"), i18n::tr(&locale, "help-scripts", &[]));
+            let _: Option<_> = util::try_reply(&ctx, &msg, reply).await;
         }
     }
 }
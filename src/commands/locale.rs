@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use crate::defn::command::{
+    Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::LocalesTmk;
+use crate::defn::subcommand::{ self, Node, Permission, Resolved, Schema, Subcommand };
+use crate::i18n;
+use crate::nom_util as util;
+
+#[derive(Clone, Copy)]
+enum Sc { // subcommand
+    Show,
+    Set,
+    Unset,
+    SetServer,
+    UnsetServer,
+}
+
+const LOCALE: Schema = Schema { args: &["locale"], ..Schema::NONE };
+
+const SUBCOMMANDS: &[Subcommand<Sc>] = &[
+    Subcommand {
+        name: "show", about: "locale-show-about",
+        permission: Permission::Everyone, schema: Schema::NONE, node: Node::Run(Sc::Show),
+    },
+    Subcommand {
+        name: "set", about: "locale-set-about",
+        permission: Permission::Everyone, schema: LOCALE, node: Node::Run(Sc::Set),
+    },
+    Subcommand {
+        name: "unset", about: "locale-unset-about",
+        permission: Permission::Everyone, schema: Schema::NONE, node: Node::Run(Sc::Unset),
+    },
+    Subcommand {
+        name: "set-server", about: "locale-set-server-about",
+        permission: Permission::GuildAdmin, schema: LOCALE, node: Node::Run(Sc::SetServer),
+    },
+    Subcommand {
+        name: "unset-server", about: "locale-unset-server-about",
+        permission: Permission::GuildAdmin, schema: Schema::NONE, node: Node::Run(Sc::UnsetServer),
+    },
+];
+
+pub struct LocaleHandler;
+
+#[async_trait]
+impl CommandHandler for LocaleHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "locale-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["locale"]
+    }
+    fn spec(&self, _: &str) -> Spec { subcommand::spec(SUBCOMMANDS) }
+    async fn call(&self, cmd: Command, ctx: Context, msg: Message) {
+        let inv = match subcommand::resolve(SUBCOMMANDS, cmd, &ctx, &msg).await {
+            Ok(Resolved::Run(inv)) => inv,
+            Ok(Resolved::Help { path, usage }) => {
+                let locale = util::locale(&ctx, &msg).await;
+                let available = i18n::locales().collect::<Vec<_>>().join(", ");
+                let help = match path.is_empty() {
+                    true => format!(
+                        "{}\n{usage}\n{}", i18n::tr(&locale, "locale-examples", &[]),
+                        i18n::tr(&locale, "locale-available", &[("available", available.into()), ("default", i18n::DEFAULT.into())]),
+                    ),
+                    false => usage,
+                };
+                let _: Option<_> = util::try_reply(&ctx, &msg, help).await;
+                return;
+            }
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nlocale: {why}\n```")).await;
+                return;
+            }
+        };
+        let store = {
+            let data = ctx.data.read().await;
+            data.get::<LocalesTmk>().expect("Locales do not exist!").clone()
+        };
+        let current = util::locale(&ctx, &msg).await;
+
+        let locale = match inv.action {
+            Sc::Set | Sc::SetServer => match i18n::find_locale(inv.param("locale")) {
+                Some(l) => Some(l),
+                None => {
+                    let available = i18n::locales().collect::<Vec<_>>().join(", ");
+                    let why = i18n::tr(&current, "locale-unknown", &[("locale", inv.param("locale").into()), ("available", available.into())]);
+                    let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nlocale: {why}\n```")).await;
+                    return;
+                }
+            },
+            Sc::Show | Sc::Unset | Sc::UnsetServer => None,
+        };
+        let (user, guild) = (msg.author.id.0, msg.guild_id.map(|g| g.0).unwrap_or_default());
+        let result = match inv.action {
+            Sc::Show => Ok(()),
+            Sc::Set => store.update(|l| { l.users.insert(user, locale.unwrap().into()); }).await,
+            Sc::Unset => store.update(|l| { l.users.remove(&user); }).await,
+            Sc::SetServer => store.update(|l| { l.guilds.insert(guild, locale.unwrap().into()); }).await,
+            Sc::UnsetServer => store.update(|l| { l.guilds.remove(&guild); }).await,
+        };
+        // confirm in the locale that is now in effect
+        let now = util::locale(&ctx, &msg).await;
+        let reply = match result {
+            Ok(()) => Ok(match inv.action {
+                Sc::Show => i18n::tr(&now, "locale-current", &[("locale", now.clone().into())]),
+                Sc::Set => i18n::tr(&now, "locale-set", &[("locale", now.clone().into())]),
+                Sc::Unset => i18n::tr(&now, "locale-unset", &[]),
+                Sc::SetServer => i18n::tr(&now, "locale-server-set", &[("locale", locale.unwrap().into())]),
+                Sc::UnsetServer => i18n::tr(&now, "locale-server-unset", &[]),
+            }),
+            Err(why) => {
+                tracing::error!(error = %why, "cannot save locales");
+                Err(format!("```\nlocale: {}\n```", i18n::tr(&now, "error-not-saved", &[])))
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
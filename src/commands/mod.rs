@@ -1,3 +1,39 @@
+use std::sync::Arc;
+use crate::defn::registry::ModuleFactory;
+
+pub mod admin;
+pub mod complete;
+pub mod config;
 pub mod echo;
 pub mod help;
+pub mod locale;
 pub mod nom;
+pub mod poll;
+pub mod quote;
+pub mod remind;
+pub mod roll;
+pub mod script;
+pub mod sed;
+pub mod set;
+pub mod tr;
+
+/// The modules compiled into the bot, loadable by name.
+pub fn builtin_modules() -> Vec<(&'static str, ModuleFactory)> {
+    vec![
+        ("admin", Arc::new(|| Box::new(admin::AdminHandler))),
+        ("complete", Arc::new(|| Box::new(complete::CompleteHandler))),
+        ("config", Arc::new(|| Box::new(config::ConfigHandler))),
+        ("echo", Arc::new(|| Box::new(echo::EchoHandler))),
+        ("help", Arc::new(|| Box::new(help::HelpHandler))),
+        ("locale", Arc::new(|| Box::new(locale::LocaleHandler))),
+        ("nom", Arc::new(|| Box::new(nom::NomHandler))),
+        ("poll", Arc::new(|| Box::new(poll::PollHandler))),
+        ("quote", Arc::new(|| Box::new(quote::QuoteHandler))),
+        ("remind", Arc::new(|| Box::new(remind::RemindHandler))),
+        ("roll", Arc::new(|| Box::new(roll::RollHandler))),
+        ("script", Arc::new(|| Box::<script::ScriptHandler>::default())),
+        ("sed", Arc::new(|| Box::new(sed::SedHandler))),
+        ("set", Arc::new(|| Box::new(set::SetHandler))),
+        ("tr", Arc::new(|| Box::new(tr::TrHandler))),
+    ]
+}
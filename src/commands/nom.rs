@@ -3,38 +3,39 @@ use crate::defn::command::{
     Arg, Command, Context, Message, ClientData,
     CommandHandler,
 };
+use crate::i18n;
 use crate::nom_util as util;
 
 pub struct NomHandler;
 #[async_trait]
 impl CommandHandler for NomHandler {
-    async fn whatis(&self, _: &str) -> String { "Nommers. ('!' for more enthusiasm, '.' for less)".into() }
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "nom-whatis", &[]) }
     async fn register(&mut self, _: ClientData) -> Vec<&'static str> { vec!["nom"] }
     async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
         // a simple secondary parser
-        if rest.len() != 0 {
-            let _: Option<_> = util::try_reply(&ctx, &msg, "```\nnom: does not accept a rest-field\n```").await;
+        let locale = util::locale(&ctx, &msg).await;
+        let report = |key, args: &[(&str, i18n::Value)]| format!("```\nnom: {}\n```", i18n::tr(&locale, key, args));
+        if !rest.is_empty() {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, report("error-no-rest", &[])).await;
             return;
         }
         let mut ups = 0u32;
         let mut downs = 0u32;
-        async fn report_conflict(c: &Context, m: &Message) {
-            let _: Option<_> = util::try_reply(c, m, "```\nnom: cannot have both `!`s and `.`s\n```").await;
-        }
         for a in args.iter() {
-            match a {
-                Arg::Pos(s) => {
-                    for c in s.chars() {
-                        match c {
-                            '!' if downs != 0 => { let () = report_conflict(&ctx, &msg).await; return; }
-                            '!' => { ups = (ups + 1).min(3); }
-                            '.' if ups != 0 => { let () = report_conflict(&ctx, &msg).await; return; }
-                            '.' => { downs = (downs + 1).min(2); }
-                            _ => { let _: Option<_> = util::try_reply(&ctx, &msg, format!("```\nnom: unrecognized character {c:?}\n```")).await; return; }
-                        }
-                    }
-                }
-                Arg::Kw(..) => { let _: Option<_> = util::try_reply(&ctx, &msg, format!("```\nnom: does not accept keyword arguments\n```")).await; return; }
+            let why = match a {
+                Arg::Pos(s) => s.chars().find_map(|c| match c {
+                    '!' if downs != 0 => Some(report("nom-conflict", &[])),
+                    '!' => { ups = (ups + 1).min(3); None }
+                    '.' if ups != 0 => Some(report("nom-conflict", &[])),
+                    '.' => { downs = (downs + 1).min(2); None }
+                    _ => Some(report("nom-bad-char", &[("char", format!("{c:?}").into())])),
+                }),
+                Arg::Kw(..) => Some(report("nom-no-keywords", &[])),
+                Arg::Code { .. } => Some(report("nom-no-code", &[])),
+            };
+            if let Some(why) = why {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, why).await;
+                return;
             }
         }
 
@@ -0,0 +1,202 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::Utc;
+use serenity::model::channel::ReactionType;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::PollsTmk;
+use crate::i18n::{ self, Msg };
+use crate::nom_util as util;
+use crate::polls::{ self, Poll, EMOJI, MAX_OPEN_PER_CHANNEL };
+
+const MAX_QUESTION_LEN: usize = 300;
+const MAX_OPTION_LEN: usize = 100;
+const DEFAULT_DURATION: Duration = Duration::from_secs(60 * 60);
+const MIN_DURATION: Duration = Duration::from_secs(60);
+const MAX_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+pub struct PollHandler;
+
+// Split `question | option | ...`.
+fn parse_poll(rest: &str) -> Result<(String, Vec<String>), Msg> {
+    let mut parts = rest.split('|').map(|p| p.trim().to_string());
+    let question = parts.next().unwrap_or_default();
+    let options: Vec<_> = parts.collect();
+    if question.is_empty() {
+        Err(Msg::new("poll-expected-question", &[]))
+    } else if question.chars().count() > MAX_QUESTION_LEN {
+        Err(Msg::new("poll-question-too-long", &[("max", MAX_QUESTION_LEN.into())]))
+    } else if options.len() < 2 || options.len() > EMOJI.len() {
+        Err(Msg::new("poll-option-count", &[("max", EMOJI.len().into()), ("count", options.len().into())]))
+    } else if options.iter().any(|o| o.is_empty()) {
+        Err(Msg::new("poll-empty-option", &[]))
+    } else if options.iter().any(|o| o.chars().count() > MAX_OPTION_LEN) {
+        Err(Msg::new("poll-option-too-long", &[("max", MAX_OPTION_LEN.into())]))
+    } else {
+        Ok((question, options))
+    }
+}
+
+#[async_trait]
+impl CommandHandler for PollHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "poll-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["poll"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec {
+            subcommands: vec![("help", Spec::default()), ("list", Spec::default()), ("close", Spec::default())],
+            keys: vec![("duration", &[])],
+            ..Spec::default()
+        }
+    }
+    async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
+        enum Sc { // subcommand
+            Help,
+            List,
+            Close(u64),
+            Post { duration: Duration, question: String, options: Vec<String> },
+        }
+        let locale = util::locale(&ctx, &msg).await;
+        let post = |duration: Result<Duration, String>| duration.and_then(|duration| {
+            parse_poll(&rest).map(|(question, options)| Sc::Post { duration, question, options }).map_err(|why| why.tr(&locale))
+        });
+        let sc = match &args[..] {
+            [Arg::Pos(s)] if s == "help" => Ok(Sc::Help),
+            [Arg::Pos(s)] if s == "list" => Ok(Sc::List),
+            [Arg::Pos(s), id] if s == "close" => id.literal::<i64>().map_err(|e| e.tr(&locale)).and_then(|id| match id {
+                id if id > 0 => Ok(Sc::Close(id as u64)),
+                id => Err(i18n::tr(&locale, "poll-not-open", &[("id", id.to_string().into())])),
+            }),
+            [] => post(Ok(DEFAULT_DURATION)),
+            [a @ Arg::Kw(k, _)] if k == "duration" => post(a.literal::<Duration>().map_err(|e| e.tr(&locale)).and_then(|d| match d {
+                d if (MIN_DURATION..=MAX_DURATION).contains(&d) => Ok(d),
+                _ => Err(i18n::tr(&locale, "poll-duration-range", &[])),
+            })),
+            _ => Err(i18n::tr(&locale, "poll-see-help", &[])),
+        }.and_then(|sc| match sc {
+            Sc::Help | Sc::List | Sc::Close(_) if !rest.is_empty() => Err(i18n::tr(&locale, "poll-no-rest", &[])),
+            sc => Ok(sc),
+        });
+        let sc = match sc {
+            Ok(sc) => sc,
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\npoll: {why}\n```")).await;
+                return;
+            }
+        };
+        if let Sc::Help = sc {
+            let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "poll-help", &[])).await;
+            return;
+        }
+        let polls = {
+            let data = ctx.data.read().await;
+            data.get::<PollsTmk>().expect("Polls do not exist!").clone()
+        };
+        let channel = msg.channel_id.0;
+
+        let reply: Result<String, String> = match sc {
+            Sc::Help => unreachable!(),
+            Sc::List => {
+                let open = polls.store.read().await;
+                let lines: Vec<_> = open.in_channel(channel).map(|p| {
+                    let link = format!("https://discord.com/channels/{}/{channel}/{}", msg.guild_id.map_or("@me".into(), |g| g.0.to_string()), p.message);
+                    i18n::tr(&locale, "poll-listed", &[
+                        ("id", p.id.to_string().into()), ("question", p.question.as_str().into()),
+                        ("closes", p.closes.to_string().into()), ("link", link.into()),
+                    ])
+                }).collect();
+                Ok(if lines.is_empty() { i18n::tr(&locale, "poll-none", &[]) } else { lines.join("\n") })
+            }
+            Sc::Close(id) => {
+                let author = polls.store.read().await.polls.iter().find(|p| p.id == id && p.channel == channel).map(|p| p.author);
+                match author {
+                    None => Err(format!("```\npoll: {}\n```", i18n::tr(&locale, "poll-not-open", &[("id", id.to_string().into())]))),
+                    Some(a) if a != msg.author.id.0 && !util::is_guild_admin(&ctx, &msg).await =>
+                        Err(format!("```\npoll: {}\n```", i18n::tr(&locale, "poll-close-denied", &[]))),
+                    Some(_) => match polls::close(&ctx.http, &polls, id).await {
+                        Some(Ok(())) => return,
+                        None => Err(format!("```\npoll: {}\n```", i18n::tr(&locale, "poll-just-closed", &[("id", id.to_string().into())]))),
+                        Some(Err(why)) => {
+                            tracing::error!(error = %why, "cannot save polls");
+                            Err(format!("```\npoll: {}\n```", i18n::tr(&locale, "poll-close-not-saved", &[])))
+                        }
+                    },
+                }
+            }
+            Sc::Post { duration, question, options } => {
+                let closes = Utc::now().timestamp() + duration.as_secs() as i64;
+                // take an id first, so that the poll can show it
+                let id = polls.store.update(|open| match open.in_channel(channel).count() {
+                    n if n >= MAX_OPEN_PER_CHANNEL => None,
+                    _ => { open.next_id += 1; Some(open.next_id) }
+                }).await;
+                let id = match id {
+                    Ok(Some(id)) => id,
+                    Ok(None) => {
+                        let why = i18n::tr(&locale, "poll-too-many", &[("max", MAX_OPEN_PER_CHANNEL.into())]);
+                        let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\npoll: {why}\n```")).await;
+                        return;
+                    }
+                    Err(why) => {
+                        tracing::error!(error = %why, "cannot save polls");
+                        let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\npoll: {}\n```", i18n::tr(&locale, "poll-unavailable", &[]))).await;
+                        return;
+                    }
+                };
+                let lines: Vec<_> = options.iter().zip(EMOJI.iter()).map(|(o, e)| format!("{e} {o}")).collect();
+                let text = i18n::tr(&locale, "poll-posted", &[
+                    ("question", question.as_str().into()), ("options", lines.join("\n").into()),
+                    ("closes", closes.to_string().into()), ("id", id.to_string().into()),
+                ]);
+                // the poll is a message of its own rather than a reply, even inside a script; it
+                // quotes the author, so it pings nobody
+                let posted = match msg.channel_id.send_message(&ctx.http, |m| m.content(text).allowed_mentions(|am| am.empty_parse())).await {
+                    Ok(m) => m,
+                    Err(why) => {
+                        tracing::warn!(error = ?why, "cannot post poll");
+                        let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\npoll: {}\n```", i18n::tr(&locale, "poll-post-failed", &[]))).await;
+                        return;
+                    }
+                };
+                let poll = Poll {
+                    id, message: posted.id.0, channel, author: msg.author.id.0,
+                    question, options, closes, votes: Default::default(), locale,
+                };
+                let n = poll.options.len();
+                if let Err(why) = polls.store.update(|open| open.polls.push(poll)).await {
+                    tracing::error!(error = %why, "cannot save polls");
+                }
+                polls.changed.notify_one();
+                for e in EMOJI.iter().take(n) {
+                    if let Err(why) = posted.react(&ctx.http, ReactionType::Unicode(e.to_string())).await {
+                        tracing::warn!(error = ?why, "cannot react to poll");
+                        break;
+                    }
+                }
+                return;
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_poll() {
+        assert_eq!(
+            parse_poll("Lunch? | Pizza | Sushi |Tacos"),
+            Ok(("Lunch?".into(), vec!["Pizza".into(), "Sushi".into(), "Tacos".into()])),
+        );
+        let err = |s: &str| parse_poll(s).unwrap_err().tr("en");
+        assert_eq!(err(""), "expected `<question> | <option> | <option>` in the rest-field");
+        assert_eq!(err("Lunch? | Pizza"), "from 2 to 10 options, not 1");
+        assert_eq!(err(&format!("Q{}", " | x".repeat(11))), "from 2 to 10 options, not 11");
+        assert_eq!(err("Lunch? | Pizza | "), "options must not be empty");
+    }
+}
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use rand::seq::SliceRandom;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::QuotesTmk;
+use crate::i18n;
+use crate::nom_args::Mention;
+use crate::nom_util as util;
+use crate::quotes::{ Quote, EMOJI };
+
+/// How many other matches are listed after a quote.
+const MAX_MORE: usize = 10;
+
+pub struct QuoteHandler;
+
+// `picked`, and the ids of the other quotes it was picked from.
+fn pick<'a>(quotes: &[&'a Quote], picked: &'a Quote, locale: &str) -> String {
+    let others: Vec<_> = quotes.iter().filter(|q| q.id != picked.id).map(|q| format!("`#{}`", q.id)).collect();
+    let also = match others.len() {
+        0 => return picked.show(locale),
+        n if n > MAX_MORE => i18n::tr(locale, "quote-also-more", &[("ids", others[..MAX_MORE].join(", ").into()), ("count", (n - MAX_MORE).into())]),
+        _ => i18n::tr(locale, "quote-also", &[("ids", others.join(", ").into())]),
+    };
+    format!("{}\n{also}", picked.show(locale))
+}
+
+#[async_trait]
+impl CommandHandler for QuoteHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "quote-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["quote"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec {
+            subcommands: vec![("help", Spec::default()), ("add", Spec::default()), ("search", Spec::default())],
+            keys: vec![("id", &[]), ("by", &[])],
+            ..Spec::default()
+        }
+    }
+    async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
+        enum Sc { // subcommand
+            Help,
+            Add,
+            Random,
+            Id(u64),
+            By(u64),
+            Search(String),
+        }
+        let locale = util::locale(&ctx, &msg).await;
+        let sc = match &args[..] {
+            [Arg::Pos(s)] if s == "help" => Ok(Sc::Help),
+            [Arg::Pos(s)] if s == "add" => Ok(Sc::Add),
+            [Arg::Pos(s)] if s == "search" => match rest.trim() {
+                "" => Err(i18n::tr(&locale, "quote-expected-words", &[])),
+                words => Ok(Sc::Search(words.to_string())),
+            },
+            [] => Ok(Sc::Random),
+            [a @ Arg::Kw(k, _)] if k == "id" => a.literal::<i64>().map_err(|e| e.tr(&locale)).and_then(|id| match id {
+                id if id > 0 => Ok(Sc::Id(id as u64)),
+                id => Err(i18n::tr(&locale, "quote-not-found", &[("id", id.to_string().into())])),
+            }),
+            [a @ Arg::Kw(k, _)] if k == "by" => a.literal::<Mention>().map_err(|e| e.tr(&locale)).and_then(|m| match m {
+                Mention::User(u) | Mention::Id(u) => Ok(Sc::By(u)),
+                _ => Err(i18n::tr(&locale, "quote-by-user", &[])),
+            }),
+            _ => Err(i18n::tr(&locale, "quote-see-help", &[])),
+        }.and_then(|sc| match sc {
+            Sc::Search(_) => Ok(sc),
+            _ if !rest.is_empty() => Err(i18n::tr(&locale, "quote-no-rest", &[])),
+            sc => Ok(sc),
+        });
+        let sc = match sc {
+            Ok(sc) => sc,
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nquote: {why}\n```")).await;
+                return;
+            }
+        };
+        if let Sc::Help = sc {
+            let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "quote-help", &[("emoji", EMOJI.into())])).await;
+            return;
+        }
+        let Some(guild) = msg.guild_id else {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nquote: {}\n```", i18n::tr(&locale, "quote-guild-only", &[]))).await;
+            return;
+        };
+        let quotes = {
+            let data = ctx.data.read().await;
+            data.get::<QuotesTmk>().expect("Quotes do not exist!").clone()
+        };
+
+        let reply: Result<String, String> = match sc {
+            Sc::Help => unreachable!(),
+            Sc::Add => {
+                let quoted = match msg.referenced_message.as_deref() {
+                    Some(m) => Quote::from_message(m, guild, msg.author.id.0).map_err(|why| why.tr(&locale)),
+                    None => Err(i18n::tr(&locale, "quote-expected-reply", &[("emoji", EMOJI.into())])),
+                };
+                match quoted {
+                    Err(why) => Err(format!("```\nquote: {why}\n```")),
+                    Ok(q) => match quotes.update(|s| s.add(guild.0, q)).await {
+                        Ok(Ok(id)) => Ok(i18n::tr(&locale, "quote-saved", &[("id", id.to_string().into())])),
+                        Ok(Err(why)) => Err(format!("```\nquote: {}\n```", why.tr(&locale))),
+                        Err(why) => {
+                            tracing::error!(error = %why, "cannot save quotes");
+                            Err(format!("```\nquote: {}\n```", i18n::tr(&locale, "quote-not-saved", &[])))
+                        }
+                    },
+                }
+            }
+            Sc::Random => {
+                let s = quotes.read().await;
+                match s.all(guild.0).choose(&mut rand::thread_rng()) {
+                    Some(q) => Ok(q.show(&locale)),
+                    None => Err(format!("```\nquote: {}\n```", i18n::tr(&locale, "quote-none", &[("emoji", EMOJI.into())]))),
+                }
+            }
+            Sc::Id(id) => match quotes.read().await.get(guild.0, id) {
+                Some(q) => Ok(q.show(&locale)),
+                None => Err(format!("```\nquote: {}\n```", i18n::tr(&locale, "quote-not-found", &[("id", id.to_string().into())]))),
+            },
+            Sc::By(user) => {
+                let s = quotes.read().await;
+                let by = s.by(guild.0, user);
+                match by.choose(&mut rand::thread_rng()) {
+                    Some(q) => Ok(pick(&by, q, &locale)),
+                    None => Err(format!("```\nquote: {}\n```", i18n::tr(&locale, "quote-none-by", &[]))),
+                }
+            }
+            Sc::Search(words) => {
+                let s = quotes.read().await;
+                let found = s.search(guild.0, &words);
+                match found.choose(&mut rand::thread_rng()) {
+                    Some(q) => Ok(pick(&found, q, &locale)),
+                    None => Err(format!("```\nquote: {}\n```", i18n::tr(&locale, "quote-none-found", &[]))),
+                }
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
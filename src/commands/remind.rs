@@ -0,0 +1,154 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::Utc;
+use chrono_tz::Tz;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::RemindersTmk;
+use crate::i18n;
+use crate::nom_args::{ self, Literal, OptSpec };
+use crate::nom_util as util;
+use crate::reminders::{ Reminder, MAX_AHEAD, MAX_TEXT_LEN };
+
+const OPTIONS: &[OptSpec] = &[OptSpec { name: "dm", shorts: &[('d', "yes")], switch: true }];
+
+pub struct RemindHandler;
+
+#[async_trait]
+impl CommandHandler for RemindHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "remind-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["remind"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec {
+            subcommands: vec![("help", Spec::default()), ("list", Spec::default()), ("cancel", Spec::default())],
+            keys: vec![("in", &[]), ("at", &[]), ("tz", &[])],
+            options: OPTIONS,
+        }
+    }
+    async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
+        enum Sc { // subcommand
+            Help,
+            List,
+            Cancel(u64),
+            In(Duration),
+            At(String, Option<String>),
+        }
+        let locale = util::locale(&ctx, &msg).await;
+        let parsed = nom_args::take_options(args, OPTIONS).map_err(|e| e.tr(&locale)).and_then(|(opts, args)| {
+            let dm = match opts.last() {
+                Some((_, v)) => bool::from_literal(v).ok_or_else(|| i18n::tr(&locale, "remind-bad-dm", &[("expected", bool::expected().tr(&locale).into()), ("value", format!("{v:?}").into())]))?,
+                None => false,
+            };
+            let sc = match &args[..] {
+                [Arg::Pos(s)] if s == "help" => Sc::Help,
+                [Arg::Pos(s)] if s == "list" => Sc::List,
+                [Arg::Pos(s), id] if s == "cancel" => match id.literal::<i64>().map_err(|e| e.tr(&locale))? {
+                    id if id > 0 => Sc::Cancel(id as u64),
+                    id => return Err(i18n::tr(&locale, "remind-not-found", &[("id", id.to_string().into())])),
+                },
+                [a @ Arg::Kw(k, _)] if k == "in" => Sc::In(a.literal().map_err(|e| e.tr(&locale))?),
+                [Arg::Kw(k, at)] if k == "at" => Sc::At(at.clone(), None),
+                [Arg::Kw(k, at), Arg::Kw(t, tz)] | [Arg::Kw(t, tz), Arg::Kw(k, at)] if k == "at" && t == "tz" =>
+                    Sc::At(at.clone(), Some(tz.clone())),
+                _ => return Err(i18n::tr(&locale, "remind-expected-subcommand", &[])),
+            };
+            match sc {
+                Sc::In(_) | Sc::At(..) if rest.trim().is_empty() => Err(i18n::tr(&locale, "remind-expected-text", &[])),
+                Sc::In(_) | Sc::At(..) if rest.chars().count() > MAX_TEXT_LEN =>
+                    Err(i18n::tr(&locale, "remind-too-long", &[("max", MAX_TEXT_LEN.into())])),
+                Sc::Help | Sc::List | Sc::Cancel(_) if !rest.is_empty() => Err(i18n::tr(&locale, "remind-no-rest", &[])),
+                sc => Ok((sc, dm)),
+            }
+        });
+        let (sc, dm) = match parsed {
+            Ok(x) => x,
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nremind: {why}\n```")).await;
+                return;
+            }
+        };
+        if let Sc::Help = sc {
+            let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "remind-help", &[])).await;
+            return;
+        }
+        let reminders = {
+            let data = ctx.data.read().await;
+            data.get::<RemindersTmk>().expect("Reminders do not exist!").clone()
+        };
+
+        let now = Utc::now();
+        let due = match &sc {
+            Sc::In(d) if d.as_secs() as i64 > MAX_AHEAD => Err(i18n::tr(&locale, "remind-too-far-ahead", &[])),
+            Sc::In(d) if d.is_zero() => Err(i18n::tr(&locale, "remind-in-zero", &[])),
+            Sc::In(d) => Ok(now.timestamp() + d.as_secs() as i64),
+            Sc::At(at, tz) => {
+                let tz = match tz.as_deref().map(|tz| tz.parse::<Tz>()) {
+                    None => Ok(Tz::UTC),
+                    Some(Ok(tz)) => Ok(tz),
+                    Some(Err(_)) => Err(i18n::tr(&locale, "remind-unknown-tz", &[("tz", format!("{:?}", tz.as_deref().unwrap_or("")).into())])),
+                };
+                match tz.and_then(|tz| crate::reminders::parse_at(at, tz, now).map_err(|why| why.tr(&locale))) {
+                    Ok(d) if d.timestamp() - now.timestamp() > MAX_AHEAD => Err(i18n::tr(&locale, "remind-too-far-ahead", &[])),
+                    r => r.map(|d| d.timestamp()),
+                }
+            }
+            Sc::Help | Sc::List | Sc::Cancel(_) => Ok(0),
+        };
+        let due = match due {
+            Ok(d) => d,
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nremind: {why}\n```")).await;
+                return;
+            }
+        };
+
+        let user = msg.author.id.0;
+        let reply: Result<String, String> = match sc {
+            Sc::Help => unreachable!(),
+            Sc::List => {
+                let pending = reminders.store.read().await;
+                let mut mine: Vec<_> = pending.of_user(user).collect();
+                mine.sort_by_key(|r| r.due);
+                let lines: Vec<_> = mine.into_iter().map(|r| {
+                    let place = match r.dm {
+                        true => i18n::tr(&locale, "remind-by-dm", &[]),
+                        false => i18n::tr(&locale, "remind-in-channel", &[("channel", r.channel.to_string().into())]),
+                    };
+                    let text: String = r.text.chars().take(50).collect();
+                    let more = if text.len() < r.text.len() { "…" } else { "" };
+                    format!("`#{}` <t:{}:R> {place}: {text}{more}", r.id, r.due)
+                }).collect();
+                Ok(if lines.is_empty() { i18n::tr(&locale, "remind-none", &[]) } else { lines.join("\n") })
+            }
+            Sc::Cancel(id) => match reminders.store.update(|p| p.cancel(user, id)).await {
+                Ok(Some(_)) => Ok(i18n::tr(&locale, "remind-cancelled", &[("id", id.to_string().into())])),
+                Ok(None) => Err(format!("```\nremind: {}\n```", i18n::tr(&locale, "remind-not-found", &[("id", id.to_string().into())]))),
+                Err(why) => {
+                    tracing::error!(error = %why, "cannot save reminders");
+                    Err(format!("```\nremind: {}\n```", i18n::tr(&locale, "remind-cancel-not-saved", &[])))
+                }
+            },
+            Sc::In(_) | Sc::At(..) => {
+                let r = Reminder { id: 0, user, channel: msg.channel_id.0, dm, due, text: rest, locale: locale.clone() };
+                match reminders.store.update(|p| p.add(r)).await {
+                    Ok(Ok(id)) => {
+                        reminders.changed.notify_one();
+                        Ok(i18n::tr(&locale, "remind-set", &[("id", id.to_string().into()), ("due", due.to_string().into())]))
+                    }
+                    Ok(Err(why)) => Err(format!("```\nremind: {}\n```", why.tr(&locale))),
+                    Err(why) => {
+                        tracing::error!(error = %why, "cannot save reminders");
+                        // the scheduler still has it
+                        reminders.changed.notify_one();
+                        Err(format!("```\nremind: {}\n```", i18n::tr(&locale, "remind-set-not-saved", &[])))
+                    }
+                }
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
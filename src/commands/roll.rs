@@ -0,0 +1,380 @@
+use async_trait::async_trait;
+use rand::{ Rng, SeedableRng };
+use rand::rngs::StdRng;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::i18n::{ self, Msg };
+use crate::nom_util as util;
+
+const MAX_ROLLS: usize = 10;
+const MAX_TERMS: usize = 20;
+/// Dice per term, before explosions and rerolls.
+const MAX_DICE: u64 = 100;
+/// Dice in all terms of all rolls, before explosions and rerolls.
+const MAX_TOTAL_DICE: u64 = 500;
+/// Dice added by explosions and rerolls, per term.
+const MAX_EXTRA_DICE: usize = 100;
+const MAX_SIDES: u64 = 1000;
+const MAX_NUMBER: u64 = 1_000_000;
+const MAX_REPLY: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keep {
+    Highest(u32),
+    Lowest(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dice {
+    count: u32,
+    sides: u32,
+    keep: Option<Keep>,
+    explode: bool,
+    /// Reroll dice showing this or less, once.
+    reroll: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Dice(Dice),
+    Number(u32),
+}
+
+/// One labeled roll, e.g. `2d6 + 3 damage`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Roll {
+    /// The terms, each negated or not, as written.
+    terms: Vec<(bool, Term, String)>,
+    label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Die {
+    value: u32,
+    /// Counts toward the total; not dropped by `kh`/`kl` nor replaced by a reroll.
+    kept: bool,
+    /// Made another die be rolled.
+    exploded: bool,
+}
+
+fn parse(s: &str) -> Result<Vec<Roll>, Msg> {
+    let rolls = s.split([',', '\n']).filter(|r| !r.trim().is_empty()).map(parse_roll).collect::<Result<Vec<_>, _>>()?;
+    if rolls.is_empty() {
+        return Err(Msg::new("roll-no-dice", &[]));
+    }
+    if rolls.len() > MAX_ROLLS {
+        return Err(Msg::new("roll-too-many-rolls", &[("max", MAX_ROLLS.into())]));
+    }
+    let dice: u64 = rolls.iter().flat_map(|r| r.terms.iter()).map(|(_, t, _)| match t {
+        Term::Dice(d) => d.count as u64,
+        Term::Number(_) => 0,
+    }).sum();
+    if dice > MAX_TOTAL_DICE {
+        return Err(Msg::new("roll-too-many-dice", &[("max", MAX_TOTAL_DICE.into()), ("dice", dice.into())]));
+    }
+    Ok(rolls)
+}
+
+fn parse_roll(s: &str) -> Result<Roll, Msg> {
+    let mut terms = vec![];
+    let (mut negative, mut s) = match s.trim_start().strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.trim_start()),
+    };
+    loop {
+        let t = s.trim_start();
+        let (term, rest) = expect_term(t)?;
+        terms.push((negative, term, t[..t.len() - rest.len()].to_string()));
+        if terms.len() > MAX_TERMS {
+            return Err(Msg::new("roll-too-many-terms", &[("max", MAX_TERMS.into())]));
+        }
+        let after = rest.trim_start();
+        match after.chars().next() {
+            Some('+') => { negative = false; s = &after[1..]; }
+            Some('-') => { negative = true; s = &after[1..]; }
+            None => return Ok(Roll { terms, label: None }),
+            Some(c) if rest.len() == after.len() => return Err(Msg::new("roll-unexpected", &[
+                ("char", format!("{c:?}").into()), ("term", terms.last().unwrap().2.clone().into()),
+            ])),
+            Some(_) => return Ok(Roll { terms, label: Some(after.trim_end().to_string()) }),
+        }
+    }
+}
+
+// Parse a decimal number, saturating on overflow so that the limits report it.
+fn expect_number(s: &str) -> Option<(u64, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    match end {
+        0 => None,
+        _ => Some((s[..end].parse().unwrap_or(u64::MAX), &s[end..])),
+    }
+}
+
+fn expect_term(s: &str) -> Result<(Term, &str), Msg> {
+    let (count, s) = match expect_number(s) {
+        Some((n, s)) => (Some(n), s),
+        None => (None, s),
+    };
+    let Some(s) = s.strip_prefix(['d', 'D']) else {
+        return match count {
+            // large numbers are passed as text; Fluent would round them
+            Some(n) if n > MAX_NUMBER => Err(Msg::new("roll-number-too-large", &[("max", MAX_NUMBER.into()), ("number", n.to_string().into())])),
+            Some(n) => Ok((Term::Number(n as u32), s)),
+            None => Err(match s.chars().next() {
+                Some(c) => Msg::new("roll-expected-term-not", &[("char", format!("{c:?}").into())]),
+                None => Msg::new("roll-expected-term", &[]),
+            }),
+        };
+    };
+    let count = count.unwrap_or(1);
+    let (sides, mut s) = match s.strip_prefix('%') {
+        Some(s) => (100, s),
+        None => expect_number(s).ok_or_else(|| Msg::new("roll-expected-sides", &[]))?,
+    };
+    if !(1..=MAX_DICE).contains(&count) {
+        return Err(Msg::new("roll-dice-per-term", &[("max", MAX_DICE.into()), ("count", count.to_string().into())]));
+    }
+    if !(1..=MAX_SIDES).contains(&sides) {
+        return Err(Msg::new("roll-sides", &[("max", MAX_SIDES.into()), ("sides", sides.to_string().into())]));
+    }
+    let mut d = Dice { count: count as u32, sides: sides as u32, keep: None, explode: false, reroll: None };
+
+    // modifiers, each at most once
+    loop {
+        let (keep, rest) = if let Some(r) = s.strip_prefix("kl") {
+            let (n, r) = expect_number(r).unwrap_or((1, r));
+            (Some(Keep::Lowest(n.min(u32::MAX as u64) as u32)), r)
+        } else if let Some(r) = s.strip_prefix("kh").or_else(|| s.strip_prefix('k')) {
+            let (n, r) = expect_number(r).unwrap_or((1, r));
+            (Some(Keep::Highest(n.min(u32::MAX as u64) as u32)), r)
+        } else if let Some(r) = s.strip_prefix("adv").or_else(|| s.strip_prefix("dis")) {
+            if d.count != 1 {
+                return Err(Msg::new("roll-advantage-single", &[]));
+            }
+            d.count = 2;
+            (Some(if s.starts_with("adv") { Keep::Highest(1) } else { Keep::Lowest(1) }), r)
+        } else if let Some(r) = s.strip_prefix('!') {
+            if d.explode {
+                return Err(Msg::new("roll-explode-repeated", &[]));
+            }
+            if d.sides == 1 {
+                return Err(Msg::new("roll-explode-one-sided", &[]));
+            }
+            d.explode = true;
+            s = r;
+            continue;
+        } else if let Some(r) = s.strip_prefix('r') {
+            let (n, r) = expect_number(r).ok_or_else(|| Msg::new("roll-expected-reroll", &[]))?;
+            if d.reroll.is_some() {
+                return Err(Msg::new("roll-reroll-repeated", &[]));
+            }
+            if n >= d.sides as u64 {
+                return Err(Msg::new("roll-reroll-every-face", &[("sides", d.sides.into())]));
+            }
+            d.reroll = Some(n as u32);
+            s = r;
+            continue;
+        } else {
+            return Ok((Term::Dice(d), s));
+        };
+        match (d.keep, keep) {
+            (Some(_), _) => return Err(Msg::new("roll-keep-repeated", &[])),
+            (None, Some(Keep::Highest(n) | Keep::Lowest(n))) if n == 0 || n > d.count =>
+                return Err(Msg::new("roll-keep-too-many", &[("keep", n.into()), ("count", d.count.into())])),
+            (None, k) => d.keep = k,
+        }
+        s = rest;
+    }
+}
+
+/// Roll `d` with `roll`, which gives a face from 1 to the number of sides it is called with.
+fn roll_dice(d: &Dice, roll: &mut impl FnMut(u32) -> u32) -> Vec<Die> {
+    let mut dice = vec![];
+    let mut extra = 0;
+    for _ in 0..d.count {
+        let mut value = roll(d.sides);
+        if d.reroll.is_some_and(|r| value <= r) {
+            dice.push(Die { value, kept: false, exploded: false });
+            extra += 1;
+            value = roll(d.sides);
+        }
+        loop {
+            let exploded = d.explode && value == d.sides && extra < MAX_EXTRA_DICE;
+            dice.push(Die { value, kept: true, exploded });
+            if !exploded {
+                break;
+            }
+            extra += 1;
+            value = roll(d.sides);
+        }
+    }
+    if let Some(keep) = d.keep {
+        let mut kept: Vec<usize> = (0..dice.len()).filter(|&i| dice[i].kept).collect();
+        let n = match keep {
+            Keep::Highest(n) => { kept.sort_by_key(|&i| std::cmp::Reverse(dice[i].value)); n }
+            Keep::Lowest(n) => { kept.sort_by_key(|&i| dice[i].value); n }
+        };
+        for &i in kept.iter().skip(n as usize) {
+            dice[i].kept = false;
+        }
+    }
+    dice
+}
+
+/// Roll every term of `r`, giving a line with the dice and one with just the total.
+fn evaluate(r: &Roll, roll: &mut impl FnMut(u32) -> u32) -> (String, String) {
+    let mut total = 0i64;
+    let mut detail = String::new();
+    let mut expr = String::new();
+    for (i, (negative, term, source)) in r.terms.iter().enumerate() {
+        let sign = match (i, negative) {
+            (0, false) => "",
+            (0, true) => "-",
+            (_, false) => " + ",
+            (_, true) => " - ",
+        };
+        let (value, shown) = match term {
+            Term::Number(n) => (*n as i64, n.to_string()),
+            Term::Dice(d) => {
+                let dice = roll_dice(d, roll);
+                let shown: Vec<_> = dice.iter().map(|d| match (d.kept, d.exploded) {
+                    (true, true) => format!("{}!", d.value),
+                    (true, false) => d.value.to_string(),
+                    (false, true) => format!("~~{}!~~", d.value),
+                    (false, false) => format!("~~{}~~", d.value),
+                }).collect();
+                (dice.iter().filter(|d| d.kept).map(|d| d.value as i64).sum(), format!("{source} ({})", shown.join(", ")))
+            }
+        };
+        total += if *negative { -value } else { value };
+        detail.push_str(&format!("{sign}{shown}"));
+        expr.push_str(&format!("{sign}{source}"));
+    }
+    let label = r.label.as_ref().map(|l| format!("{l}: ")).unwrap_or_default();
+    (format!("{label}{detail} = **{total}**"), format!("{label}{expr} = **{total}**"))
+}
+
+fn reply(rolls: &[Roll], roll: &mut impl FnMut(u32) -> u32) -> String {
+    let (detailed, short): (Vec<_>, Vec<_>) = rolls.iter().map(|r| evaluate(r, roll)).unzip();
+    let detailed = detailed.join("\n");
+    // too many dice to show
+    if detailed.chars().count() > MAX_REPLY { short.join("\n") } else { detailed }
+}
+
+pub struct RollHandler;
+
+#[async_trait]
+impl CommandHandler for RollHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "roll-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["roll"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec { subcommands: vec![("help", Spec::default())], ..Spec::default() }
+    }
+    async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
+        let locale = util::locale(&ctx, &msg).await;
+        match &args[..] {
+            [] => {}
+            [Arg::Pos(s)] if s == "help" => {
+                let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "roll-help", &[])).await;
+                return;
+            }
+            _ => {
+                let why = i18n::tr(&locale, "roll-no-arguments", &[]);
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nroll: {why}\n```")).await;
+                return;
+            }
+        }
+        let reply = parse(&rest).map(|rolls| {
+            let mut rng = StdRng::from_entropy();
+            reply(&rolls, &mut |sides| rng.gen_range(1..=sides))
+        });
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply.map_err(|why| format!("```\nroll: {}\n```", why.tr(&locale)))).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dice(count: u32, sides: u32) -> Dice { Dice { count, sides, keep: None, explode: false, reroll: None } }
+
+    // A die that shows `faces` in turn.
+    fn faces(faces: &[u32]) -> impl FnMut(u32) -> u32 + '_ {
+        let mut i = 0;
+        move |sides| {
+            let f = faces[i % faces.len()];
+            assert!((1..=sides).contains(&f));
+            i += 1;
+            f
+        }
+    }
+
+    fn terms_of(s: &str) -> Vec<(bool, Term)> {
+        parse_roll(s).unwrap().terms.into_iter().map(|(n, t, _)| (n, t)).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(terms_of("2d6kl"), vec![(false, Term::Dice(Dice { keep: Some(Keep::Lowest(1)), ..dice(2, 6) }))]);
+        let terms = terms_of;
+        assert_eq!(terms("4d6kh3 + 2d8! - 5"), vec![
+            (false, Term::Dice(Dice { keep: Some(Keep::Highest(3)), ..dice(4, 6) })),
+            (false, Term::Dice(Dice { explode: true, ..dice(2, 8) })),
+            (true, Term::Number(5)),
+        ]);
+        assert_eq!(terms("-d%"), vec![(true, Term::Dice(dice(1, 100)))]);
+        assert_eq!(terms("d20dis"), vec![(false, Term::Dice(Dice { keep: Some(Keep::Lowest(1)), ..dice(2, 20) }))]);
+        assert_eq!(terms("3d6r2!k"), vec![(false, Term::Dice(Dice { keep: Some(Keep::Highest(1)), explode: true, reroll: Some(2), ..dice(3, 6) }))]);
+
+        let rolls = parse("d20adv + 3 to hit, 2d6 + 3 fire damage\n1d4").unwrap();
+        assert_eq!(rolls.iter().map(|r| r.label.as_deref()).collect::<Vec<_>>(), vec![Some("to hit"), Some("fire damage"), None]);
+        assert_eq!(rolls[0].terms[0].2, "d20adv");
+
+        let err = |s| parse(s).unwrap_err().tr("en");
+        assert_eq!(err("1000000d1000000"), "from 1 to 100 dice per term, not 1000000");
+        assert_eq!(err("99999999999999999999999d6"), format!("from 1 to 100 dice per term, not {}", u64::MAX));
+        assert_eq!(err("d1000000"), "dice have from 1 to 1000 sides, not 1000000");
+        assert_eq!(err("0d6"), "from 1 to 100 dice per term, not 0");
+        assert_eq!(err("100d6,100d6,100d6,100d6,100d6,1d6"), "at most 500 dice at once, not 501");
+        assert_eq!(err("d1!"), "one-sided dice cannot explode");
+        assert_eq!(err("d6r6"), "cannot reroll every face of a d6");
+        assert_eq!(err("2d6kh3"), "cannot keep 3 of 2 dice");
+        assert_eq!(err("2d6adv"), "`adv` and `dis` apply to a single die");
+        assert_eq!(err("4d6khkl"), "only one of `kh`, `kl`, `adv` and `dis` per term");
+        assert_eq!(err("2d6x"), "unexpected 'x' after `2d6`");
+        assert_eq!(err("2d6 +"), "expected dice like `2d6` or a number");
+        assert_eq!(err("d"), "expected the number of sides after `d`");
+        assert_eq!(err(" , "), "expected dice like `2d6` in the rest-field");
+        assert_eq!(err(&"1+".repeat(21)), "at most 20 terms per roll");
+    }
+
+    #[test]
+    fn test_roll() {
+        let shown = |s, f: &[u32]| reply(&parse(s).unwrap(), &mut faces(f));
+        assert_eq!(shown("4d6kh3 + 2d8! + 5", &[2, 6, 5, 4, 8, 3, 1]), "4d6kh3 (~~2~~, 6, 5, 4) + 2d8! (8!, 3, 1) + 5 = **32**");
+        assert_eq!(shown("d20adv + 3 to hit", &[7, 15]), "to hit: d20adv (~~7~~, 15) + 3 = **18**");
+        assert_eq!(shown("2d6r1 - 1", &[1, 1, 4]), "2d6r1 (~~1~~, 1, 4) - 1 = **4**");
+        assert_eq!(shown("-5 + d4kl", &[3]), "-5 + d4kl (3) = **-2**");
+
+        // explosions stop eventually
+        let exploded = roll_dice(&Dice { explode: true, ..dice(1, 2) }, &mut faces(&[2]));
+        assert_eq!(exploded.len(), MAX_EXTRA_DICE + 1);
+        assert!(!exploded.last().unwrap().exploded);
+
+        // too much to show
+        let long = shown("100d1000, 100d1000, 100d1000, 100d1000", &[100]);
+        assert_eq!(long, ["100d1000 = **10000**"; 4].join("\n"));
+
+        // seeded rolls are reproducible
+        let rolls = parse("10d20! + 4d6kh3").unwrap();
+        let seeded = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            super::reply(&rolls, &mut |sides| rng.gen_range(1..=sides))
+        };
+        assert_eq!(seeded(7), seeded(7));
+    }
+}
@@ -0,0 +1,213 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::defn::command::{
+    Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::{ CommandMapTmk, ConfigTmk, ScriptsTmk };
+use crate::defn::subcommand::{ self, Node, Permission, Resolved, Schema, Subcommand };
+use crate::i18n;
+use crate::nom_args;
+use crate::nom_util as util;
+use crate::script::{ self, Limits, Script };
+use crate::storage::JsonStore;
+
+#[derive(Clone, Copy)]
+enum Sc { // subcommand
+    List,
+    Show,
+    Set,
+    Remove,
+}
+
+const NAME: Schema = Schema { keys: &["name"], ..Schema::NONE };
+
+const SUBCOMMANDS: &[Subcommand<Sc>] = &[
+    Subcommand {
+        name: "list", about: "script-list-about",
+        permission: Permission::Everyone, schema: Schema::NONE, node: Node::Run(Sc::List),
+    },
+    Subcommand {
+        name: "show", about: "script-show-about",
+        permission: Permission::Everyone, schema: NAME, node: Node::Run(Sc::Show),
+    },
+    Subcommand {
+        name: "set", about: "script-set-about",
+        permission: Permission::GuildAdmin, schema: Schema { rest: Some("source"), ..NAME }, node: Node::Run(Sc::Set),
+    },
+    Subcommand {
+        name: "remove", about: "script-remove-about",
+        permission: Permission::GuildAdmin, schema: NAME, node: Node::Run(Sc::Remove),
+    },
+];
+
+#[derive(Default)]
+pub struct ScriptHandler {
+    limits: Limits,
+}
+
+#[async_trait]
+impl CommandHandler for ScriptHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "script-whatis", &[]) }
+    async fn register(&mut self, data: ClientData) -> Vec<&'static str> {
+        let mut data = data.write().await;
+        if let Some(config) = data.get::<ConfigTmk>().cloned() {
+            // bad settings are reported by `check_settings`
+            self.limits = Limits::from_settings(config.command_settings.get("script")).unwrap_or_default();
+            if !data.contains_key::<ScriptsTmk>() {
+                match JsonStore::open(config.storage_path.join("scripts.json")) {
+                    Ok(s) => { data.insert::<ScriptsTmk>(Arc::new(s)); }
+                    Err(why) => tracing::error!(error = %why, "cannot load scripts"),
+                }
+            }
+        }
+        vec!["script"]
+    }
+    async fn check_settings(&self, _: &str, settings: &toml::Table) -> Result<(), String> {
+        Limits::from_settings(Some(settings)).map(|_| ())
+    }
+    fn spec(&self, _: &str) -> Spec { subcommand::spec(SUBCOMMANDS) }
+    async fn call(&self, cmd: Command, ctx: Context, msg: Message) {
+        let locale = util::locale(&ctx, &msg).await;
+        let store = {
+            let data = ctx.data.read().await;
+            data.get::<ScriptsTmk>().cloned()
+        };
+        let Some(store) = store else {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nscript: {}\n```", i18n::tr(&locale, "script-unavailable", &[]))).await;
+            return;
+        };
+        if cmd.name != "script" {
+            // the dispatcher hands us commands that name a script of this guild
+            return self.run(store, cmd, ctx, msg, &locale).await;
+        }
+
+        let inv = match subcommand::resolve(SUBCOMMANDS, cmd, &ctx, &msg).await {
+            Ok(Resolved::Run(inv)) => inv,
+            Ok(Resolved::Help { path, usage }) => {
+                let help = match path.is_empty() {
+                    true => format!("{}\n{usage}\n{}", i18n::tr(&locale, "script-examples", &[]), i18n::tr(&locale, "script-notes", &[])),
+                    false => usage,
+                };
+                let _: Option<_> = util::try_reply(&ctx, &msg, help).await;
+                return;
+            }
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nscript: {why}\n```")).await;
+                return;
+            }
+        };
+        let Some(guild_id) = msg.guild_id else {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\nscript: {}\n```", i18n::tr(&locale, "error-guild-only", &[]))).await;
+            return;
+        };
+
+        let tr = |key, args: &[(&str, i18n::Value)]| i18n::tr(&locale, key, args);
+        let error = |key, name: &str| format!("```\nscript: {}\n```", tr(key, &[("name", format!("{name:?}").into())]));
+        let reply: Result<String, String> = match inv.action {
+            Sc::List => {
+                let scripts = store.read().await;
+                match scripts.scripts.get(&guild_id.0) {
+                    Some(s) if !s.is_empty() => Ok(format!("```\n{}\n```", s.keys().cloned().collect::<Vec<_>>().join("\n"))),
+                    _ => Ok(tr("script-none", &[])),
+                }
+            }
+            Sc::Show => {
+                let name = inv.param("name");
+                match store.read().await.get(guild_id.0, name) {
+                    Some(s) => {
+                        let by = tr("script-by", &[("name", name.into()), ("author", s.author.to_string().into())]);
+                        Ok(format!("{by}\n```rust\n{}\n```", s.source))
+                    }
+                    None => Err(error("script-not-found", name)),
+                }
+            }
+            Sc::Set => {
+                let name = inv.param("name");
+                let source = strip_code_block(&inv.rest);
+                let taken = {
+                    let data = ctx.data.read().await;
+                    let cm = data
+                        .get::<CommandMapTmk>().expect("Command map does not exist!")
+                        .read().await;
+                    cm.get(name).is_some()
+                };
+                if !nom_args::is_ident(name) {
+                    Err(error("script-bad-name", name))
+                } else if taken {
+                    Err(error("script-taken", name))
+                } else if let Err(e) = script::compile(source, &self.limits) {
+                    Err(format!("```\nscript: {e}\n```"))
+                } else {
+                    let s = Script { source: source.into(), author: msg.author.id.0 };
+                    match store.update(|g| g.scripts.entry(guild_id.0).or_default().insert(name.into(), s)).await {
+                        Ok(_) => Ok(tr("script-saved", &[("name", name.into())])),
+                        Err(why) => {
+                            tracing::error!(error = %why, "cannot save scripts");
+                            Err(format!("```\nscript: {}\n```", tr("script-set-not-saved", &[])))
+                        }
+                    }
+                }
+            }
+            Sc::Remove => {
+                let name = inv.param("name");
+                let result = store.update(|g| {
+                    let removed = g.scripts.get_mut(&guild_id.0).and_then(|s| s.remove(name));
+                    if g.scripts.get(&guild_id.0).is_some_and(|s| s.is_empty()) {
+                        g.scripts.remove(&guild_id.0);
+                    }
+                    removed
+                }).await;
+                match result {
+                    Ok(Some(_)) => Ok(tr("script-removed", &[("name", name.into())])),
+                    Ok(None) => Err(error("script-not-found", name)),
+                    Err(why) => {
+                        tracing::error!(error = %why, "cannot save scripts");
+                        Err(format!("```\nscript: {}\n```", tr("script-remove-not-saved", &[])))
+                    }
+                }
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+impl ScriptHandler {
+    async fn run(&self, store: Arc<JsonStore<script::GuildScripts>>, cmd: Command, ctx: Context, msg: Message, locale: &str) {
+        let source = match msg.guild_id {
+            Some(g) => store.read().await.get(g.0, &cmd.name).map(|s| s.source.clone()),
+            None => None,
+        };
+        let Some(source) = source else {
+            let why = i18n::tr(locale, "script-no-command", &[("name", format!("{:?}", cmd.name).into())]);
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{why}\n```")).await;
+            return;
+        };
+        let limits = self.limits.clone();
+        let (author, channel) = (msg.author.id.0, msg.channel_id.0);
+        let name = cmd.name.clone();
+        let result = tokio::task::spawn_blocking(move || script::run(&source, &cmd, author, channel, &limits)).await;
+        let reply = match result {
+            Ok(Ok(r)) if r.is_empty() => return,
+            Ok(Ok(r)) => Ok(r),
+            Ok(Err(e)) => Err(format!("```\n{name}: {e}\n```")),
+            Err(why) => {
+                tracing::error!(error = %why, "script task failed");
+                Err(format!("```\n{name}: {}\n```", i18n::tr(locale, "script-failed", &[])))
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+// Accept sources pasted as ```lang ... ``` code blocks.
+fn strip_code_block(s: &str) -> &str {
+    let t = s.trim();
+    match t.strip_prefix("```").and_then(|t| t.strip_suffix("```")) {
+        Some(inner) => match inner.split_once('\n') {
+            Some((lang, body)) if !lang.contains(char::is_whitespace) => body,
+            _ => inner,
+        },
+        None => s,
+    }
+}
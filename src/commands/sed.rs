@@ -0,0 +1,250 @@
+use async_trait::async_trait;
+use regex::{ Regex, RegexBuilder };
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::RecentTmk;
+use crate::i18n::{ self, Msg };
+use crate::nom_util as util;
+
+const MAX_PATTERN_LEN: usize = 200;
+/// Bounds on the compiled regex, in bytes.
+const MAX_REGEX_SIZE: usize = 1 << 18;
+const MAX_DFA_SIZE: usize = 1 << 20;
+const MAX_NEST: u32 = 32;
+const MAX_RESULT_LEN: usize = 1900;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Group(usize),
+}
+
+#[derive(Debug)]
+struct Substitution {
+    re: Regex,
+    replacement: Vec<Piece>,
+    global: bool,
+}
+
+// Split `s/<regex>/<replacement>/<flags>` (any delimiter) into its fields, unescaping the delimiter.
+// The last delimiter may be left out.
+fn fields(expr: &str) -> Result<Vec<String>, Msg> {
+    let mut chars = expr.strip_prefix('s').ok_or_else(|| Msg::new("sed-expected-s", &[]))?.chars();
+    let d = match chars.next() {
+        Some(d) if !d.is_alphanumeric() && !d.is_whitespace() && d != '\\' => d,
+        _ => return Err(Msg::new("sed-expected-delimiter", &[])),
+    };
+    let mut fields = vec![String::new()];
+    while let Some(c) = chars.next() {
+        let f = fields.last_mut().unwrap();
+        match c {
+            '\\' => match chars.next() {
+                Some(e) if e == d => f.push(d),
+                Some(e) => { f.push('\\'); f.push(e); }
+                None => f.push('\\'),
+            },
+            c if c == d => fields.push(String::new()),
+            c => f.push(c),
+        }
+    }
+    match fields.len() {
+        2 => fields.push(String::new()),
+        3 => {}
+        _ => return Err(Msg::new("sed-expected-fields", &[("d", d.to_string().into())])),
+    }
+    Ok(fields)
+}
+
+fn parse_replacement(s: &str, groups: usize) -> Result<Vec<Piece>, Msg> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = s.chars();
+    let group = |pieces: &mut Vec<Piece>, text: &mut String, g: usize| {
+        if g >= groups {
+            return Err(Msg::new("sed-no-group", &[("group", g.into())]));
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(std::mem::take(text)));
+        }
+        pieces.push(Piece::Group(g));
+        Ok(())
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => group(&mut pieces, &mut text, 0)?,
+            '\\' => match chars.next() {
+                Some(g @ '0'..='9') => group(&mut pieces, &mut text, g as usize - '0' as usize)?,
+                Some('n') => text.push('\n'),
+                Some(e) => text.push(e),
+                None => text.push('\\'),
+            },
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+fn parse_sed(expr: &str) -> Result<Substitution, Msg> {
+    let f = fields(expr)?;
+    let (pattern, replacement, flags) = (&f[0], &f[1], &f[2]);
+    if pattern.is_empty() {
+        return Err(Msg::new("sed-empty", &[]));
+    } else if pattern.chars().count() > MAX_PATTERN_LEN {
+        return Err(Msg::new("sed-too-long", &[("max", MAX_PATTERN_LEN.into())]));
+    }
+    let (mut global, mut icase) = (false, false);
+    for c in flags.chars() {
+        match c {
+            'g' => global = true,
+            'i' => icase = true,
+            c => return Err(Msg::new("sed-unknown-flag", &[("flag", format!("{c:?}").into())])),
+        }
+    }
+    let re = RegexBuilder::new(pattern)
+        .case_insensitive(icase)
+        .size_limit(MAX_REGEX_SIZE)
+        .dfa_size_limit(MAX_DFA_SIZE)
+        .nest_limit(MAX_NEST)
+        .build()
+        .map_err(|e| match e {
+            regex::Error::CompiledTooBig(_) => Msg::new("sed-too-big", &[]),
+            e => {
+                let e = e.to_string().lines().last().unwrap_or_default().trim_start_matches("error: ").to_string();
+                Msg::new("sed-invalid", &[("error", e.into())])
+            }
+        })?;
+    let replacement = parse_replacement(replacement, re.captures_len())?;
+    Ok(Substitution { re, replacement, global })
+}
+
+impl Substitution {
+    /// `text` with the first or every match replaced; an error if nothing matches or the result
+    /// would be too long to post.
+    fn apply(&self, text: &str) -> Result<String, Msg> {
+        let mut out = String::new();
+        let mut last = 0;
+        let mut matched = false;
+        for caps in self.re.captures_iter(text) {
+            let m = caps.get(0).unwrap();
+            matched = true;
+            out.push_str(&text[last..m.start()]);
+            for p in self.replacement.iter() {
+                match p {
+                    Piece::Text(t) => out.push_str(t),
+                    Piece::Group(g) => out.push_str(caps.get(*g).map_or("", |g| g.as_str())),
+                }
+            }
+            last = m.end();
+            if out.len() > MAX_RESULT_LEN * 4 || !self.global {
+                break;
+            }
+        }
+        if !matched {
+            return Err(Msg::new("sed-no-match", &[]));
+        }
+        out.push_str(&text[last..]);
+        if out.chars().count() > MAX_RESULT_LEN {
+            return Err(Msg::new("sed-result-too-long", &[("max", MAX_RESULT_LEN.into())]));
+        }
+        Ok(out)
+    }
+}
+
+pub struct SedHandler;
+
+#[async_trait]
+impl CommandHandler for SedHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "sed-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["sed", "s"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec { subcommands: vec![("help", Spec::default())], ..Spec::default() }
+    }
+    async fn call(&self, Command { name, args, rest }: Command, ctx: Context, msg: Message) {
+        let locale = util::locale(&ctx, &msg).await;
+        let sub = match &args[..] {
+            [Arg::Pos(s)] if s == "help" && rest.is_empty() => {
+                let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "sed-help", &[])).await;
+                return;
+            }
+            [] if name == "s" => parse_sed(&format!("s/{rest}")),
+            [] => parse_sed(&rest),
+            _ => Err(Msg::new("sed-see-help", &[])),
+        };
+        let sub = match sub {
+            Ok(s) => s,
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {}\n```", why.tr(&locale))).await;
+                return;
+            }
+        };
+        let recent = {
+            let data = ctx.data.read().await;
+            data.get::<RecentTmk>().expect("Recent messages do not exist!").clone()
+        };
+        let last = recent.lock().await.last_of(msg.channel_id.0, msg.author.id.0).cloned();
+        let reply = match last {
+            None => Err(Msg::new("sed-no-recent", &[])),
+            Some(m) => sub.apply(&m.content),
+        };
+        let reply = reply
+            .map(|text| meant(&locale, &msg.author.name, &text))
+            .map_err(|why| format!("```\n{name}: {}\n```", why.tr(&locale)));
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+// The corrected message as a reply. It repeats what the author wrote, so mentions in it are broken
+// up rather than pinging anyone again.
+fn meant(locale: &str, name: &str, text: &str) -> String {
+    i18n::tr(locale, "sed-meant", &[("name", util::defuse(name).into()), ("text", util::defuse(text).into())])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nom_args;
+
+    fn sed(expr: &str, text: &str) -> Result<String, String> {
+        parse_sed(expr).and_then(|s| s.apply(text)).map_err(|why| why.tr("en"))
+    }
+
+    #[test]
+    fn test_sed() {
+        // `nom/s/...` leaves the rest of the expression in the rest-field
+        assert_eq!(nom_args::parse("s/teh/the/g").map(|c| (c.name, c.rest)), Ok(("s".into(), "teh/the/g".into())));
+
+        assert_eq!(sed("s/teh/the/", "teh cat and teh hat"), Ok("the cat and teh hat".into()));
+        assert_eq!(sed("s/teh/the/g", "teh cat and teh hat"), Ok("the cat and the hat".into()));
+        assert_eq!(sed("s/teh/the", "teh cat"), Ok("the cat".into()));
+        assert_eq!(sed("s/TEH/the/gi", "Teh cat"), Ok("the cat".into()));
+        assert_eq!(sed(r"s/(\w+) (\w+)/\2 \1 [&]/", "hello world"), Ok("world hello [hello world]".into()));
+        assert_eq!(sed(r"s|/|\||g", "a/b/c"), Ok("a|b|c".into()));
+        assert_eq!(sed(r"s/a\/b/x\&y/", "a/b"), Ok("x&y".into()));
+        assert_eq!(sed("s/x*/-/g", "abc"), Ok("-a-b-c-".into()));
+        assert_eq!(sed("s/x//", "abc"), Err("the regex does not match your last message".into()));
+
+        assert_eq!(sed("s/a/b/q", "a").unwrap_err(), "unknown flag 'q'; the flags are `g` and `i`");
+        assert_eq!(sed(r"s/(a)/\2/", "a").unwrap_err(), "there is no group 2 in the regex");
+        assert_eq!(sed("s/a/b/c/d", "a").unwrap_err(), "expected `s/<regex>/<replacement>/[flags]`");
+        assert_eq!(sed("y/a/b/", "a").unwrap_err(), "expected `s/<regex>/<replacement>/`");
+        assert!(sed("s/(/b/", "a").unwrap_err().starts_with("invalid regex"));
+
+        let text = sed("s/hi/hey/", "hi <@5> <@&6> @everyone").unwrap();
+        assert_eq!(meant("en", "al", &text), "**al** meant: hey <@\u{200b}5> <@\u{200b}&6> @\u{200b}everyone");
+    }
+
+    #[test]
+    fn test_sed_limits() {
+        assert_eq!(sed("s/a{1000}{1000}/b/", "a").unwrap_err(), "the regex is too big");
+        assert!(sed(&format!("s/{}a{}/b/", "(".repeat(40), ")".repeat(40)), "a").is_err());
+        assert_eq!(sed(&format!("s/{}/b/", "a".repeat(201)), "a").unwrap_err(), "the regex is longer than 200 characters");
+        assert_eq!(sed("s/a/&&&&&&&&&&/g", &"a".repeat(1000)).unwrap_err(), "the result is longer than 1900 characters");
+    }
+}
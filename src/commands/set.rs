@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use serenity::model::Permissions;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::defn::globals::VariablesTmk;
+use crate::i18n;
+use crate::nom_args::{ self, Literal, OptSpec };
+use crate::nom_util as util;
+use crate::variables::{ self, BUILTINS, MAX_PER_SCOPE, MAX_VALUE_LEN };
+
+const OPTIONS: &[OptSpec] = &[OptSpec { name: "channel", shorts: &[('c', "yes")], switch: true }];
+
+pub struct SetHandler;
+
+/// Whether an author with `perms` in a guild channel may change the channel's variables. Anyone may
+/// in a direct message (`perms` is then None and `in_guild` false).
+fn may_change_channel(in_guild: bool, perms: Option<Permissions>) -> bool {
+    !in_guild || perms.is_some_and(|p| p.intersects(Permissions::ADMINISTRATOR | Permissions::MANAGE_GUILD | Permissions::MANAGE_CHANNELS))
+}
+
+#[async_trait]
+impl CommandHandler for SetHandler {
+    async fn whatis(&self, name: &str, locale: &str) -> String {
+        if name == "unset" {
+            i18n::tr(locale, "set-whatis-unset", &[])
+        } else {
+            i18n::tr(locale, "set-whatis", &[])
+        }
+    }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["set", "unset"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec { subcommands: vec![("help", Spec::default())], options: OPTIONS, ..Spec::default() }
+    }
+    async fn call(&self, Command { name, args, rest }: Command, ctx: Context, msg: Message) {
+        let locale = util::locale(&ctx, &msg).await;
+        if !rest.is_empty() {
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {}\n```", i18n::tr(&locale, "error-no-rest", &[]))).await;
+            return;
+        }
+        let (opts, args) = match nom_args::take_options(args, OPTIONS) {
+            Ok(x) => x,
+            Err(why) => {
+                let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {}\n```", why.tr(&locale))).await;
+                return;
+            }
+        };
+        let mut channel = false;
+        for (_, v) in opts.iter() {
+            match bool::from_literal(v) {
+                Some(c) => channel = c,
+                None => {
+                    let why = i18n::tr(&locale, "set-bad-channel", &[("expected", bool::expected().tr(&locale).into()), ("value", format!("{v:?}").into())]);
+                    let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {why}\n```")).await;
+                    return;
+                }
+            }
+        }
+        let mut assignments = vec![];
+        for a in args.iter() {
+            match a {
+                Arg::Pos(s) if s == "help" => {
+                    let _: Option<_> = util::try_reply(&ctx, &msg, i18n::tr(&locale, "set-help", &[])).await;
+                    return;
+                }
+                Arg::Pos(k) if name == "unset" => assignments.push((k, None)),
+                Arg::Kw(k, v) if name == "set" => assignments.push((k, Some(v))),
+                _ => {
+                    let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {}\n```", i18n::tr(&locale, "set-see-help", &[]))).await;
+                    return;
+                }
+            }
+        }
+        for (k, v) in assignments.iter() {
+            let why = if !variables::is_name(k) {
+                i18n::tr(&locale, "set-bad-name", &[("name", format!("{k:?}").into())])
+            } else if BUILTINS.contains(&&k[..]) {
+                i18n::tr(&locale, "set-builtin", &[("name", k.as_str().into())])
+            } else if v.is_some_and(|v| v.len() > MAX_VALUE_LEN) {
+                i18n::tr(&locale, "set-too-long", &[("name", k.as_str().into()), ("max", MAX_VALUE_LEN.into())])
+            } else {
+                continue;
+            };
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {why}\n```")).await;
+            return;
+        }
+
+        if channel && !assignments.is_empty()
+            && !may_change_channel(msg.guild_id.is_some(), util::channel_permissions(&ctx, &msg).await) {
+            let why = i18n::tr(&locale, "set-channel-denied", &[]);
+            let _: Option<_> = util::try_reply_error(&ctx, &msg, format!("```\n{name}: {why}\n```")).await;
+            return;
+        }
+
+        let store = {
+            let data = ctx.data.read().await;
+            data.get::<VariablesTmk>().expect("Variables do not exist!").clone()
+        };
+        if assignments.is_empty() {
+            let vars = store.read().await;
+            let mut lines = vec![];
+            for (key, v) in [("set-listed-user", vars.users.get(&msg.author.id.0)), ("set-listed-channel", vars.channels.get(&msg.channel_id.0))] {
+                for (k, v) in v.into_iter().flatten() {
+                    lines.push(i18n::tr(&locale, key, &[("name", k.as_str().into()), ("value", format!("{v:?}").into())]));
+                }
+            }
+            let reply = if lines.is_empty() { i18n::tr(&locale, "set-none", &[]) } else { format!("```\n{}\n```", lines.join("\n")) };
+            let _: Option<_> = util::try_reply(&ctx, &msg, reply).await;
+            return;
+        }
+
+        let id = if channel { msg.channel_id.0 } else { msg.author.id.0 };
+        let result = store.update(|vars| {
+            let scope = if channel { &mut vars.channels } else { &mut vars.users };
+            let mut v = scope.get(&id).cloned().unwrap_or_default();
+            for (k, val) in assignments.iter() {
+                match val {
+                    Some(val) => { v.insert(k.to_string(), val.to_string()); }
+                    None => { v.remove(&k[..]); }
+                }
+            }
+            if v.len() > MAX_PER_SCOPE {
+                return true;
+            }
+            if v.is_empty() {
+                scope.remove(&id);
+            } else {
+                scope.insert(id, v);
+            }
+            false
+        }).await;
+        let reply = match result {
+            Ok(false) => Ok(i18n::tr(&locale, "set-done", &[])),
+            Ok(true) => Err(format!("```\n{name}: {}\n```", i18n::tr(&locale, "set-too-many", &[("max", MAX_PER_SCOPE.into())]))),
+            Err(why) => {
+                tracing::error!(error = %why, "cannot save variables");
+                Err(format!("```\n{name}: {}\n```", i18n::tr(&locale, "error-not-saved", &[])))
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_may_change_channel() {
+        assert!(may_change_channel(false, None));
+        assert!(!may_change_channel(true, None));
+        assert!(!may_change_channel(true, Some(Permissions::SEND_MESSAGES | Permissions::MANAGE_MESSAGES)));
+        assert!(may_change_channel(true, Some(Permissions::SEND_MESSAGES | Permissions::MANAGE_CHANNELS)));
+        assert!(may_change_channel(true, Some(Permissions::MANAGE_GUILD)));
+        assert!(may_change_channel(true, Some(Permissions::ADMINISTRATOR)));
+    }
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use crate::defn::command::{
+    Arg, Command, Context, Message, ClientData, Spec,
+    CommandHandler,
+};
+use crate::i18n::{ self, Msg };
+use crate::nom_args::{ self, OptSpec };
+use crate::nom_util as util;
+use crate::transform::{ self, Collation, Scope, Transform, TRANSFORMS };
+
+const OPTIONS: &[OptSpec] = &[
+    OptSpec { name: "scope", shorts: &[('g', "grapheme"), ('c', "char"), ('w', "word"), ('l', "line")], switch: false },
+    OptSpec { name: "seed", shorts: &[], switch: false },
+    OptSpec { name: "collation", shorts: &[], switch: false },
+    OptSpec { name: "numeric", shorts: &[('n', "yes")], switch: true },
+    OptSpec { name: "ignore-case", shorts: &[('i', "yes")], switch: true },
+];
+
+const MAX_TRANSFORMS: usize = 10;
+/// The longest text a transform may produce before the next one, in bytes.
+const MAX_WORK_LEN: usize = 16_000;
+const MAX_REPLY_LEN: usize = 2000;
+
+pub struct TrHandler;
+
+fn run(transforms: &[Transform], text: &str, scope: Scope, collation: &Collation, seed: Option<u64>) -> Result<String, Msg> {
+    let mut rng = transform::rng(seed);
+    let mut text = text.to_string();
+    for t in transforms.iter() {
+        text = t.apply(&text, scope, collation, &mut rng)?;
+        if text.len() > MAX_WORK_LEN {
+            return Err(Msg::new("tr-grew-too-long", &[]));
+        }
+    }
+    if text.chars().count() > MAX_REPLY_LEN {
+        Err(Msg::new("tr-too-long", &[("max", MAX_REPLY_LEN.into())]))
+    } else if text.trim().is_empty() {
+        Err(Msg::new("tr-empty", &[]))
+    } else {
+        Ok(text)
+    }
+}
+
+#[async_trait]
+impl CommandHandler for TrHandler {
+    async fn whatis(&self, _: &str, locale: &str) -> String { i18n::tr(locale, "tr-whatis", &[]) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        vec!["tr"]
+    }
+    fn spec(&self, _: &str) -> Spec {
+        Spec {
+            subcommands: std::iter::once("help").chain(TRANSFORMS.iter().map(|(n, _, _)| *n)).map(|n| (n, Spec::default())).collect(),
+            keys: vec![
+                ("scope", Scope::NAMES), ("seed", &[]), ("collation", &[]),
+                ("numeric", &["yes", "no"]), ("ignore-case", &["yes", "no"]),
+            ],
+            options: OPTIONS,
+        }
+    }
+    async fn call(&self, Command { name: _, args, rest }: Command, ctx: Context, msg: Message) {
+        enum Sc { // subcommand
+            Help,
+            Tr { transforms: Vec<Transform>, scope: Scope, seed: Option<u64>, collation: Option<String>, numeric: bool, ignore_case: bool },
+        }
+        let locale = util::locale(&ctx, &msg).await;
+        let sc = nom_args::take_options(args, OPTIONS).map_err(|e| e.tr(&locale)).and_then(|(opts, args)| {
+            let (mut scope, mut seed, mut transforms) = (None, None, vec![]);
+            let (mut collation, mut numeric, mut ignore_case) = (None, None, None);
+            let opts = opts.into_iter().map(|(k, v)| Arg::Kw(k.into(), v));
+            for a in opts.chain(args) {
+                match &a {
+                    Arg::Pos(s) if s == "help" => return Ok(Sc::Help),
+                    Arg::Pos(s) => match Transform::from_name(s) {
+                        Some(t) => transforms.push(t),
+                        None => return Err(i18n::tr(&locale, "tr-unknown-transform", &[("name", format!("{s:?}").into())])),
+                    },
+                    Arg::Kw(k, _) if (k == "scope" && scope.is_some()) || (k == "seed" && seed.is_some())
+                        || (k == "collation" && collation.is_some()) || (k == "numeric" && numeric.is_some())
+                        || (k == "ignore-case" && ignore_case.is_some()) =>
+                        return Err(i18n::tr(&locale, "tr-option-repeated", &[("option", k.as_str().into())])),
+                    Arg::Kw(k, v) if k == "scope" => match Scope::from_name(v) {
+                        Some(s) => scope = Some(s),
+                        None => return Err(i18n::tr(&locale, "tr-bad-scope", &[("scopes", Scope::NAMES.join(", ").into())])),
+                    },
+                    Arg::Kw(k, _) if k == "seed" => match a.literal::<i64>().map_err(|e| e.tr(&locale))? {
+                        n if n >= 0 => seed = Some(n as u64),
+                        _ => return Err(i18n::tr(&locale, "tr-negative-seed", &[])),
+                    },
+                    Arg::Kw(k, v) if k == "collation" => collation = Some(v.clone()),
+                    Arg::Kw(k, _) if k == "numeric" => numeric = Some(a.literal::<bool>().map_err(|e| e.tr(&locale))?),
+                    Arg::Kw(k, _) if k == "ignore-case" => ignore_case = Some(a.literal::<bool>().map_err(|e| e.tr(&locale))?),
+                    Arg::Kw(k, _) => return Err(i18n::tr(&locale, "tr-unknown-option", &[("option", format!("{k:?}").into())])),
+                    Arg::Code { .. } => return Err(i18n::tr(&locale, "tr-no-code", &[])),
+                }
+            }
+            match transforms.len() {
+                0 => Err(i18n::tr(&locale, "tr-no-transforms", &[])),
+                n if n > MAX_TRANSFORMS => Err(i18n::tr(&locale, "tr-too-many", &[("max", MAX_TRANSFORMS.into())])),
+                _ => Ok(Sc::Tr {
+                    transforms, scope: scope.unwrap_or(Scope::Grapheme), seed, collation,
+                    numeric: numeric.unwrap_or(false), ignore_case: ignore_case.unwrap_or(false),
+                }),
+            }
+        });
+        let reply = match sc {
+            Ok(Sc::Help) => {
+                let list: Vec<_> = TRANSFORMS.iter().map(|(n, _, what)| format!("`{n}`: {}", i18n::tr(&locale, what, &[]))).collect();
+                let _: Option<_> = util::try_reply(&ctx, &msg, format!("{}\n{}", i18n::tr(&locale, "tr-help", &[]), list.join("\n"))).await;
+                return;
+            }
+            Ok(Sc::Tr { transforms, scope, seed, collation, numeric, ignore_case }) => {
+                Collation::new(collation.as_deref(), numeric, ignore_case, &locale)
+                    .and_then(|collation| run(&transforms, &rest, scope, &collation, seed))
+                    .map_err(|why| why.tr(&locale))
+            }
+            Err(why) => Err(why),
+        };
+        // like `nom/echo`, do not let the bot feed itself commands
+        let reply = match reply {
+            Ok(text) if util::feeds_itself(&ctx, &msg, &text).await => Err(i18n::tr(&locale, "tr-refuse-commands", &[])),
+            r => r,
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply.map_err(|why| format!("```\ntr: {why}\n```"))).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let run = |t: &[Transform], text| run(t, text, Scope::Char, &Collation::Codepoint, None).map_err(|why| why.tr("en"));
+        assert_eq!(run(&[Transform::Unbase64, Transform::Rot13], "QWJ6IGFieg=="), Ok("Nom nom".into()));
+        assert_eq!(run(&[Transform::Hex; 10], "nom"), Err("the text grew too long".into()));
+        assert_eq!(run(&[Transform::Dedupe], "   "), Err("the result is empty".into()));
+        // every transform is described
+        for (name, _, what) in TRANSFORMS.iter() {
+            assert_ne!(i18n::tr("en", what, &[]), *what, "{name}");
+        }
+    }
+}
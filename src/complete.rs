@@ -0,0 +1,165 @@
+//! Completion of partial commands, e.g. `echo sco` to `echo scope=`.
+//!
+//! Commands describe what they accept with a [`Spec`] (see `CommandHandler::spec`), and
+//! [`complete`] works out which word of the input is being typed and what could go there.
+
+use crate::nom_args::{ self, ArgTemplate, OptSpec, Segment, Word };
+
+/// What a command accepts after its name, as far as completion is concerned.
+#[derive(Default)]
+pub struct Spec {
+    /// Positional words selecting a subcommand, and what the subcommand accepts in turn.
+    pub subcommands: Vec<(&'static str, Spec)>,
+    /// `key=value` arguments, with their values if there are only a few.
+    pub keys: Vec<(&'static str, &'static [&'static str])>,
+    pub options: &'static [OptSpec],
+}
+
+/// Candidates for the word being typed, each replacing the input from `start` (a byte offset, as
+/// in `nom_args::ParseError`) to its end.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+/// Complete `input`, the text after the prefix, given the command `names` and their specs.
+pub fn complete(input: &str, names: &[&str], spec: impl Fn(&str) -> Option<Spec>) -> Completion {
+    let start = input.rfind(char::is_whitespace).map_or(0, |i| i + input[i..].chars().next().unwrap().len_utf8());
+    let (head, word) = input.split_at(start);
+    let none = Completion { start, candidates: vec![] };
+    // quotes, variables, code, the rest-field and scripts are not completed
+    if word.contains(['"', '\'', '`', '$', '(', ')', '/', '{', '}', ';', '&']) || nom_args::is_script(input) {
+        return none;
+    }
+    if start == 0 {
+        let candidates = names.iter().filter(|n| n.starts_with(word)).map(|n| n.to_string()).collect();
+        return Completion { start, candidates };
+    }
+    let t = match nom_args::parse_template(head) {
+        Ok(t) if t.rest.0.is_empty() => t,
+        _ => return none,
+    };
+    let Some(root) = spec(&t.name) else { return none };
+
+    // follow the subcommands named so far
+    let (mut spec, mut subcommands, mut options, mut used, mut value_of) = (&root, true, true, vec![], None);
+    for a in t.args.iter() {
+        value_of = None;
+        match a {
+            ArgTemplate::Pos(w) => match literal(w) {
+                Some("--") if options => options = false,
+                Some(s) if options && s.starts_with('-') => {
+                    let name = s.trim_start_matches('-');
+                    value_of = spec.options.iter().find(|o| o.name == name && !o.switch).map(|o| o.name);
+                }
+                Some(s) if subcommands => match spec.subcommands.iter().find(|(n, _)| *n == s) {
+                    Some((_, sub)) => spec = sub,
+                    None => subcommands = false,
+                },
+                _ => subcommands = false,
+            },
+            ArgTemplate::Kw(k, _) | ArgTemplate::Code { key: Some(k), .. } => used.push(k.trim_start_matches('-')),
+            ArgTemplate::Code { key: None, .. } => subcommands = false,
+        }
+    }
+
+    let values = |key: &str| spec.keys.iter().find(|(k, _)| *k == key).map_or(&[][..], |(_, vs)| vs);
+    let candidates: Vec<String> = if let Some(key) = value_of {
+        values(key).iter().filter(|v| v.starts_with(word)).map(|v| v.to_string()).collect()
+    } else if let Some((key, value)) = word.split_once('=') {
+        values(key).iter().filter(|v| v.starts_with(value)).map(|v| format!("{key}={v}")).collect()
+    } else if word.starts_with('-') {
+        if !options { return none; }
+        spec.options.iter()
+            .flat_map(|o| [Some(format!("--{}", o.name)), o.switch.then(|| format!("--no-{}", o.name))])
+            .flatten()
+            .filter(|o| o.starts_with(word))
+            .collect()
+    } else {
+        let subs = spec.subcommands.iter().filter(|_| subcommands).map(|(n, _)| n.to_string());
+        let keys = spec.keys.iter().filter(|(k, _)| !used.contains(k)).map(|(k, _)| format!("{k}="));
+        subs.chain(keys).filter(|c| c.starts_with(word)).collect()
+    };
+    Completion { start, candidates }
+}
+
+fn literal(w: &Word) -> Option<&str> {
+    match &w.0[..] {
+        [] => Some(""),
+        [Segment::Lit(s)] => Some(s),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nom_args::Arg;
+
+    const OPTIONS: &[OptSpec] = &[
+        OptSpec { name: "sorted", shorts: &[('s', "yes")], switch: true },
+        OptSpec { name: "scope", shorts: &[], switch: false },
+    ];
+
+    fn spec(name: &str) -> Option<Spec> {
+        match name {
+            "echo" => Some(Spec {
+                subcommands: vec![("help", Spec::default())],
+                keys: vec![("sorted", &["yes", "no"]), ("scope", &["char", "line"])],
+                options: OPTIONS,
+            }),
+            "script" => Some(Spec {
+                subcommands: vec![
+                    ("show", Spec { keys: vec![("name", &[])], ..Spec::default() }),
+                    ("set", Spec::default()),
+                ],
+                ..Spec::default()
+            }),
+            _ => None,
+        }
+    }
+
+    fn complete(input: &str) -> (usize, Vec<String>) {
+        let c = super::complete(input, &["echo", "eval", "nom", "script"], spec);
+        (c.start, c.candidates)
+    }
+
+    #[test]
+    fn test_complete() {
+        let s = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(complete(""), (0, s(&["echo", "eval", "nom", "script"])));
+        assert_eq!(complete("e"), (0, s(&["echo", "eval"])));
+        assert_eq!(complete("echo sco"), (5, s(&["scope="])));
+        assert_eq!(complete("echo scope="), (5, s(&["scope=char", "scope=line"])));
+        assert_eq!(complete("echo scope=l"), (5, s(&["scope=line"])));
+        assert_eq!(complete("echo  sorted=yes "), (17, s(&["help", "scope="])));
+        assert_eq!(complete("echo --s"), (5, s(&["--sorted", "--scope"])));
+        assert_eq!(complete("echo --no"), (5, s(&["--no-sorted"])));
+        assert_eq!(complete("echo --scope c"), (13, s(&["char"])));
+        assert_eq!(complete("echo -- --s"), (8, s(&[])));
+        assert_eq!(complete("script "), (7, s(&["show", "set"])));
+        assert_eq!(complete("script show n"), (12, s(&["name="])));
+        assert_eq!(complete("script nope "), (12, s(&[])));
+        assert_eq!(complete("echo/sco"), (0, s(&[])));
+        assert_eq!(complete("echo/a sco"), (7, s(&[])));
+        assert_eq!(complete("echo x=\"a sco"), (10, s(&[])));
+        assert_eq!(complete("nom !"), (4, s(&[])));
+
+        // a candidate put in place of the word it completes parses as that word
+        for input in ["echo sco", "echo  scope=", "echo\u{3000}--s", "script sh"] {
+            let (start, candidates) = complete(input);
+            for c in candidates.into_iter() {
+                // keys need a value to parse
+                let c = if c.ends_with('=') { c + "x" } else { c };
+                let cmd = nom_args::parse(&format!("{}{c}", &input[..start])).unwrap();
+                let last = match cmd.args.last().unwrap() {
+                    Arg::Pos(p) => p.clone(),
+                    Arg::Kw(k, v) => format!("{k}={v}"),
+                    a => panic!("{a:?}"),
+                };
+                assert_eq!(last, c);
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::path::{ Path, PathBuf };
+
+use serde::Deserialize;
+use serenity::model::gateway::GatewayIntents;
+
+use crate::logging::LogFormat;
+
+pub const DEFAULT_PATH: &str = "nombot.toml";
+
+// The on-disk shape of the config file. Everything is optional here so that env var overrides can
+// fill the gaps; `Config::resolve` decides what is actually required.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    prefix: Option<String>,
+    storage: Option<PathBuf>,
+    plugins: Option<PathBuf>,
+    intents: Option<Vec<String>>,
+    #[serde(default)]
+    owners: Vec<u64>,
+    #[serde(default)]
+    token: RawToken,
+    #[serde(default)]
+    logging: RawLogging,
+    #[serde(default)]
+    commands: RawCommands,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawToken {
+    value: Option<String>,
+    env: Option<String>,
+    file: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawLogging {
+    level: Option<String>,
+    format: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCommands {
+    enabled: Option<Vec<String>>,
+    #[serde(default)]
+    settings: HashMap<String, toml::Table>,
+}
+
+pub struct Config {
+    pub token: String,
+    /// The command prefix, without the trailing '/'.
+    pub prefix: String,
+    pub intents: GatewayIntents,
+    /// Ids of the users allowed to run bot-wide admin commands.
+    pub owners: Vec<u64>,
+    /// `None` enables every registered command.
+    pub enabled_commands: Option<Vec<String>>,
+    pub command_settings: HashMap<String, toml::Table>,
+    pub log_level: String,
+    pub log_format: LogFormat,
+    pub storage_path: PathBuf,
+    /// Directory of command pack plugins, if any.
+    pub plugin_path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Read the config file at `path` (if any) and apply overrides from the process environment.
+    /// A missing file is only an error when the path was given explicitly.
+    pub fn load(path: Option<&Path>) -> Result<Self, Vec<String>> {
+        let text = match path {
+            Some(p) => Some(
+                std::fs::read_to_string(p).map_err(|e| vec![format!("cannot read {}: {e}", p.display())])?
+            ),
+            None => std::fs::read_to_string(DEFAULT_PATH).ok(),
+        };
+        Self::parse(text.as_deref(), |k| std::env::var(k).ok())
+    }
+
+    pub fn parse(text: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Result<Self, Vec<String>> {
+        let raw: RawConfig = match text {
+            Some(t) => toml::from_str(t).map_err(|e| vec![format!("malformed config file: {e}")])?,
+            None => RawConfig::default(),
+        };
+        Self::resolve(raw, env)
+    }
+
+    fn resolve(raw: RawConfig, env: impl Fn(&str) -> Option<String>) -> Result<Self, Vec<String>> {
+        let mut errors = vec![];
+
+        // token: DISCORD_TOKEN always wins, then exactly one of token.value/env/file
+        let token = match env("DISCORD_TOKEN") {
+            Some(t) => Some(t),
+            None => match (raw.token.value, raw.token.env, raw.token.file) {
+                (None, None, None) => {
+                    errors.push("no bot token; set token.value, token.env or token.file, or DISCORD_TOKEN".into());
+                    None
+                }
+                (Some(t), None, None) => Some(t),
+                (None, Some(k), None) => {
+                    let t = env(&k);
+                    if t.is_none() { errors.push(format!("token.env names {k:?}, which is not set")); }
+                    t
+                }
+                (None, None, Some(p)) => match std::fs::read_to_string(&p) {
+                    Ok(t) => Some(t.trim().into()),
+                    Err(e) => {
+                        errors.push(format!("token.file: cannot read {}: {e}", p.display()));
+                        None
+                    }
+                },
+                _ => {
+                    errors.push("token.value, token.env and token.file are mutually exclusive".into());
+                    None
+                }
+            },
+        };
+        if token.as_deref() == Some("") { errors.push("the bot token is empty".into()); }
+
+        let prefix = env("PREFIX").or(raw.prefix);
+        match &prefix {
+            None => errors.push("no command prefix; set prefix or PREFIX".into()),
+            Some(p) if p.is_empty() => errors.push("prefix is empty".into()),
+            Some(p) if p.contains(|c: char| c.is_whitespace() || c == '/') =>
+                errors.push(format!("prefix {p:?} must not contain whitespace or '/'")),
+            Some(_) => (),
+        }
+
+        let intents = match raw.intents {
+            None => GatewayIntents::GUILD_MESSAGES
+                | GatewayIntents::GUILD_MESSAGE_REACTIONS
+                | GatewayIntents::DIRECT_MESSAGES
+                | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+                | GatewayIntents::MESSAGE_CONTENT,
+            Some(names) => names.iter().fold(GatewayIntents::empty(), |acc, n| match intent_by_name(n) {
+                Some(i) => acc | i,
+                None => {
+                    errors.push(format!("unknown intent {n:?}"));
+                    acc
+                }
+            }),
+        };
+
+        let log_level = env("LOG_LEVEL").or(raw.logging.level).unwrap_or_else(|| "info".into());
+        if let Err(e) = tracing_subscriber::EnvFilter::try_new(&log_level) {
+            errors.push(format!("invalid log level {log_level:?}: {e}"));
+        }
+        let log_format = env("LOG_FORMAT").or(raw.logging.format)
+            .map(|f| f.parse())
+            .unwrap_or(Ok(LogFormat::Text))
+            .unwrap_or_else(|e| {
+                errors.push(e);
+                LogFormat::Text
+            });
+
+        let storage_path = env("NOMBOT_STORAGE").map(PathBuf::from).or(raw.storage)
+            .unwrap_or_else(|| "data".into());
+        if storage_path.exists() && !storage_path.is_dir() {
+            errors.push(format!("storage path {} is not a directory", storage_path.display()));
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(Self {
+            token: token.unwrap(),
+            prefix: prefix.unwrap(),
+            intents,
+            owners: raw.owners,
+            enabled_commands: raw.commands.enabled,
+            command_settings: raw.commands.settings,
+            log_level,
+            log_format,
+            storage_path,
+            plugin_path: raw.plugins,
+        })
+    }
+
+    /// Check the parts of the config that refer to registered commands.
+    pub fn validate_commands(&self, registered: &[&str]) -> Vec<String> {
+        let mut errors = vec![];
+        for name in self.enabled_commands.iter().flatten() {
+            if !registered.contains(&&name[..]) {
+                errors.push(format!("commands.enabled: unknown command {name:?}"));
+            }
+        }
+        for name in self.command_settings.keys() {
+            if !registered.contains(&&name[..]) {
+                errors.push(format!("commands.settings: unknown command {name:?}"));
+            }
+        }
+        errors
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled_commands.as_ref().is_none_or(|v| v.iter().any(|n| n == name))
+    }
+}
+
+fn intent_by_name(name: &str) -> Option<GatewayIntents> {
+    Some(match name {
+        "GUILDS" => GatewayIntents::GUILDS,
+        "GUILD_MEMBERS" => GatewayIntents::GUILD_MEMBERS,
+        "GUILD_MESSAGES" => GatewayIntents::GUILD_MESSAGES,
+        "GUILD_MESSAGE_REACTIONS" => GatewayIntents::GUILD_MESSAGE_REACTIONS,
+        "DIRECT_MESSAGES" => GatewayIntents::DIRECT_MESSAGES,
+        "DIRECT_MESSAGE_REACTIONS" => GatewayIntents::DIRECT_MESSAGE_REACTIONS,
+        "MESSAGE_CONTENT" => GatewayIntents::MESSAGE_CONTENT,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> { None }
+
+    #[test]
+    fn test_parse() {
+        let c = Config::parse(Some(r#"
+            prefix = "nom"
+            intents = ["GUILD_MESSAGES", "MESSAGE_CONTENT"]
+            [token]
+            value = "abc"
+            [logging]
+            format = "json"
+            [commands]
+            enabled = ["echo"]
+            [commands.settings.echo]
+            max-length = 100
+        "#), no_env).ok().unwrap();
+        assert_eq!(c.prefix, "nom");
+        assert_eq!(c.token, "abc");
+        assert_eq!(c.intents, GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT);
+        assert_eq!(c.log_format, LogFormat::Json);
+        assert!(c.is_enabled("echo") && !c.is_enabled("nom"));
+        assert!(c.validate_commands(&["echo", "nom"]).is_empty());
+        assert_eq!(c.validate_commands(&["nom"]).len(), 2);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let env = |k: &str| match k {
+            "DISCORD_TOKEN" => Some("from-env".into()),
+            "PREFIX" => Some("nom2".into()),
+            _ => None,
+        };
+        let c = Config::parse(Some("prefix = \"nom\"\ntoken.value = \"abc\""), env).ok().unwrap();
+        assert_eq!((&c.token[..], &c.prefix[..]), ("from-env", "nom2"));
+        let c = Config::parse(None, env).ok().unwrap();
+        assert_eq!(c.prefix, "nom2");
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors = Config::parse(Some(r#"
+            prefix = "n m"
+            intents = ["GUILD_MESSAGES", "TELEPATHY"]
+            [logging]
+            format = "xml"
+        "#), no_env).err().unwrap();
+        assert_eq!(errors.len(), 4);
+        assert!(Config::parse(Some("prefx = \"nom\""), no_env).is_err());
+    }
+}
@@ -3,7 +3,9 @@ use async_trait::async_trait;
 use serenity::prelude::*;
 pub use serenity::prelude::Context;
 pub use serenity::model::channel::Message;
+pub use crate::complete::Spec;
 pub use crate::nom_args::{ Arg, Command };
+use crate::i18n;
 
 pub type ClientData = Arc<RwLock<TypeMap>>;
 
@@ -11,5 +13,10 @@ pub type ClientData = Arc<RwLock<TypeMap>>;
 pub trait CommandHandler: Send + Sync {
     async fn register(&mut self, data: ClientData) -> Vec<&'static str>;
     async fn call(&self, cmd: Command, ctx: Context, msg: Message);
-    async fn whatis(&self, _: &str) -> String { "(nothing appropriate)".into() }
+    /// One line on what the command `name` does, in `locale`.
+    async fn whatis(&self, _name: &str, locale: &str) -> String { i18n::tr(locale, "whatis-default", &[]) }
+    /// Validate the `[commands.settings.<name>]` table of the config for the command `name`.
+    async fn check_settings(&self, _name: &str, _settings: &toml::Table) -> Result<(), String> { Ok(()) }
+    /// What the command `name` accepts, for completion.
+    fn spec(&self, _name: &str) -> Spec { Spec::default() }
 }
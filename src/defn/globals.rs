@@ -1,11 +1,65 @@
-use std::collections::HashMap;
 use std::sync::Arc;
 use serenity::prelude::*;
-use crate::defn::command::CommandHandler;
+use crate::config::Config;
+use crate::defn::guild::GuildSettings;
+use crate::defn::registry::CommandRegistry;
+use crate::i18n::Locales;
+use crate::polls::Polls;
+use crate::quotes::Quotes;
+use crate::recent::RecentMessages;
+use crate::reminders::Reminders;
+use crate::script::GuildScripts;
+use crate::storage::JsonStore;
+use crate::variables::Variables;
 
-pub type CommandMap = Arc<RwLock<HashMap< &'static str, Arc<dyn CommandHandler> >>>;
+pub type CommandMap = Arc<RwLock<CommandRegistry>>;
 
 pub struct CommandMapTmk;
 impl TypeMapKey for CommandMapTmk {
     type Value = CommandMap;
 }
+
+pub struct ConfigTmk;
+impl TypeMapKey for ConfigTmk {
+    type Value = Arc<Config>;
+}
+
+pub struct GuildSettingsTmk;
+impl TypeMapKey for GuildSettingsTmk {
+    type Value = Arc<JsonStore<GuildSettings>>;
+}
+
+pub struct LocalesTmk;
+impl TypeMapKey for LocalesTmk {
+    type Value = Arc<JsonStore<Locales>>;
+}
+
+pub struct PollsTmk;
+impl TypeMapKey for PollsTmk {
+    type Value = Arc<Polls>;
+}
+
+pub struct QuotesTmk;
+impl TypeMapKey for QuotesTmk {
+    type Value = Arc<JsonStore<Quotes>>;
+}
+
+pub struct RecentTmk;
+impl TypeMapKey for RecentTmk {
+    type Value = Arc<Mutex<RecentMessages>>;
+}
+
+pub struct RemindersTmk;
+impl TypeMapKey for RemindersTmk {
+    type Value = Arc<Reminders>;
+}
+
+pub struct ScriptsTmk;
+impl TypeMapKey for ScriptsTmk {
+    type Value = Arc<JsonStore<GuildScripts>>;
+}
+
+pub struct VariablesTmk;
+impl TypeMapKey for VariablesTmk {
+    type Value = Arc<JsonStore<Variables>>;
+}
@@ -0,0 +1,69 @@
+use std::collections::{ BTreeMap, BTreeSet };
+use serde::{ Deserialize, Serialize };
+use serenity::model::id::{ ChannelId, GuildId };
+
+/// How a single guild has customized the available commands.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuildCommands {
+    /// Commands that cannot be used anywhere in the guild.
+    #[serde(default)]
+    pub disabled: BTreeSet<String>,
+    /// Commands that can only be used in the listed channels.
+    #[serde(default)]
+    pub channels: BTreeMap<String, BTreeSet<u64>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    Disabled,
+    /// Restricted to other channels of the guild.
+    NotInChannel,
+}
+
+/// Per-guild settings, keyed by guild id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GuildSettings {
+    #[serde(default)]
+    pub commands: BTreeMap<u64, GuildCommands>,
+}
+
+impl GuildSettings {
+    /// Whether the command `name` may be invoked in `channel`. Direct messages (no guild) are
+    /// never restricted.
+    pub fn availability(&self, guild: Option<GuildId>, channel: ChannelId, name: &str) -> Availability {
+        let Some(gc) = guild.and_then(|g| self.commands.get(&g.0)) else {
+            return Availability::Available;
+        };
+        if gc.disabled.contains(name) {
+            Availability::Disabled
+        } else if gc.channels.get(name).is_some_and(|cs| !cs.contains(&channel.0)) {
+            Availability::NotInChannel
+        } else {
+            Availability::Available
+        }
+    }
+
+    pub fn is_available(&self, guild: Option<GuildId>, channel: ChannelId, name: &str) -> bool {
+        self.availability(guild, channel, name) == Availability::Available
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_availability() {
+        let mut s = GuildSettings::default();
+        let (g, c1, c2) = (GuildId(1), ChannelId(10), ChannelId(11));
+        let gc = s.commands.entry(g.0).or_default();
+        gc.disabled.insert("echo".into());
+        gc.channels.insert("nom".into(), [c1.0].into());
+        assert_eq!(s.availability(Some(g), c1, "echo"), Availability::Disabled);
+        assert_eq!(s.availability(Some(g), c1, "nom"), Availability::Available);
+        assert_eq!(s.availability(Some(g), c2, "nom"), Availability::NotInChannel);
+        assert_eq!(s.availability(Some(GuildId(2)), c2, "echo"), Availability::Available);
+        assert_eq!(s.availability(None, c2, "echo"), Availability::Available);
+    }
+}
@@ -1,2 +1,5 @@
 pub mod command;
 pub mod globals;
+pub mod guild;
+pub mod registry;
+pub mod subcommand;
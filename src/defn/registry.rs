@@ -0,0 +1,210 @@
+use std::collections::{ BTreeMap, HashMap };
+use std::sync::Arc;
+use crate::defn::command::{ ClientData, CommandHandler };
+use crate::defn::globals::CommandMap;
+use crate::i18n;
+
+/// Creates a fresh, unregistered handler for a module.
+pub type ModuleFactory = Arc<dyn Fn() -> Box<dyn CommandHandler> + Send + Sync>;
+
+struct Module {
+    factory: ModuleFactory,
+    /// The handler and the names it is bound to, if the module is loaded.
+    loaded: Option<(Arc<dyn CommandHandler>, Vec<&'static str>)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    DuplicateModule(String),
+    UnknownModule(String),
+    AlreadyLoaded(String),
+    NotLoaded(String),
+    /// `name` is already bound by `module`.
+    Conflict { name: &'static str, module: String },
+}
+
+impl RegistryError {
+    pub fn tr(&self, locale: &str) -> String {
+        let tr = |key, m: &str| i18n::tr(locale, key, &[("module", format!("{m:?}").into())]);
+        match self {
+            Self::DuplicateModule(m) => tr("registry-duplicate-module", m),
+            Self::UnknownModule(m) => tr("registry-unknown-module", m),
+            Self::AlreadyLoaded(m) => tr("registry-already-loaded", m),
+            Self::NotLoaded(m) => tr("registry-not-loaded", m),
+            Self::Conflict { name, module } => i18n::tr(locale, "registry-conflict", &[
+                ("name", format!("{name:?}").into()), ("module", format!("{module:?}").into()),
+            ]),
+        }
+    }
+}
+
+/// All known modules (groups of commands served by one handler) and the commands of the loaded
+/// ones.
+#[derive(Default)]
+pub struct CommandRegistry {
+    modules: BTreeMap<String, Module>,
+    commands: HashMap<&'static str, (String, Arc<dyn CommandHandler>)>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn CommandHandler>> {
+        self.commands.get(name).map(|(_, h)| h)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.keys().copied()
+    }
+
+    /// The module that the command `name` belongs to.
+    pub fn module_of(&self, name: &str) -> Option<&str> {
+        self.commands.get(name).map(|(m, _)| &m[..])
+    }
+
+    /// Every name bound to the same module as `name`, including `name` itself, sorted.
+    pub fn aliases(&self, name: &str) -> Vec<&'static str> {
+        match self.module_of(name).and_then(|m| self.modules.get(m)).and_then(|m| m.loaded.as_ref()) {
+            None => vec![],
+            Some((_, names)) => {
+                let mut names = names.clone();
+                names.sort();
+                names
+            }
+        }
+    }
+
+    /// Known module names, each with whether it is loaded.
+    pub fn modules(&self) -> impl Iterator<Item = (&str, bool)> + '_ {
+        self.modules.iter().map(|(k, m)| (&k[..], m.loaded.is_some()))
+    }
+
+    pub fn add_module(&mut self, name: impl Into<String>, factory: ModuleFactory) -> Result<(), RegistryError> {
+        let name = name.into();
+        if self.modules.contains_key(&name) {
+            return Err(RegistryError::DuplicateModule(name));
+        }
+        self.modules.insert(name, Module { factory, loaded: None });
+        Ok(())
+    }
+
+    pub fn factory(&self, module: &str) -> Option<ModuleFactory> {
+        self.modules.get(module).map(|m| m.factory.clone())
+    }
+
+    /// Bind `names` to a registered handler of `module`. With `replace`, a loaded module has its
+    /// old handler and names swapped out; otherwise loading it twice is an error. Nothing changes
+    /// if an error is returned.
+    pub fn insert(
+        &mut self, module: &str, h: Arc<dyn CommandHandler>, names: Vec<&'static str>, replace: bool,
+    ) -> Result<(), RegistryError> {
+        let m = self.modules.get(module).ok_or_else(|| RegistryError::UnknownModule(module.into()))?;
+        let was_loaded = m.loaded.is_some();
+        if was_loaded && !replace {
+            return Err(RegistryError::AlreadyLoaded(module.into()));
+        }
+        for (i, &name) in names.iter().enumerate() {
+            match self.commands.get(name) {
+                Some((other, _)) if other != module => {
+                    return Err(RegistryError::Conflict { name, module: other.clone() });
+                }
+                _ if names[..i].contains(&name) => {
+                    return Err(RegistryError::Conflict { name, module: module.into() });
+                }
+                _ => (),
+            }
+        }
+
+        if was_loaded {
+            let _ = self.remove(module);
+        }
+        for &name in names.iter() {
+            self.commands.insert(name, (module.into(), h.clone()));
+        }
+        self.modules.get_mut(module).unwrap().loaded = Some((h, names));
+        Ok(())
+    }
+
+    /// Unbind every command of `module`, returning their names.
+    pub fn remove(&mut self, module: &str) -> Result<Vec<&'static str>, RegistryError> {
+        let m = self.modules.get_mut(module).ok_or_else(|| RegistryError::UnknownModule(module.into()))?;
+        let (_, names) = m.loaded.take().ok_or_else(|| RegistryError::NotLoaded(module.into()))?;
+        for name in names.iter() {
+            self.commands.remove(name);
+        }
+        Ok(names)
+    }
+
+    /// Unbind a single command, leaving the rest of its module loaded. Returns whether `name` was
+    /// bound.
+    pub fn unbind(&mut self, name: &str) -> bool {
+        let Some((module, _)) = self.commands.remove(name) else { return false };
+        if let Some((_, names)) = self.modules.get_mut(&module).and_then(|m| m.loaded.as_mut()) {
+            names.retain(|&n| n != name);
+        }
+        true
+    }
+}
+
+/// Instantiate and register a known module, then bind those of its names accepted by `keep`.
+/// Returns the bound names.
+pub async fn load_module(
+    cm: &CommandMap, data: ClientData, module: &str, replace: bool, keep: impl Fn(&str) -> bool,
+) -> Result<Vec<&'static str>, RegistryError> {
+    // the handler registers without the registry locked, since it may look at other commands
+    let factory = {
+        let cm = cm.read().await;
+        cm.factory(module).ok_or_else(|| RegistryError::UnknownModule(module.into()))?
+    };
+    let mut h = factory();
+    let mut names = h.register(data).await;
+    names.retain(|n| keep(n));
+    cm.write().await.insert(module, Arc::from(h), names.clone(), replace)?;
+    Ok(names)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::defn::command::{ Command, Context, Message };
+
+    struct Dummy(Vec<&'static str>);
+
+    #[async_trait]
+    impl CommandHandler for Dummy {
+        async fn register(&mut self, _: ClientData) -> Vec<&'static str> { self.0.clone() }
+        async fn call(&self, _: Command, _: Context, _: Message) {}
+    }
+
+    fn factory(names: Vec<&'static str>) -> ModuleFactory {
+        Arc::new(move || Box::new(Dummy(names.clone())))
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut r = CommandRegistry::new();
+        r.add_module("help", factory(vec![])).unwrap();
+        r.add_module("other", factory(vec![])).unwrap();
+        assert_eq!(r.add_module("help", factory(vec![])), Err(RegistryError::DuplicateModule("help".into())));
+
+        let h: Arc<dyn CommandHandler> = Arc::new(Dummy(vec![]));
+        r.insert("help", h.clone(), vec!["help", "h"], false).unwrap();
+        assert_eq!(r.aliases("h"), vec!["h", "help"]);
+        assert_eq!(r.insert("help", h.clone(), vec!["help"], false), Err(RegistryError::AlreadyLoaded("help".into())));
+        assert_eq!(
+            r.insert("other", h.clone(), vec!["x", "h"], false),
+            Err(RegistryError::Conflict { name: "h", module: "help".into() }),
+        );
+        assert!(r.get("x").is_none());
+
+        r.insert("help", h.clone(), vec!["help", "hh"], true).unwrap();
+        assert!(r.get("h").is_none() && r.get("hh").is_some());
+        assert!(r.unbind("hh") && !r.unbind("hh"));
+        assert_eq!(r.aliases("help"), vec!["help"]);
+        assert_eq!(r.remove("help"), Ok(vec!["help"]));
+        assert_eq!(r.remove("help"), Err(RegistryError::NotLoaded("help".into())));
+        assert_eq!(r.names().count(), 0);
+        assert_eq!(r.modules().collect::<Vec<_>>(), vec![("help", false), ("other", false)]);
+    }
+}
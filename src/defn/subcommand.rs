@@ -0,0 +1,302 @@
+//! Subcommands of a command, like `nom/admin enable-module <module>`, each with its own
+//! arguments, help and permission.
+//!
+//! A handler describes its subcommands as a static tree of [`Subcommand`]s whose leaves name an
+//! action of the handler's own, and matches on the action of the [`Invocation`] it gets back from
+//! [`resolve`]. `help` at any level of the tree prints the usage of that level. Help and errors
+//! are in the locale of the author, see [`i18n`].
+
+use std::collections::VecDeque;
+
+use crate::defn::command::{ Arg, Command, Context, Message, Spec };
+use crate::defn::globals::ConfigTmk;
+use crate::i18n;
+use crate::nom_args::{ self, OptSpec, Options };
+use crate::nom_util as util;
+
+/// Who may run a subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    Everyone,
+    /// Owners of the server and members with the Manage Server permission.
+    GuildAdmin,
+    /// The bot owners listed in the config.
+    Owner,
+}
+
+impl Permission {
+    async fn check(self, ctx: &Context, msg: &Message, locale: &str) -> Result<(), String> {
+        match self {
+            Permission::Everyone => Ok(()),
+            Permission::GuildAdmin if msg.guild_id.is_none() => Err(i18n::tr(locale, "error-guild-only", &[])),
+            Permission::GuildAdmin if !util::is_guild_admin(ctx, msg).await => Err(i18n::tr(locale, "subcommand-guild-admin", &[])),
+            Permission::GuildAdmin => Ok(()),
+            Permission::Owner => {
+                let data = ctx.data.read().await;
+                let config = data.get::<ConfigTmk>().expect("Config does not exist!");
+                if config.owners.contains(&msg.author.id.0) { Ok(()) } else { Err(i18n::tr(locale, "subcommand-owner", &[])) }
+            }
+        }
+    }
+
+    fn note(self, locale: &str) -> String {
+        match self {
+            Permission::Everyone => String::new(),
+            Permission::GuildAdmin => format!(" {}", i18n::tr(locale, "subcommand-note-guild-admin", &[])),
+            Permission::Owner => format!(" {}", i18n::tr(locale, "subcommand-note-owner", &[])),
+        }
+    }
+}
+
+/// The arguments a subcommand takes. Positional and keyword parameters are all required.
+pub struct Schema {
+    pub args: &'static [&'static str],
+    pub keys: &'static [&'static str],
+    pub options: &'static [OptSpec],
+    /// What the rest-field holds, if the subcommand takes one.
+    pub rest: Option<&'static str>,
+}
+
+impl Schema {
+    pub const NONE: Self = Self { args: &[], keys: &[], options: &[], rest: None };
+
+    fn usage(&self) -> String {
+        let mut s = String::new();
+        for a in self.args.iter() {
+            s.push_str(&format!(" <{a}>"));
+        }
+        for k in self.keys.iter() {
+            s.push_str(&format!(" {k}=<{k}>"));
+        }
+        for o in self.options.iter() {
+            let shorts: String = o.shorts.iter().map(|(c, _)| c).collect();
+            let shorts = if shorts.is_empty() { String::new() } else { format!("-{shorts} | ") };
+            match o.switch {
+                true => s.push_str(&format!(" [{shorts}--[no-]{}]", o.name)),
+                false => s.push_str(&format!(" [{shorts}--{} <{}>]", o.name, o.name)),
+            }
+        }
+        if let Some(r) = self.rest {
+            s.push_str(&format!("/<{r}>"));
+        }
+        s
+    }
+}
+
+pub enum Node<T: 'static> {
+    Run(T),
+    Sub(&'static [Subcommand<T>]),
+}
+
+pub struct Subcommand<T: 'static> {
+    pub name: &'static str,
+    /// The message key of one line on what the subcommand does. Unused unless `node` is
+    /// `Node::Run`.
+    pub about: &'static str,
+    /// Applies to everything below, too.
+    pub permission: Permission,
+    /// Ignored unless `node` is `Node::Run`.
+    pub schema: Schema,
+    pub node: Node<T>,
+}
+
+/// A subcommand to run, with its arguments checked against its schema.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Invocation<T> {
+    pub action: T,
+    /// The names of the subcommands leading to the action.
+    pub path: Vec<&'static str>,
+    params: Vec<(&'static str, String)>,
+    pub options: Options,
+    pub rest: String,
+}
+
+impl<T> Invocation<T> {
+    /// The value of the parameter `name` of the schema.
+    pub fn param(&self, name: &str) -> &str {
+        self.params.iter().find(|(p, _)| *p == name).map(|(_, v)| &v[..])
+            .expect("not a parameter of the schema")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resolved<T> {
+    Run(Invocation<T>),
+    /// Usage of the subcommands under `path`, for `help`.
+    Help { path: Vec<&'static str>, usage: String },
+}
+
+/// Find the subcommand of `tree` that `cmd` asks for, check its arguments, and check that the
+/// author of `msg` may run it. Errors are meant for the user.
+pub async fn resolve<T: Copy>(tree: &'static [Subcommand<T>], cmd: Command, ctx: &Context, msg: &Message) -> Result<Resolved<T>, String> {
+    let locale = util::locale(ctx, msg).await;
+    let (resolved, permissions) = select(tree, cmd, &locale)?;
+    if let Resolved::Run(_) = resolved {
+        for p in permissions.into_iter() {
+            p.check(ctx, msg, &locale).await?;
+        }
+    }
+    Ok(resolved)
+}
+
+/// What `tree` accepts, for `CommandHandler::spec`.
+pub fn spec<T>(tree: &[Subcommand<T>]) -> Spec {
+    let mut subcommands = vec![("help", Spec::default())];
+    for sub in tree.iter() {
+        subcommands.push((sub.name, match &sub.node {
+            Node::Run(_) => Spec {
+                subcommands: vec![("help", Spec::default())],
+                keys: sub.schema.keys.iter().map(|&k| (k, &[][..])).collect(),
+                options: sub.schema.options,
+            },
+            Node::Sub(tree) => spec(tree),
+        }));
+    }
+    Spec { subcommands, ..Spec::default() }
+}
+
+/// "## Usage" help for `tree`, the subcommands of `nom/<path>`.
+pub fn usage<T>(path: &str, tree: &[Subcommand<T>], locale: &str) -> String {
+    let mut s = format!("{}\n{}\n```\nnom/{path} help\n```", i18n::tr(locale, "usage-heading", &[]), i18n::tr(locale, "usage-help", &[]));
+    for sub in tree.iter() {
+        usage_of(&mut s, path, sub, locale);
+    }
+    s
+}
+
+fn usage_of<T>(s: &mut String, path: &str, sub: &Subcommand<T>, locale: &str) {
+    let path = format!("{path} {}", sub.name);
+    match &sub.node {
+        Node::Run(_) => {
+            let about = i18n::tr(locale, sub.about, &[]);
+            s.push_str(&format!("\n{about}{}\n```\nnom/{path}{}\n```", sub.permission.note(locale), sub.schema.usage()));
+        }
+        Node::Sub(tree) => tree.iter().for_each(|t| usage_of(s, &path, t, locale)),
+    }
+}
+
+/// `resolve` without the permission checks, which need a context: the subcommand, and the
+/// permissions required on the way to it.
+pub(crate) fn select<T: Copy>(tree: &'static [Subcommand<T>], cmd: Command, locale: &str) -> Result<(Resolved<T>, Vec<Permission>), String> {
+    let tr = |key, args: &[(&str, i18n::Value)]| i18n::tr(locale, key, args);
+    let Command { name, args, rest } = cmd;
+    let mut args: VecDeque<Arg> = args.into();
+    let (mut level, mut path, mut permissions) = (tree, vec![], vec![]);
+    let help = |args: &VecDeque<Arg>| args.len() == 1 && matches!(args.front(), Some(Arg::Pos(h)) if h == "help") && rest.is_empty();
+    let prefix = |path: &[&str]| std::iter::once(&name[..]).chain(path.iter().copied()).collect::<Vec<_>>().join(" ");
+    let (sub, action) = loop {
+        let names = || level.iter().map(|s| s.name).collect::<Vec<_>>().join(", ");
+        if help(&args) {
+            let usage = usage(&prefix(&path), level, locale);
+            return Ok((Resolved::Help { path, usage }, permissions));
+        }
+        match args.pop_front() {
+            Some(Arg::Pos(s)) => match level.iter().find(|sub| sub.name == s) {
+                Some(sub) => {
+                    path.push(sub.name);
+                    permissions.push(sub.permission);
+                    match &sub.node {
+                        Node::Run(action) => break (sub, *action),
+                        Node::Sub(tree) => level = tree,
+                    }
+                }
+                None => return Err(tr("subcommand-unknown", &[("name", format!("{s:?}").into()), ("available", names().into())])),
+            },
+            _ => return Err(tr("subcommand-expected", &[("available", names().into())])),
+        }
+    };
+    if help(&args) {
+        let mut usage = tr("usage-heading", &[]);
+        usage_of(&mut usage, &prefix(&path[..path.len() - 1]), sub, locale);
+        return Ok((Resolved::Help { path, usage }, permissions));
+    }
+
+    let schema = &sub.schema;
+    let (options, args) = nom_args::take_options(args.into(), schema.options).map_err(|e| e.tr(locale))?;
+    let mut params = vec![];
+    let mut positional = schema.args.iter();
+    for a in args.into_iter() {
+        match a {
+            Arg::Kw(k, v) | Arg::Code { key: Some(k), code: v, .. } => match schema.keys.iter().find(|&&p| p == k) {
+                Some(_) if params.iter().any(|(p, _)| *p == k) => return Err(tr("subcommand-key-repeated", &[("key", k.into())])),
+                Some(p) => params.push((*p, v)),
+                None if schema.keys.is_empty() => return Err(tr("subcommand-no-keys", &[("key", k.into())])),
+                None => return Err(tr("subcommand-unknown-key", &[("key", format!("{k:?}").into()), ("expected", schema.keys.join(", ").into())])),
+            },
+            Arg::Pos(v) | Arg::Code { key: None, code: v, .. } => match positional.next() {
+                Some(p) => params.push((*p, v)),
+                None => return Err(tr("subcommand-unexpected-arg", &[("arg", format!("{v:?}").into())])),
+            },
+        }
+    }
+    if let Some(p) = positional.next() {
+        return Err(tr("subcommand-expected-arg", &[("arg", (*p).into())]));
+    }
+    if let Some(k) = schema.keys.iter().find(|&&k| !params.iter().any(|(p, _)| *p == k)) {
+        return Err(tr("subcommand-expected-key", &[("key", (*k).into())]));
+    }
+    if schema.rest.is_none() && !rest.is_empty() {
+        return Err(tr("error-no-rest", &[]));
+    }
+    Ok((Resolved::Run(Invocation { action, path, params, options, rest }), permissions))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Action { List, Set, Remove }
+
+    const TAG: &[Subcommand<Action>] = &[
+        Subcommand { name: "list", about: "script-list-about", permission: Permission::Everyone, schema: Schema::NONE, node: Node::Run(Action::List) },
+        Subcommand {
+            name: "admin", about: "", permission: Permission::GuildAdmin, schema: Schema::NONE,
+            node: Node::Sub(&[
+                Subcommand {
+                    name: "set", about: "script-set-about", permission: Permission::Everyone,
+                    schema: Schema {
+                        args: &["tag"], keys: &["owner"], rest: Some("text"),
+                        options: &[OptSpec { name: "pin", shorts: &[('p', "yes")], switch: true }],
+                    },
+                    node: Node::Run(Action::Set),
+                },
+                Subcommand {
+                    name: "remove", about: "script-remove-about", permission: Permission::Owner,
+                    schema: Schema { args: &["tag"], ..Schema::NONE }, node: Node::Run(Action::Remove),
+                },
+            ]),
+        },
+    ];
+
+    fn select(s: &str) -> Result<(Resolved<Action>, Vec<Permission>), String> {
+        super::select(TAG, nom_args::parse(s).unwrap(), "en")
+    }
+
+    #[test]
+    fn test_select() {
+        let Ok((Resolved::Run(i), ps)) = select("tag admin set -p owner=<@1> x/hello") else { panic!() };
+        assert_eq!((i.action, &i.path[..], i.param("tag"), i.param("owner"), &i.rest[..]), (Action::Set, &["admin", "set"][..], "x", "<@1>", "hello"));
+        assert_eq!(i.options, vec![("pin", "yes".into())]);
+        assert_eq!(ps, vec![Permission::GuildAdmin, Permission::Everyone]);
+        assert!(matches!(select("tag list"), Ok((Resolved::Run(Invocation { action: Action::List, .. }), _))));
+
+        assert_eq!(select("tag lost").unwrap_err(), "unknown subcommand \"lost\"; available: list, admin");
+        assert_eq!(select("tag").unwrap_err(), "expected a subcommand; available: list, admin");
+        assert_eq!(select("tag admin").unwrap_err(), "expected a subcommand; available: set, remove");
+        assert_eq!(select("tag admin remove").unwrap_err(), "expected <tag>");
+        assert_eq!(select("tag admin remove x y").unwrap_err(), "unexpected argument \"y\"");
+        assert_eq!(select("tag admin remove x/z").unwrap_err(), "does not accept a rest-field");
+        assert_eq!(select("tag admin set x").unwrap_err(), "expected owner=<owner>");
+        assert_eq!(select("tag admin set x owner=1 owner=2").unwrap_err(), "owner= is given more than once");
+        assert_eq!(select("tag admin set x color=1").unwrap_err(), "unknown key \"color\"; expected: owner");
+
+        let Ok((Resolved::Help { path, usage }, _)) = select("tag help") else { panic!() };
+        assert!(path.is_empty());
+        assert!(usage.contains("Add or replace a script\n```\nnom/tag admin set <tag> owner=<owner> [-p | --[no-]pin]/<text>\n```"), "{usage}");
+        assert!(usage.contains("Remove a script (bot owners only)\n```\nnom/tag admin remove <tag>\n```"), "{usage}");
+        let Ok((Resolved::Help { path, usage }, _)) = select("tag admin remove help") else { panic!() };
+        assert_eq!(path, vec!["admin", "remove"]);
+        assert_eq!(usage, "## Usage\nRemove a script (bot owners only)\n```\nnom/tag admin remove <tag>\n```");
+    }
+}
@@ -0,0 +1,192 @@
+//! Turning parsed commands into calls: expanding variables and command substitutions, and
+//! resolving command names to handlers.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::defn::command::{ Command, CommandHandler, Context, Message };
+use crate::defn::globals::{ CommandMapTmk, GuildSettingsTmk, ScriptsTmk, VariablesTmk };
+use crate::defn::guild::Availability;
+use crate::i18n::{ self, Msg };
+use crate::nom_args::{ CommandTemplate, ParseError, Step };
+use crate::nom_util::{ self as util, Captured };
+
+/// How many `$(...)` a single message may run.
+pub const MAX_SUBSTITUTIONS: usize = 16;
+/// How many bytes of output all the `$(...)` of a single message may produce together.
+pub const MAX_SUBSTITUTION_OUTPUT: usize = 4000;
+/// How long the combined reply of a script may be, in characters; Discord rejects longer messages.
+pub const MAX_SCRIPT_REPLY: usize = 2000;
+
+#[derive(Debug)]
+pub enum EvalError {
+    Parse(ParseError),
+    UnknownCommand(String),
+    Disabled(String),
+    NotInChannel(String),
+    Limit(Msg),
+    /// The command of a substitution failed, with its reply.
+    Substitution(String, String),
+}
+
+impl EvalError {
+    /// The error in `locale`, for the reply.
+    pub fn tr(&self, locale: &str) -> String {
+        let tr = |key, n: &str| i18n::tr(locale, key, &[("name", format!("{n:?}").into())]);
+        match self {
+            Self::Parse(e) => e.tr(locale),
+            Self::UnknownCommand(n) => tr("eval-unknown-command", n),
+            Self::Disabled(n) => tr("eval-disabled", n),
+            Self::NotInChannel(n) => tr("eval-not-in-channel", n),
+            Self::Limit(why) => why.tr(locale),
+            Self::Substitution(n, why) =>
+                i18n::tr(locale, "eval-substitution-failed", &[("name", n.clone().into()), ("why", why.clone().into())]),
+        }
+    }
+
+    /// A short tag for logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Parse(_) | Self::Limit(_) | Self::Substitution(..) => "error",
+            Self::UnknownCommand(_) => "unknown-command",
+            Self::Disabled(_) | Self::NotInChannel(_) => "disabled",
+        }
+    }
+}
+
+/// Find the handler for the command `name` as invoked by `msg`, taking the guild's settings and
+/// scripts into account.
+pub async fn resolve(ctx: &Context, msg: &Message, name: &str) -> Result<Arc<dyn CommandHandler>, EvalError> {
+    let data = ctx.data.read().await;
+    let settings = data
+        .get::<GuildSettingsTmk>().expect("Guild settings do not exist!")
+        .read().await;
+    let cm = data
+        .get::<CommandMapTmk>().expect("Command map does not exist!")
+        .read().await;
+    let mut h = cm.get(name).map(Arc::clone);
+    // commands scripted by the guild are run by the `script` command
+    if let (None, Some(g), Some(scripts)) = (&h, msg.guild_id, data.get::<ScriptsTmk>()) {
+        if scripts.read().await.get(g.0, name).is_some() {
+            h = cm.get("script").map(Arc::clone);
+        }
+    }
+    match (settings.availability(msg.guild_id, msg.channel_id, name), h) {
+        (_, None) => Err(EvalError::UnknownCommand(name.into())),
+        (Availability::Disabled, Some(_)) => Err(EvalError::Disabled(name.into())),
+        (Availability::NotInChannel, Some(_)) => Err(EvalError::NotInChannel(name.into())),
+        (Availability::Available, Some(h)) => Ok(h),
+    }
+}
+
+// What a substitution running `name` expands to, or, if the command failed, its reply without the
+// code block around it.
+fn substituted(name: &str, out: Captured) -> Result<String, EvalError> {
+    let text = out.text();
+    if out.failed {
+        let why = text.trim().trim_start_matches("```").trim_end_matches("```").trim();
+        return Err(EvalError::Substitution(name.into(), why.into()));
+    }
+    // like a shell, drop trailing newlines
+    Ok(text.trim_end().to_string())
+}
+
+/// Expands the commands of a single message, keeping count of the substitutions it runs.
+pub struct Evaluator<'a> {
+    ctx: &'a Context,
+    msg: &'a Message,
+    // the text after the prefix, which error locations refer to
+    source: &'a str,
+    substitutions: usize,
+    output: usize,
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+impl<'a> Evaluator<'a> {
+    pub fn new(ctx: &'a Context, msg: &'a Message, source: &'a str) -> Self {
+        Self { ctx, msg, source, substitutions: 0, output: 0 }
+    }
+
+    /// Run the substitutions of `t`, innermost first, then expand it.
+    pub fn expand<'b>(&'b mut self, t: &'b CommandTemplate) -> BoxFuture<'b, Result<Command, EvalError>> {
+        Box::pin(async move {
+            let mut outputs = vec![];
+            for inner in t.substitutions() {
+                self.substitutions += 1;
+                if self.substitutions > MAX_SUBSTITUTIONS {
+                    return Err(EvalError::Limit(Msg::new("eval-too-many-substitutions", &[("max", MAX_SUBSTITUTIONS.into())])));
+                }
+                let cmd = self.expand(inner).await?;
+                let h = resolve(self.ctx, self.msg, &cmd.name).await?;
+                let name = cmd.name.clone();
+                let ((), out) = util::capture(h.call(cmd, self.ctx.clone(), self.msg.clone())).await;
+                let out = substituted(&name, out)?;
+                self.output += out.len();
+                if self.output > MAX_SUBSTITUTION_OUTPUT {
+                    return Err(EvalError::Limit(Msg::new("eval-substitution-output", &[("max", MAX_SUBSTITUTION_OUTPUT.to_string().into())])));
+                }
+                outputs.push(out);
+            }
+
+            let data = self.ctx.data.read().await;
+            let vars = data
+                .get::<VariablesTmk>().expect("Variables do not exist!")
+                .read().await;
+            let mut outputs = outputs.into_iter();
+            t.expand(&|name| vars.lookup(self.msg, name), &mut |_| outputs.next())
+                .map_err(|e| EvalError::Parse(e.locate(self.source)))
+        })
+    }
+
+    /// Run the commands of a script in order, skipping those after a failed `&&`, and combine
+    /// their replies into one.
+    pub async fn run_script(&mut self, steps: &[Step]) -> String {
+        let locale = util::locale(self.ctx, self.msg).await;
+        let mut replies = vec![];
+        let mut ok = true;
+        for Step { cmd, and } in steps.iter() {
+            if *and && !ok {
+                continue;
+            }
+            let result = match self.expand(cmd).await {
+                Ok(cmd) => resolve(self.ctx, self.msg, &cmd.name).await.map(|h| (h, cmd)),
+                Err(e) => Err(e),
+            };
+            match result {
+                Err(e) => {
+                    ok = false;
+                    replies.push(format!("```\n{}\n```", e.tr(&locale)));
+                }
+                Ok((h, cmd)) => {
+                    let ((), out) = util::capture(h.call(cmd, self.ctx.clone(), self.msg.clone())).await;
+                    ok = !out.failed;
+                    if !out.replies.is_empty() {
+                        replies.push(out.text());
+                    }
+                }
+            }
+        }
+        let reply = replies.join("\n");
+        match reply.char_indices().nth(MAX_SCRIPT_REPLY - 1) {
+            Some((i, _)) => format!("{}…", &reply[..i]),
+            None => reply,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_substituted() {
+        let out = |replies: &[&str], failed| Captured { replies: replies.iter().map(|r| r.to_string()).collect(), failed };
+        assert_eq!(substituted("echo", out(&["cookies\n\n"], false)).unwrap(), "cookies");
+        assert_eq!(substituted("echo", out(&["a", "b"], false)).unwrap(), "a\nb");
+        // a failing command aborts the expansion rather than passing its error on as a value
+        let e = substituted("roll", out(&["```\nroll: expected dice like `2d6` in the rest-field\n```"], true)).unwrap_err();
+        assert_eq!(e.tr("en"), "$(roll) failed: roll: expected dice like `2d6` in the rest-field");
+    }
+}
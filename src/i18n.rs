@@ -0,0 +1,203 @@
+//! Translated messages, and which locale to use for whom.
+//!
+//! Messages are Fluent (https://projectfluent.org) catalogs compiled into the bot, one per locale
+//! under `locales/`. A message missing from a locale falls back to English.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{ FluentArgs, FluentResource };
+pub use fluent_bundle::FluentValue as Value;
+use serde::{ Deserialize, Serialize };
+use unic_langid::LanguageIdentifier;
+
+use crate::defn::command::Message;
+
+pub const DEFAULT: &str = "en";
+
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("ja", include_str!("../locales/ja.ftl")),
+];
+
+/// The locales there is a catalog for.
+pub fn locales() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(l, _)| *l)
+}
+
+/// The locale in `locales()` that `s` names, e.g. `ja` for `ja-JP`.
+pub fn find_locale(s: &str) -> Option<&'static str> {
+    let id: LanguageIdentifier = s.parse().ok()?;
+    locales().find(|l| *l == id.language.as_str())
+}
+
+struct Catalog(Vec<(&'static str, FluentBundle<FluentResource>)>);
+
+fn catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| Catalog(CATALOGS.iter().map(|&(l, ftl)| (l, bundle(l, ftl).unwrap())).collect()))
+}
+
+fn bundle(locale: &str, ftl: &str) -> Result<FluentBundle<FluentResource>, String> {
+    let id: LanguageIdentifier = locale.parse().map_err(|e| format!("{locale}: {e}"))?;
+    let res = FluentResource::try_new(ftl.to_string()).map_err(|(_, e)| format!("{locale}.ftl: {e:?}"))?;
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Discord renders the isolation marks around arguments as-is
+    bundle.set_use_isolating(false);
+    bundle.add_resource(res).map_err(|e| format!("{locale}.ftl: {e:?}"))?;
+    Ok(bundle)
+}
+
+/// The message `key` in `locale`, with `args` filled in. Numeric arguments select plural forms.
+pub fn tr(locale: &str, key: &str, args: &[(&str, Value)]) -> String {
+    let catalog = catalog();
+    let mut fargs = FluentArgs::new();
+    for (k, v) in args.iter() {
+        fargs.set(*k, v.clone());
+    }
+    let found = [locale, DEFAULT].into_iter().find_map(|l| {
+        let (_, bundle) = catalog.0.iter().find(|(c, _)| *c == l)?;
+        Some((bundle, bundle.get_message(key)?.value()?))
+    });
+    match found {
+        Some((bundle, pattern)) => {
+            let mut errors = vec![];
+            let s = bundle.format_pattern(pattern, Some(&fargs), &mut errors);
+            if !errors.is_empty() {
+                tracing::warn!(key, locale, ?errors, "cannot format message");
+            }
+            s.into_owned()
+        }
+        None => {
+            tracing::warn!(key, locale, "no such message");
+            key.into()
+        }
+    }
+}
+
+/// A message to put in a locale once it is known, e.g. an error of a parser that does not know
+/// whom it parses for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Msg {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, Value<'static>)>,
+    /// Arguments that are messages themselves, put in the same locale.
+    pub nested: Vec<(&'static str, Msg)>,
+}
+
+impl Msg {
+    pub fn new(key: &'static str, args: &[(&'static str, Value<'static>)]) -> Self {
+        Self { key, args: args.to_vec(), nested: vec![] }
+    }
+
+    /// Pass `msg` as the argument `name`.
+    pub fn with(mut self, name: &'static str, msg: Msg) -> Self {
+        self.nested.push((name, msg));
+        self
+    }
+
+    pub fn tr(&self, locale: &str) -> String {
+        let mut args = self.args.clone();
+        args.extend(self.nested.iter().map(|(k, m)| (*k, m.tr(locale).into())));
+        tr(locale, self.key, &args)
+    }
+}
+
+/// The locales chosen by users and by guilds, by user id and by guild id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Locales {
+    #[serde(default)]
+    pub users: BTreeMap<u64, String>,
+    #[serde(default)]
+    pub guilds: BTreeMap<u64, String>,
+}
+
+impl Locales {
+    /// The locale to reply to `msg` in: the author's, then the guild's, then the default.
+    pub fn lookup(&self, msg: &Message) -> &str {
+        self.of(msg.author.id.0, msg.guild_id.map(|g| g.0))
+    }
+
+    /// The locale of `user` in `guild`, as in `lookup`.
+    pub fn of(&self, user: u64, guild: Option<u64>) -> &str {
+        self.users.get(&user)
+            .or_else(|| guild.and_then(|g| self.guilds.get(&g)))
+            .map_or(DEFAULT, |l| &l[..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_catalogs() {
+        // every locale has every message, in the same order as English
+        let ids = |ftl: &'static str| ftl.lines()
+            .filter_map(|l| l.split_once(" =").map(|(id, _)| id))
+            .filter(|id| id.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter(|id| id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'))
+            .collect::<Vec<_>>();
+        let english = ids(CATALOGS[0].1);
+        for (l, ftl) in CATALOGS.iter() {
+            bundle(l, ftl).unwrap();
+            assert_eq!(ids(ftl), english, "{l}.ftl");
+        }
+    }
+
+    #[test]
+    fn test_keys_exist() {
+        // the keys that the sources name literally, as in `tr(&locale, "key", ..)`, `Msg::new("key", ..)`
+        // or `about: "key"`
+        let re = regex::Regex::new(r#"(?:\b(?:tr|report|error|Msg::new)\([^"()]*|about: )"([a-z0-9-]+)""#).unwrap();
+        let mut dirs = vec![std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src")];
+        let mut checked = 0;
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let source = std::fs::read_to_string(&path).unwrap();
+                // tests have helpers of their own named like these
+                let source = source.split("#[cfg(test)]").next().unwrap();
+                for key in re.captures_iter(source).map(|c| c.get(1).unwrap().as_str()) {
+                    let (_, bundle) = &catalog().0[0];
+                    assert!(bundle.has_message(key), "{}: no message {key:?}", path.display());
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked > 100, "{checked}");
+    }
+
+    #[test]
+    fn test_tr() {
+        assert_eq!(tr("en", "set-too-many", &[("max", 1.into())]), "at most 1 variable can be set");
+        assert_eq!(tr("en", "set-too-many", &[("max", 64.into())]), "at most 64 variables can be set");
+        assert_eq!(tr("ja", "set-too-many", &[("max", 64.into())]), "設定できる変数は 64 個までです");
+        assert_eq!(
+            tr("en", "admin-loaded", &[("module", "echo".into()), ("count", 1.into()), ("names", "echo".into())]),
+            "Loaded `echo` (1 command: echo).",
+        );
+        assert_eq!(tr("en", "set-too-long", &[("name", "x".into()), ("max", 2000.into())]), "$x is longer than 2000 bytes");
+        assert_eq!(tr("en", "set-listed-channel", &[("name", "x".into()), ("value", "\"y\"".into())]), "$x = \"y\" (channel)");
+        assert_eq!(tr("en", "help-scripts", &[]).matches('{').count(), 1);
+        // nested messages are put in the same locale
+        let bad = Msg::new("literal-bad", &[("value", "\"x\"".into())]).with("expected", Msg::new("literal-integer", &[]));
+        assert_eq!(bad.tr("en"), "expected an integer, not \"x\"");
+        assert_eq!(bad.tr("ja"), "整数 を指定してください（\"x\" ではなく）");
+        // multi-line messages keep their lines, without the indentation
+        assert!(tr("ja", "locale-examples", &[]).ends_with("\n```\nnom/locale set ja\n```"));
+        assert!(tr("en", "script-examples", &[]).contains("`Hello, ${cmd.rest}!`"));
+        assert_eq!(tr("fr", "set-done", &[]), "Done.");
+        assert_eq!(tr("en", "no-such-key", &[]), "no-such-key");
+
+        assert_eq!(find_locale("ja-JP"), Some("ja"));
+        assert_eq!(find_locale("EN"), Some("en"));
+        assert_eq!(find_locale("fr"), None);
+        assert_eq!(find_locale("not a locale"), None);
+    }
+}
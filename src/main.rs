@@ -1,19 +1,43 @@
-use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+use std::process;
 use std::sync::Arc;
+use std::time::Instant;
 
 use serenity::async_trait;
-use serenity::model::channel::Message;
+use serenity::model::channel::{ Message, Reaction };
 use serenity::model::gateway::Ready;
 use serenity::prelude::*;
+use tracing::{ debug, error, field, info, info_span, Instrument, Span };
 
 mod commands;
+mod complete;
+mod config;
 mod defn;
+mod eval;
+mod i18n;
+mod logging;
 mod nom_args;
 mod nom_util;
+mod plugin;
+mod polls;
+mod quotes;
+mod recent;
+mod reminders;
+mod script;
+mod storage;
+mod transform;
+mod variables;
 
-use crate::defn::command::{ ClientData, CommandHandler };
-use crate::defn::globals::{ CommandMap, CommandMapTmk };
+use crate::config::Config;
+use crate::defn::command::ClientData;
+use crate::defn::globals::{ CommandMap, CommandMapTmk, ConfigTmk, GuildSettingsTmk, LocalesTmk, PollsTmk, QuotesTmk, RecentTmk, RemindersTmk, VariablesTmk };
+use crate::eval::{ EvalError, Evaluator };
+use crate::defn::registry::{ load_module, CommandRegistry, ModuleFactory };
+use crate::polls::Polls;
+use crate::recent::Recent;
+use crate::reminders::Reminders;
+use crate::storage::JsonStore;
 
 struct Handler {
     command_prefix: String,
@@ -21,6 +45,69 @@ struct Handler {
 
 impl Handler {
     fn new(command_prefix: String) -> Self { Self { command_prefix } }
+
+    async fn dispatch(&self, ctx: Context, msg: Message) {
+        let start = Instant::now();
+        let Some(content_tail) = nom_args::strip_prefix(&msg.content, &self.command_prefix) else { return };
+        if nom_args::is_script(content_tail) {
+            run_script(content_tail, &ctx, &msg).await;
+        } else {
+            run_command(content_tail, &ctx, &msg).await;
+        }
+        Span::current().record("latency_ms", start.elapsed().as_millis() as u64);
+        info!("invocation finished");
+    }
+}
+
+async fn run_command(s: &str, ctx: &Context, msg: &Message) {
+    let span = Span::current();
+    let result = match nom_args::parse_template(s) {
+        Err(e) => Err(EvalError::Parse(e)),
+        Ok(t) => {
+            span.record("command", &t.name[..]);
+            match Evaluator::new(ctx, msg, s).expand(&t).await {
+                Ok(cmd) => eval::resolve(ctx, msg, &cmd.name).await.map(|h| (h, cmd)),
+                Err(e) => Err(e),
+            }
+        }
+    };
+    match result {
+        Err(e) => {
+            span.record("parse", e.kind());
+            debug!(error = %e.tr(i18n::DEFAULT), "cannot evaluate command");
+            let locale = nom_util::locale(ctx, msg).await;
+            let _: Option<_> = nom_util::try_reply_error(ctx, msg, format!("```\n{}\n```", e.tr(&locale))).await;
+        }
+        Ok((h, cmd)) => {
+            span.record("parse", "ok");
+            // the command as it runs, i.e. with its variables and substitutions expanded
+            if let Ok(expanded) = nom_args::unparse(&cmd) {
+                debug!(%expanded, "running command");
+            }
+            h.call(cmd, ctx.clone(), msg.clone()).await;
+        }
+    };
+}
+
+// Run a script, `{a; b && c}`, replying once with the replies of all its commands.
+async fn run_script(s: &str, ctx: &Context, msg: &Message) {
+    let span = Span::current();
+    match nom_args::parse_script(s) {
+        Err(e) => {
+            span.record("parse", "error");
+            debug!(error = %e.tr(i18n::DEFAULT), "cannot parse script");
+            let locale = nom_util::locale(ctx, msg).await;
+            let _: Option<_> = nom_util::try_reply_error(ctx, msg, format!("```\n{}\n```", e.tr(&locale))).await;
+        }
+        Ok(steps) => {
+            span.record("parse", "ok");
+            span.record("command", steps.iter().map(|s| &s.cmd.name[..]).collect::<Vec<_>>().join(";"));
+            let reply = Evaluator::new(ctx, msg, s).run_script(&steps).await;
+            if !reply.is_empty() {
+                let _: Option<_> = nom_util::try_reply(ctx, msg, reply).await;
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -31,93 +118,222 @@ impl EventHandler for Handler {
     // Event handlers are dispatched through a threadpool, and so multiple events can be dispatched
     // simultaneously.
     async fn message(&self, ctx: Context, msg: Message) {
-        let pref = &self.command_prefix[..];
-        if msg.content.len() >= pref.len() && &msg.content.chars().take(pref.chars().count()).collect::<String>()[..] == pref {
-            // Sending a message can fail, due to a network error, an authentication error, or lack
-            // of permissions to post in the channel, so log to stdout when some error happens,
-            // with a description of it.
-            let content_tail = &msg.content[pref.len()..];
-            match nom_args::parse(content_tail) {
-                Err(e) => {
-                    if let Err(why) = msg.channel_id.say(&ctx.http, format!("```\n{}\n```", e)).await {
-                        println!("Error sending message: {why:?}");
-                    }
-                }
-                Ok(cmd) => {
-                    let h = {
-                        let data = ctx.data.read().await;
-                        let cm = data
-                            .get::<CommandMapTmk>().expect("Command map does not exist!")
-                            .read().await;
-                        cm.get(&cmd.name[..]).map(Arc::clone)
-                    };
-                    match h {
-                        None => {
-                            if let Err(why) = msg.channel_id.say(&ctx.http, format!("```\nCommand {:?} does not exist\n```", cmd.name)).await {
-                                println!("Error sending message: {why:?}");
-                            }
-                        }
-                        Some(h) => h.call(cmd, ctx, msg).await,
-                    }
-                }
-            };
+        if nom_args::strip_prefix(&msg.content, &self.command_prefix).is_some() {
+            // one span per invocation; the fields left empty here are filled in by `dispatch` and
+            // by `nom_util::try_reply` as the invocation progresses
+            let span = info_span!(
+                "invocation",
+                guild = msg.guild_id.map(|g| g.0),
+                channel = msg.channel_id.0,
+                author = msg.author.id.0,
+                command = field::Empty,
+                parse = field::Empty,
+                reply = field::Empty,
+                latency_ms = field::Empty,
+            );
+            self.dispatch(ctx, msg).instrument(span).await;
+        } else if !msg.author.bot && !msg.content.is_empty() {
+            // other messages are remembered for `nom/s`
+            let recent = ctx.data.read().await.get::<RecentTmk>().cloned();
+            if let Some(recent) = recent {
+                recent.lock().await.push(msg.channel_id.0, Recent { id: msg.id.0, author: msg.author.id.0, content: msg.content });
+            }
         }
     }
 
+    // Reactions are votes in polls, and save quotes.
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        polls::on_reaction(&ctx, &reaction, true).await;
+        quotes::on_reaction(&ctx, &reaction).await;
+    }
+
+    async fn reaction_remove(&self, ctx: Context, reaction: Reaction) {
+        polls::on_reaction(&ctx, &reaction, false).await;
+    }
+
     // Set a handler to be called on the `ready` event. This is called when a shard is booted, and
     // a READY payload is sent by Discord. This payload contains data like the current user's guild
     // Ids, current user data, private channels, and more.
     //
     // In this case, just print what the current user's username is.
     async fn ready(&self, _: Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
+        info!(user = %ready.user.name, "connected");
+    }
+}
+
+const USAGE: &str = "usage: nombot [--config <path>] [--check-config] [-h|--help]";
+
+struct Args {
+    config: Option<PathBuf>,
+    check_config: bool,
+}
+
+fn parse_args(mut itr: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args { config: None, check_config: false };
+    while let Some(a) = itr.next() {
+        match &a[..] {
+            "--config" => match itr.next() {
+                Some(p) => args.config = Some(p.into()),
+                None => return Err("--config expects a path".into()),
+            },
+            "--check-config" => args.check_config = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            _ => return Err(format!("unknown option {a:?}")),
+        }
     }
+    Ok(args)
+}
+
+fn exit_with_config_errors(errors: &[String]) -> ! {
+    eprintln!("invalid configuration:");
+    for e in errors.iter() {
+        eprintln!("  - {e}");
+    }
+    process::exit(1);
+}
+
+// Load every built-in module and plugin into a fresh registry, binding only the commands enabled by the
+// config.
+async fn register_commands(data: ClientData, config: &Config) -> Result<CommandMap, Vec<String>> {
+    let command_map: CommandMap = Arc::new(RwLock::new(CommandRegistry::new()));
+    let mut modules: Vec<(String, ModuleFactory)> = commands::builtin_modules().into_iter()
+        .map(|(name, factory)| (name.to_string(), factory))
+        .collect();
+    if let Some(dir) = &config.plugin_path {
+        let mut errors = vec![];
+        for p in plugin::scan(dir).map_err(|e| vec![e])?.into_iter() {
+            match p {
+                Ok(p) => modules.push((p.name.clone(), p.factory())),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+    }
+    {
+        let mut cm = command_map.write().await;
+        for (name, factory) in modules.iter() {
+            cm.add_module(&name[..], factory.clone()).map_err(|e| vec![e.tr(i18n::DEFAULT)])?;
+        }
+    }
+
+    let mut names = vec![];
+    for (name, _) in modules.iter() {
+        names.extend(load_module(&command_map, data.clone(), name, false, |_| true).await.map_err(|e| vec![e.tr(i18n::DEFAULT)])?);
+    }
+    let mut errors = config.validate_commands(&names);
+    {
+        let cm = command_map.read().await;
+        for (name, settings) in config.command_settings.iter() {
+            if let Some(h) = cm.get(name) {
+                if let Err(e) = h.check_settings(name, settings).await {
+                    errors.push(e);
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    {
+        let mut cm = command_map.write().await;
+        for name in names.into_iter().filter(|n| !config.is_enabled(n)) {
+            cm.unbind(name);
+        }
+    }
+
+    Ok(command_map)
 }
 
 #[tokio::main]
 async fn main() {
-    // Configure the client with your Discord bot token in the environment.
-    let token = env::var("DISCORD_TOKEN").expect("Expected a DISCORD_TOKEN in the environment.");
-    // Set gateway intents, which decides what events the bot will be notified about
-    let intents = GatewayIntents::GUILD_MESSAGES
-        | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::MESSAGE_CONTENT;
+    let args = parse_args(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{e}\n{USAGE}");
+        process::exit(2);
+    });
+    let config = Arc::new(Config::load(args.config.as_deref()).unwrap_or_else(|e| exit_with_config_errors(&e)));
+
+    if args.check_config {
+        // commands may read their settings while registering, so register them against a scratch
+        // data map to validate the command-related parts too
+        let data: ClientData = Arc::new(RwLock::new(TypeMap::new()));
+        data.write().await.insert::<ConfigTmk>(config.clone());
+        if let Err(e) = register_commands(data, &config).await {
+            exit_with_config_errors(&e);
+        }
+        println!("configuration OK");
+        return;
+    }
+
+    logging::init(&config.log_level, config.log_format).expect("Err initializing logging");
+    info!(prefix = %config.prefix, storage = %config.storage_path.display(), "starting");
 
     // Create a new instance of the Client, logging in as a bot. This will automatically prepend
     // your bot token with "Bot ", which is a requirement by Discord for bot users.
-    let mut prefix = env::var("PREFIX").expect("Expected a PREFIX in the environment.");
-    prefix.push('/');
-    let handler = Handler::new(prefix);
-    let mut client =
-        Client::builder(&token, intents).event_handler(handler).await.expect("Err creating client");
-    let command_map = Arc::new(RwLock::new(HashMap::new()));
-
-    async fn register<H: 'static + CommandHandler>(mut h: H, d: ClientData, c: CommandMap) {
-        let keys = h.register(d).await;
-        let mut c = c.write().await;
-        let h_arc = Arc::new(h);
-        for k in keys.into_iter() {
-            if let Some(_) = c.insert(k, h_arc.clone()) {
-                panic!("The same name is bound to multiple commands");
-            }
-        }
+    let handler = Handler::new(format!("{}/", config.prefix));
+    if let Err(why) = std::fs::create_dir_all(&config.storage_path) {
+        error!(error = %why, "cannot create storage directory");
+        process::exit(1);
     }
+    let guild_settings = JsonStore::open(config.storage_path.join("guilds.json")).unwrap_or_else(|why| {
+        error!(error = %why, "cannot load guild settings");
+        process::exit(1);
+    });
+    let variables = JsonStore::open(config.storage_path.join("variables.json")).unwrap_or_else(|why| {
+        error!(error = %why, "cannot load variables");
+        process::exit(1);
+    });
+    let locales = JsonStore::open(config.storage_path.join("locales.json")).unwrap_or_else(|why| {
+        error!(error = %why, "cannot load locales");
+        process::exit(1);
+    });
+    let reminders = JsonStore::open(config.storage_path.join("reminders.json")).unwrap_or_else(|why| {
+        error!(error = %why, "cannot load reminders");
+        process::exit(1);
+    });
+    let reminders = Arc::new(Reminders { store: reminders, changed: Default::default() });
+    let polls = JsonStore::open(config.storage_path.join("polls.json")).unwrap_or_else(|why| {
+        error!(error = %why, "cannot load polls");
+        process::exit(1);
+    });
+    let polls = Arc::new(Polls { store: polls, changed: Default::default() });
+    let quotes = JsonStore::open(config.storage_path.join("quotes.json")).unwrap_or_else(|why| {
+        error!(error = %why, "cannot load quotes");
+        process::exit(1);
+    });
+    let mut client = Client::builder(&config.token, config.intents)
+        .event_handler(handler)
+        .type_map_insert::<ConfigTmk>(config.clone())
+        .type_map_insert::<GuildSettingsTmk>(Arc::new(guild_settings))
+        .type_map_insert::<VariablesTmk>(Arc::new(variables))
+        .type_map_insert::<LocalesTmk>(Arc::new(locales))
+        .type_map_insert::<RemindersTmk>(reminders.clone())
+        .type_map_insert::<PollsTmk>(polls.clone())
+        .type_map_insert::<QuotesTmk>(Arc::new(quotes))
+        .type_map_insert::<RecentTmk>(Default::default())
+        .await.expect("Err creating client");
 
-    tokio::join!(
-        register(commands::echo::EchoHandler, client.data.clone(), command_map.clone()),
-        register(commands::help::HelpHandler, client.data.clone(), command_map.clone()),
-        register(commands::nom::NomHandler, client.data.clone(), command_map.clone()),
-    );
+    let command_map = register_commands(client.data.clone(), &config).await
+        .unwrap_or_else(|e| exit_with_config_errors(&e));
     {
         let mut data = client.data.write().await;
         data.insert::<CommandMapTmk>(command_map);
     }
 
+    // deliver reminders in the background, including the ones that fell due while the bot was down
+    tokio::spawn(reminders::run(client.cache_and_http.http.clone(), reminders));
+    // likewise close polls
+    tokio::spawn(polls::run(client.cache_and_http.http.clone(), polls));
+
     // Finally, start a single shard, and start listening to events.
     //
     // Shards will automatically attempt to reconnect, and will perform exponential backoff until
     // it reconnects.
     if let Err(why) = client.start().await {
-        println!("Client error: {why:?}");
+        error!(error = ?why, "client error");
     }
 }
@@ -1,7 +1,13 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::i18n::Msg;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Arg {
     Pos(String),
     Kw(String, String),
+    /// A code block, given positionally or as the value of `key`.
+    Code { key: Option<String>, lang: String, code: String },
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -15,35 +21,525 @@ impl Command {
     pub fn new(name: String, args: Vec<Arg>, rest: String) -> Self { Self { name, args, rest } }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseError {
-    loc: usize,
-    why: String,
+impl Arg {
+    /// The value as written, or the code of a code block.
+    pub fn text(&self) -> &str {
+        match self {
+            Arg::Pos(v) | Arg::Kw(_, v) => v,
+            Arg::Code { code, .. } => code,
+        }
+    }
+
+    /// The value read as a typed literal. The text is still there for handlers that want it.
+    pub fn literal<T: Literal>(&self) -> Result<T, Msg> {
+        T::from_literal(self.text()).ok_or_else(|| match self {
+            Arg::Kw(k, v) => Msg::new("literal-bad-key", &[("key", format!("{k:?}").into()), ("value", format!("{v:?}").into())]),
+            a => Msg::new("literal-bad", &[("value", format!("{:?}", a.text()).into())]),
+        }.with("expected", T::expected()))
+    }
 }
 
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        write!(f, "At position {} after prefix: {}", self.loc, self.why)
+/// An option of a command, for `take_options`.
+pub struct OptSpec {
+    pub name: &'static str,
+    /// Short flags that set the option to a value, e.g. `('s', "yes")` for `-s`.
+    pub shorts: &'static [(char, &'static str)],
+    /// Whether the option is a switch, where `--name` means yes and `--no-name` means no. Other
+    /// options take a value, as `--name value` or `--name=value`.
+    pub switch: bool,
+}
+
+/// Options taken out of the arguments, by name, in order.
+pub type Options = Vec<(&'static str, String)>;
+
+/// Take the GNU-style options out of `args`: `--name`, `--no-name`, `--name value`,
+/// `--name=value`, and clusters of short flags like `-srl`. Arguments like `-1` are numbers, not
+/// flags. Everything after `--` is left as it is, so other values starting with '-' can be
+/// passed. Returns the options and the other arguments.
+pub fn take_options(args: Vec<Arg>, specs: &[OptSpec]) -> Result<(Options, Vec<Arg>), Msg> {
+    let find = |name: &str| specs.iter().find(|o| o.name == name);
+    let unknown = |name: &str| {
+        let names: Vec<_> = specs.iter().map(|o| format!("--{}", o.name)).collect();
+        Msg::new("option-unknown", &[("name", name.to_string().into()), ("available", names.join(", ").into())])
+    };
+    let (mut opts, mut rest) = (vec![], vec![]);
+    let mut itr = args.into_iter();
+    while let Some(a) = itr.next() {
+        match a {
+            Arg::Pos(s) if s == "--" => {
+                rest.extend(itr);
+                break;
+            }
+            Arg::Kw(k, v) if k.starts_with("--") => match find(&k[2..]) {
+                Some(o) => opts.push((o.name, v)),
+                None => return Err(unknown(&k[2..])),
+            },
+            Arg::Pos(s) if s.starts_with("--") => {
+                let name = &s[2..];
+                match (find(name), name.strip_prefix("no-").and_then(find)) {
+                    (Some(o), _) if o.switch => opts.push((o.name, "yes".into())),
+                    (Some(o), _) => match itr.next() {
+                        Some(Arg::Pos(v)) => opts.push((o.name, v)),
+                        _ => return Err(Msg::new("option-no-value", &[("name", name.to_string().into())])),
+                    },
+                    (None, Some(o)) if o.switch => opts.push((o.name, "no".into())),
+                    _ => return Err(unknown(name)),
+                }
+            }
+            // `-1` is a negative number, not a flag
+            Arg::Pos(s) if s.len() > 1 && s.starts_with('-') && !s[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                for c in s[1..].chars() {
+                    let found = specs.iter()
+                        .find_map(|o| o.shorts.iter().find(|(d, _)| *d == c).map(|(_, v)| (o.name, v.to_string())));
+                    match found {
+                        Some(opt) => opts.push(opt),
+                        None => {
+                            let shorts: String = specs.iter().flat_map(|o| o.shorts.iter().map(|(d, _)| d)).collect();
+                            return Err(Msg::new("option-unknown-short", &[("char", format!("{c:?}").into()), ("available", shorts.into())]));
+                        }
+                    }
+                }
+            }
+            a => rest.push(a),
+        }
     }
+    Ok((opts, rest))
+}
+
+/// A type that argument values can be read as, see `Arg::literal`.
+pub trait Literal: Sized {
+    /// What the literal looks like, for error messages.
+    fn expected() -> Msg;
+    fn from_literal(s: &str) -> Option<Self>;
+}
+
+impl Literal for i64 {
+    fn expected() -> Msg { Msg::new("literal-integer", &[]) }
+    fn from_literal(s: &str) -> Option<Self> {
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+}
+
+impl Literal for f64 {
+    fn expected() -> Msg { Msg::new("literal-number", &[]) }
+    fn from_literal(s: &str) -> Option<Self> {
+        // Rust also reads "inf" and "NaN", which nobody means
+        if !s.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c)) {
+            return None;
+        }
+        s.parse().ok().filter(|x: &f64| x.is_finite())
+    }
+}
+
+impl Literal for bool {
+    fn expected() -> Msg { Msg::new("literal-boolean", &[]) }
+    fn from_literal(s: &str) -> Option<Self> {
+        match &s.to_ascii_lowercase()[..] {
+            "yes" | "true" | "1" => Some(true),
+            "no" | "false" | "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl Literal for std::time::Duration {
+    fn expected() -> Msg { Msg::new("literal-duration", &[]) }
+    fn from_literal(s: &str) -> Option<Self> {
+        const UNITS: [(char, u64); 5] = [('w', 7 * 86400), ('d', 86400), ('h', 3600), ('m', 60), ('s', 1)];
+        // each unit at most once, from the largest to the smallest
+        let (mut s, mut units, mut secs) = (s, &UNITS[..], 0u64);
+        if s.is_empty() {
+            return None;
+        }
+        while !s.is_empty() {
+            let len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            let n: u64 = s[..len].parse().ok()?;
+            let unit = s[len..].chars().next()?;
+            let i = units.iter().position(|&(u, _)| u == unit)?;
+            secs = secs.checked_add(n.checked_mul(units[i].1)?)?;
+            (s, units) = (&s[len + 1..], &units[i + 1..]);
+        }
+        Some(Self::from_secs(secs))
+    }
+}
+
+/// A Discord mention, `<@user>`, `<#channel>` or `<@&role>`, or a bare ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mention {
+    User(u64),
+    Channel(u64),
+    Role(u64),
+    Id(u64),
+}
+
+impl Literal for Mention {
+    fn expected() -> Msg { Msg::new("literal-mention", &[]) }
+    fn from_literal(s: &str) -> Option<Self> {
+        let id = |s: &str| Some(s).filter(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))?.parse().ok();
+        let Some(inner) = s.strip_prefix('<').and_then(|s| s.strip_suffix('>')) else {
+            return id(s).map(Mention::Id);
+        };
+        if let Some(t) = inner.strip_prefix("@&") {
+            id(t).map(Mention::Role)
+        } else if let Some(t) = inner.strip_prefix("@!").or_else(|| inner.strip_prefix('@')) {
+            id(t).map(Mention::User)
+        } else {
+            id(inner.strip_prefix('#')?).map(Mention::Channel)
+        }
+    }
+}
+
+/// Comma lists, `a,b,c`, of any literal. Spaces around items are ignored; empty items are not
+/// allowed.
+impl<T: Literal> Literal for Vec<T> {
+    fn expected() -> Msg { Msg::new("literal-list", &[]).with("item", T::expected()) }
+    fn from_literal(s: &str) -> Option<Self> {
+        s.split(',').map(|t| Some(t.trim()).filter(|t| !t.is_empty()).and_then(T::from_literal)).collect()
+    }
+}
+
+impl Literal for String {
+    fn expected() -> Msg { Msg::new("literal-text", &[]) }
+    fn from_literal(s: &str) -> Option<Self> { Some(s.into()) }
+}
+
+/// A piece of an argument value or rest-field before expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Lit(String),
+    /// `$name` or `${name}`, found at `loc`.
+    Var { name: String, loc: usize },
+    /// `$(...)`, found at `loc`, replaced by the output of the inner command.
+    Subst { cmd: Box<CommandTemplate>, loc: usize },
+}
+
+/// Supplies the output of command substitutions, in order of appearance.
+pub type Substitutions<'a> = dyn FnMut(&CommandTemplate) -> Option<String> + 'a;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Word(pub Vec<Segment>);
+
+impl Word {
+    fn push_char(&mut self, c: char) {
+        match self.0.last_mut() {
+            Some(Segment::Lit(l)) => l.push(c),
+            _ => self.0.push(Segment::Lit(c.into())),
+        }
+    }
+
+    // Drop the newline ending the last line of a heredoc.
+    fn pop_newline(&mut self) {
+        if let Some(Segment::Lit(l)) = self.0.last_mut() {
+            l.pop();
+            if l.is_empty() {
+                self.0.pop();
+            }
+        }
+    }
+
+    fn append(&mut self, other: Word) {
+        for seg in other.0.into_iter() {
+            match seg {
+                Segment::Lit(t) => t.chars().for_each(|c| self.push_char(c)),
+                seg => self.0.push(seg),
+            }
+        }
+    }
+
+    /// Substitute every variable through `vars` and every command substitution through
+    /// `substs`. An unknown variable is an error.
+    pub fn expand(&self, vars: &dyn Fn(&str) -> Option<String>, substs: &mut Substitutions) -> Result<String, ParseError> {
+        let mut buf = String::new();
+        for seg in self.0.iter() {
+            match seg {
+                Segment::Lit(l) => buf.push_str(l),
+                Segment::Var { name, loc } => match vars(name) {
+                    Some(v) => buf.push_str(&v),
+                    None => return Err(ParseError::new(
+                        *loc,
+                        Msg::new("parse-unknown-variable", &[("name", name.clone().into())]),
+                    )),
+                },
+                Segment::Subst { cmd, loc } => match substs(cmd) {
+                    Some(v) => buf.push_str(&v),
+                    None => return Err(ParseError::new(*loc, Msg::new("parse-no-substitution", &[]))),
+                },
+            }
+        }
+        Ok(buf)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgTemplate {
+    Pos(Word),
+    Kw(String, Word),
+    /// Code blocks are verbatim, so there is nothing to expand.
+    Code { key: Option<String>, lang: String, code: String },
+}
+
+/// A parsed command whose values may still refer to variables and substitutions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTemplate {
+    pub name: String,
+    pub args: Vec<ArgTemplate>,
+    pub rest: Word,
+}
+
+impl CommandTemplate {
+    fn words(&self) -> impl Iterator<Item = &Word> {
+        self.args.iter()
+            .filter_map(|a| match a {
+                ArgTemplate::Pos(v) | ArgTemplate::Kw(_, v) => Some(v),
+                ArgTemplate::Code { .. } => None,
+            })
+            .chain(std::iter::once(&self.rest))
+    }
+
+    /// The substitutions directly inside this command, in the order `expand` asks for them.
+    pub fn substitutions(&self) -> Vec<&CommandTemplate> {
+        self.words()
+            .flat_map(|w| w.0.iter())
+            .filter_map(|seg| match seg { Segment::Subst { cmd, .. } => Some(&**cmd), _ => None })
+            .collect()
+    }
+
+    pub fn expand(&self, vars: &dyn Fn(&str) -> Option<String>, substs: &mut Substitutions) -> Result<Command, ParseError> {
+        let mut args = vec![];
+        for a in self.args.iter() {
+            args.push(match a {
+                ArgTemplate::Pos(v) => Arg::Pos(v.expand(vars, substs)?),
+                ArgTemplate::Kw(k, v) => Arg::Kw(k.clone(), v.expand(vars, substs)?),
+                ArgTemplate::Code { key, lang, code } =>
+                    Arg::Code { key: key.clone(), lang: lang.clone(), code: code.clone() },
+            });
+        }
+        Ok(Command::new(self.name.clone(), args, self.rest.expand(vars, substs)?))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// In bytes, from the beginning of the command.
+    loc: usize,
+    /// In grapheme clusters, i.e. what users see as characters, once known; see `locate`.
+    col: Option<usize>,
+    why: Msg,
 }
 
 impl ParseError {
-    pub fn new(loc: usize, why: String) -> Self { Self { loc, why } }
+    pub fn new(loc: usize, why: Msg) -> Self { Self { loc, col: None, why } }
+
+    /// The error in `locale`, with where it was found.
+    pub fn tr(&self, locale: &str) -> String {
+        let loc = self.loc.to_string();
+        match self.col {
+            Some(col) => Msg::new("parse-at-column", &[("col", col.into()), ("byte", loc.into())]),
+            None => Msg::new("parse-at-byte", &[("byte", loc.into())]),
+        }.with("why", self.why.clone()).tr(locale)
+    }
+
+    /// Work out the column of the error in `s`, the command it was found in.
+    pub fn locate(self, s: &str) -> Self {
+        let col = s.get(..self.loc).map(|t| t.graphemes(true).count());
+        Self { col, ..self }
+    }
+
     pub fn max_by_loc(self, other: Self) -> Self {
         if self.loc >= other.loc { self } else { other }
     }
 }
 
+/// Parse a command in which no variables are defined.
+#[cfg(test)]
 pub fn parse(s: &str) -> Result<Command, ParseError> {
-    let (name, mut s, mut offset) = expect_ident(s, 0)?;
+    parse_template(s)?.expand(&|_| None, &mut |_| None)
+}
+
+/// Render `cmd` as text that parses back into `cmd`, quoting and escaping values as needed. Fails
+/// if some part cannot be written in nom syntax, e.g. a name that is not an identifier or a code
+/// block containing "```".
+pub fn unparse(cmd: &Command) -> Result<String, String> {
+    let mut buf = String::new();
+    let ident = |s: &str| if is_ident(s) { Ok(s.to_string()) } else { Err(format!("{s:?} is not an identifier")) };
+    buf.push_str(&ident(&cmd.name)?);
+    for a in cmd.args.iter() {
+        buf.push(' ');
+        match a {
+            Arg::Pos(v) => buf.push_str(&unparse_value(v)),
+            Arg::Kw(k, v) => buf.push_str(&format!("{}={}", ident(k)?, unparse_value(v))),
+            Arg::Code { key, lang, code } => {
+                if let Some(k) = key {
+                    buf.push_str(&format!("{}=", ident(k)?));
+                }
+                if !lang.chars().all(|c| c.is_alphanumeric() || "+-_#.".contains(c)) {
+                    return Err(format!("{lang:?} is not a code block language"));
+                } else if code.contains("```") {
+                    return Err("a code block cannot contain \"```\"".into());
+                }
+                buf.push_str(&format!("```{lang}\n{code}\n```"));
+            }
+        }
+    }
+    if !cmd.rest.is_empty() {
+        buf.push('/');
+        buf.push_str(&cmd.rest.replace('$', "\\$"));
+    }
+    Ok(buf)
+}
+
+// A value as a naked string if it can be one, otherwise as a double-quoted string.
+fn unparse_value(v: &str) -> String {
+    let naked = |c: char| !c.is_whitespace() && !"='\"/\\$".contains(c);
+    if !v.is_empty() && v.chars().all(naked) && !v.starts_with("```") {
+        return v.into();
+    }
+    let mut buf = String::from('"');
+    for c in v.chars() {
+        match c {
+            '"' | '\\' | '$' => { buf.push('\\'); buf.push(c); }
+            '\n' => buf.push_str("\\n"),
+            '\t' => buf.push_str("\\t"),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+    buf
+}
+
+/// The command after `prefix` in `content`, if `content` begins with it.
+pub fn strip_prefix<'a>(content: &'a str, prefix: &str) -> Option<&'a str> {
+    // comparing whole strings keeps multi-byte prefixes intact
+    content.strip_prefix(prefix)
+}
+
+pub fn parse_template(s: &str) -> Result<CommandTemplate, ParseError> {
+    expect_command(s, 0, Nest::TOP).map(|(t, _, _)| t).map_err(|e| e.locate(s))
+}
+
+/// How deeply `$(...)` may nest.
+pub const MAX_SUBSTITUTION_DEPTH: usize = 3;
+/// How many commands a script may run.
+pub const MAX_SCRIPT_COMMANDS: usize = 16;
+
+/// A command of a script, `{a; b && c}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub cmd: CommandTemplate,
+    /// Whether the command follows `&&`, i.e. runs only if the previous one succeeded.
+    pub and: bool,
+}
+
+/// Whether `s` begins a script rather than a single command.
+pub fn is_script(s: &str) -> bool {
+    s.starts_with('{')
+}
+
+/// Parse a script: commands between '{' and '}', separated by ';', newlines or '&&'.
+pub fn parse_script(s: &str) -> Result<Vec<Step>, ParseError> {
+    expect_script(s).map_err(|e| e.locate(s))
+}
+
+fn expect_script(s: &str) -> Result<Vec<Step>, ParseError> {
+    let Some(mut s) = s.strip_prefix('{') else {
+        return Err(ParseError::new(0, Msg::new("parse-script-open", &[])));
+    };
+    let mut offset = 1;
+    let mut steps: Vec<Step> = vec![];
+    // after '&&', a command must follow; right after a command, '&&' may follow
+    let (mut pending_and, mut can_and) = (false, false);
+    loop {
+        let t = s.trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        (s, offset) = (t, offset + s.len() - t.len());
+        if let Some(t) = s.strip_prefix('}') {
+            if pending_and {
+                break Err(ParseError::new(offset, Msg::new("parse-command-after-and", &[])));
+            } else if !t.trim().is_empty() {
+                break Err(ParseError::new(offset + 1, Msg::new("parse-after-script", &[])));
+            } else if steps.is_empty() {
+                break Err(ParseError::new(offset, Msg::new("parse-script-empty", &[])));
+            }
+            break Ok(steps);
+        } else if s.is_empty() {
+            break Err(ParseError::new(offset, Msg::new("parse-script-close", &[])));
+        } else if let Some(t) = s.strip_prefix("&&") {
+            if !can_and {
+                break Err(ParseError::new(offset, Msg::new("parse-command-before-and", &[])));
+            }
+            (pending_and, can_and) = (true, false);
+            (s, offset) = (t, offset + 2);
+        } else if s.starts_with([';', '\n']) {
+            // a newline after '&&' continues the line
+            if pending_and && s.starts_with(';') {
+                break Err(ParseError::new(offset, Msg::new("parse-command-after-and", &[])));
+            } else if !pending_and {
+                can_and = false;
+            }
+            (s, offset) = (&s[1..], offset + 1);
+        } else {
+            if steps.len() == MAX_SCRIPT_COMMANDS {
+                break Err(ParseError::new(offset, Msg::new("parse-script-too-long", &[("max", MAX_SCRIPT_COMMANDS.into())])));
+            }
+            let (cmd, t, o) = expect_command(s, offset, Nest::SCRIPT)?;
+            steps.push(Step { cmd, and: pending_and });
+            (pending_and, can_and) = (false, true);
+            (s, offset) = (t, o);
+        }
+    }
+}
+
+// Where the command being parsed is nested, which decides what ends it.
+#[derive(Debug, Clone, Copy)]
+struct Nest {
+    // how many `$(` enclose the command
+    depth: usize,
+    // whether the command is part of a script
+    script: bool,
+}
+
+impl Nest {
+    const TOP: Self = Self { depth: 0, script: false };
+    const SCRIPT: Self = Self { depth: 0, script: true };
+
+    fn inner(self) -> Self { Self { depth: self.depth + 1, ..self } }
+
+    // Whether `s` begins with what ends the command: the ')' closing a substitution, or a
+    // separator or the '}' closing a script.
+    fn at_end(self, s: &str) -> bool {
+        if self.depth > 0 {
+            s.starts_with(')')
+        } else if self.script {
+            s.starts_with([';', '\n', '}']) || s.starts_with("&&")
+        } else {
+            false
+        }
+    }
+
+    fn unclosed(self) -> ParseError {
+        if self.depth > 0 {
+            ParseError::new(0, Msg::new("parse-substitution-close", &[]))
+        } else {
+            ParseError::new(0, Msg::new("parse-script-close", &[]))
+        }
+    }
+}
+
+// Parse a command. At the top level the command runs to the end of `s`; elsewhere it stops where
+// `nest` says it ends.
+fn expect_command(s: &str, offset: usize, nest: Nest) -> Result<(CommandTemplate, &str, usize), ParseError> {
+    let (name, mut s, mut offset) = expect_ident(s, offset)?;
     let mut args = vec![];
     let mut most_successful_error: Option<ParseError> = None;
     loop {
-        if let Ok(s) = expect_rest(s, offset)
-            .or_else(|_| expect_spaces(s, offset).and_then(|(s, o)| expect_rest(s, o))) {
-            break Ok(Command::new(name, args, s))
+        if let Ok((rest, s, o)) = expect_rest(s, offset, nest)
+            .or_else(|_| expect_spaces(s, offset, nest).and_then(|(s, o)| expect_rest(s, o, nest))) {
+            break Ok((CommandTemplate { name, args, rest }, s, o))
         }
-        let (s1, offset1) = match expect_spaces(s, offset) {
+        let (s1, offset1) = match expect_spaces(s, offset, nest) {
             Ok(x) => x,
             Err(e) =>
                 break Err(match most_successful_error {
@@ -51,19 +547,19 @@ pub fn parse(s: &str) -> Result<Command, ParseError> {
                     None => e,
                 }),
         };
-        match expect_key_value(s1, offset1) {
-            Ok((k, v, r, o)) => {
-                args.push(Arg::Kw(k, v));
+        match expect_key_value(s1, offset1, nest) {
+            Ok((a, r, o)) => {
+                args.push(a);
                 (s, offset) = (r, o);
             }
-            Err(e1) => match expect_value(s1, offset1) {
-                Ok((v, r, o)) => {
+            Err(e1) => match expect_positional(s1, offset1, nest) {
+                Ok((a, r, o)) => {
                     most_successful_error =
                         Some(match most_successful_error {
                             Some(m) => m.max_by_loc(e1),
                             None => e1,
                         });
-                    args.push(Arg::Pos(v));
+                    args.push(a);
                     (s, offset) = (r, o);
                 }
                 Err(e2) =>
@@ -76,129 +572,269 @@ pub fn parse(s: &str) -> Result<Command, ParseError> {
     }
 }
 
-fn expect_rest(s: &str, offset: usize) -> Result<String, ParseError> {
-    if s.chars().count() == 0 {
-        Ok("".into())
-    } else if s.chars().nth(0).unwrap() == '/' {
-        Ok((&s[1..]).into())
-    } else {
-        Err(ParseError::new(offset, "expected a <rest> region beginning with '/'".into()))
-    }
+/// Whether `s` is usable as a command name or option key.
+pub fn is_ident(s: &str) -> bool {
+    matches!(expect_ident(s, 0), Ok((_, "", _)))
 }
 
-fn expect_key_value(s: &str, offset: usize) -> Result<(String, String, &str, usize), ParseError> {
-    // eprintln!("expect_key_value({:?}, {:?})", s, offset);
-    expect_ident(s, offset).and_then(
-        |(k, s, offset)|
-            if s.chars().count() >= 1 && s.chars().nth(0).unwrap() == '=' {
-                expect_value(&s[1..], offset + 1).map(|(v, s, offset)| (k, v, s, offset))
+fn expect_rest(s: &str, offset: usize, nest: Nest) -> Result<(Word, &str, usize), ParseError> {
+    let nested = nest.depth > 0 || nest.script;
+    if s.is_empty() {
+        if nested {
+            Err(ParseError { loc: offset, ..nest.unclosed() })
+        } else {
+            Ok((Word::default(), s, offset))
+        }
+    } else if nest.at_end(s) {
+        Ok((Word::default(), s, offset))
+    } else if let Some(s) = s.strip_prefix('/') {
+        // The rest-field is verbatim except for variables, substitutions, and \$ for a literal '$'.
+        // Inside a substitution it ends at the matching ')', and \( and \) are literal parentheses.
+        // In a script it ends at a separator or '}', which \; \& \} and \(newline) escape.
+        let mut end = 0usize;
+        let mut parens = 0usize;
+        let mut buf = Word::default();
+        while let Some(c) = s[end..].chars().next() {
+            let t = &s[end..];
+            let escapes: &[char] = match (nest.depth > 0, nest.script) {
+                (true, _) => &['$', '(', ')'],
+                (false, true) => &['$', ';', '&', '}', '\n'],
+                (false, false) => &['$'],
+            };
+            if let Some(d) = t.strip_prefix('\\').and_then(|t| t.chars().next()).filter(|d| escapes.contains(d)) {
+                buf.push_char(d);
+                end += 1 + d.len_utf8();
+            } else if c == '$' {
+                end += expect_variable(t, offset + 1 + end, nest, &mut buf)?;
+            } else if parens == 0 && nest.at_end(t) {
+                return Ok((buf, t, offset + 1 + end));
             } else {
-                Err(ParseError::new(offset, "expected '=' for key-value pair".into()))
+                if nest.depth > 0 && c == '(' {
+                    parens += 1;
+                } else if nest.depth > 0 && c == ')' {
+                    parens -= 1;
+                }
+                buf.push_char(c);
+                end += c.len_utf8();
             }
-    )
+        }
+        if nested {
+            Err(ParseError { loc: offset + 1 + end, ..nest.unclosed() })
+        } else {
+            Ok((buf, &s[end..], offset + 1 + end))
+        }
+    } else {
+        Err(ParseError::new(offset, Msg::new("parse-rest", &[])))
+    }
 }
 
-fn expect_value(mut s: &str, mut offset: usize) -> Result<(String, &str, usize), ParseError> {
-    let mut buf = vec![];
+fn expect_key_value(s: &str, offset: usize, nest: Nest) -> Result<(ArgTemplate, &str, usize), ParseError> {
+    let (k, s, offset) = expect_ident(s, offset)?;
+    let Some(s) = s.strip_prefix('=') else {
+        return Err(ParseError::new(offset, Msg::new("parse-equals", &[])));
+    };
+    if s.starts_with("```") {
+        expect_code_block(s, offset + 1)
+            .map(|(lang, code, s, offset)| (ArgTemplate::Code { key: Some(k), lang, code }, s, offset))
+    } else {
+        expect_value(s, offset + 1, nest).map(|(v, s, offset)| (ArgTemplate::Kw(k, v), s, offset))
+    }
+}
+
+fn expect_positional(s: &str, offset: usize, nest: Nest) -> Result<(ArgTemplate, &str, usize), ParseError> {
+    if s.starts_with("```") {
+        expect_code_block(s, offset)
+            .map(|(lang, code, s, offset)| (ArgTemplate::Code { key: None, lang, code }, s, offset))
+    } else {
+        expect_value(s, offset, nest).map(|(v, s, offset)| (ArgTemplate::Pos(v), s, offset))
+    }
+}
+
+// A code block, ```lang\n...```, taken verbatim along with its language tag. Without a newline
+// after the opening backticks, e.g. ```x```, there is no language tag.
+fn expect_code_block(s: &str, offset: usize) -> Result<(String, String, &str, usize), ParseError> {
+    let Some(t) = s.strip_prefix("```") else {
+        return Err(ParseError::new(offset, Msg::new("parse-code-open", &[])));
+    };
+    let lang_len = t.find(|c: char| !(c.is_alphanumeric() || "+-_#.".contains(c))).unwrap_or(t.len());
+    let (lang, body) = match t[lang_len..].strip_prefix('\n') {
+        Some(body) => (&t[..lang_len], body),
+        None => ("", t),
+    };
+    let Some(end) = body.find("```") else {
+        return Err(ParseError::new(offset + s.len(), Msg::new("parse-code-close", &[])));
+    };
+    let code = body[..end].strip_suffix('\n').unwrap_or(&body[..end]);
+    let s1 = &body[end + 3..];
+    Ok((lang.into(), code.into(), s1, offset + s.len() - s1.len()))
+}
+
+fn expect_value(mut s: &str, mut offset: usize, nest: Nest) -> Result<(Word, &str, usize), ParseError> {
+    let mut buf = Word::default();
+    let mut empty = true;
 
     loop {
-        match expect_string(s, offset) {
+        match expect_string(s, offset, nest) {
             Ok((t, u, p)) => {
-                buf.push(t);
+                buf.append(t);
+                empty = false;
                 s = u;
                 offset = p;
             }
-            Err(e) => break if buf.len() == 0 {
+            Err(e) => break if empty {
                 Err(e)
             } else {
-                Ok((buf.join(""), s, offset))
+                Ok((buf, s, offset))
             }
         }
     }
 }
 
-fn expect_string(s: &str, offset: usize) -> Result<(String, &str, usize), ParseError> {
+fn expect_string(s: &str, offset: usize, nest: Nest) -> Result<(Word, &str, usize), ParseError> {
     if s.chars().count() == 0 {
-        return Err(ParseError::new(offset, "missing string".into()));
+        return Err(ParseError::new(offset, Msg::new("parse-missing-string", &[])));
     }
-    let first = s.chars().nth(0).unwrap();
+    let first = s.chars().next().unwrap();
 
     if first == '\'' || first == '"' {
-        expect_quoted_string(s, offset)
+        expect_quoted_string(s, offset, nest)
+    } else if heredoc_start(s).is_some() {
+        expect_heredoc(s, offset, nest)
     } else {
-        expect_naked_string(s, offset)
+        expect_naked_string(s, offset, nest)
     }
 }
 
-fn expect_quoted_string(s: &str, offset: usize) -> Result<(String, &str, usize), ParseError> {
+// Double-quoted strings expand variables and substitutions; single-quoted strings are taken
+// literally.
+fn expect_quoted_string(s: &str, offset: usize, nest: Nest) -> Result<(Word, &str, usize), ParseError> {
     let quo: char;
     let mut end = 0usize;
-    let mut buf = String::new();
+    let mut buf = Word::default();
     let mut itr = s.chars();
     if let Some(c) = itr.next() {
         if c == '\'' || c == '"' {
             quo = c;
             end += c.len_utf8();
         } else {
-            return Err(ParseError::new(offset + end, "quoted string does not begin with quote".into()));
+            return Err(ParseError::new(offset + end, Msg::new("parse-quote-open", &[])));
         }
     } else {
-        return Err(ParseError::new(offset + end, "missing string".into()));
+        return Err(ParseError::new(offset + end, Msg::new("parse-missing-string", &[])));
     }
     while let Some(c) = itr.next() {
+        if c == '$' && quo == '"' {
+            end += expect_variable(&s[end..], offset + end, nest, &mut buf)?;
+            itr = s[end..].chars();
+            continue;
+        }
         end += c.len_utf8();
         if c == quo {
-            break;
+            return Ok((buf, &s[end..], offset + end));
         } else if c != '\\' {
-            buf.push(c);
+            buf.push_char(c);
         } else /* c == '\\' */ {
             if let Some(d) = itr.next() {
                 end += d.len_utf8();
-                if d == '\'' || d == '"' || d == '\\' {
-                    buf.push(d);
+                if d == '\'' || d == '"' || d == '\\' || d == '$' {
+                    buf.push_char(d);
                 } else if d == 'n' {
-                    buf.push('\n');
+                    buf.push_char('\n');
                 } else if d == 't' {
-                    buf.push('\t');
-                } else if d.is_whitespace() || d == '=' || d == '/' {
-                    return Err(ParseError::new(offset + end, "\\(whitespace), \\=, and \\/ are only available in naked strings".into()));
+                    buf.push_char('\t');
+                } else if d.is_whitespace() || d == '=' || d == '/' || d == '(' || d == ')' {
+                    return Err(ParseError::new(offset + end, Msg::new("parse-naked-escape", &[])));
                 } else {
-                    return Err(ParseError::new(offset + end, format!("unsupported escape sequence \\{}", d)));
+                    return Err(ParseError::new(offset + end, Msg::new("parse-bad-escape", &[("char", d.to_string().into())])));
                 }
             } else {
-                return Err(ParseError::new(offset + end, "unterminated quoted string".into()));
+                return Err(ParseError::new(offset + end, Msg::new("parse-quote-close", &[])));
             }
         }
     }
 
-    Ok((buf, &s[end..], offset + end))
+    Err(ParseError::new(offset + end, Msg::new("parse-quote-close", &[])))
 }
 
-fn expect_naked_string(s: &str, offset: usize) -> Result<(String, &str, usize), ParseError> {
+// `<<TAG` or `<<'TAG'` and a newline begin a heredoc. Returns the tag, whether it is quoted, and
+// the rest of `s` after the newline.
+fn heredoc_start(s: &str) -> Option<(&str, bool, &str)> {
+    let t = s.strip_prefix("<<")?;
+    let (quoted, t) = match t.strip_prefix('\'') {
+        Some(t) => (true, t),
+        None => (false, t),
+    };
+    let (tag, t, _) = expect_ident(t, 0).ok()?;
+    let t = if quoted { t.strip_prefix('\'')? } else { t };
+    let tag = &s[2 + quoted as usize..][..tag.len()];
+    Some((tag, quoted, t.strip_prefix('\n')?))
+}
+
+// A heredoc runs from the line after `<<TAG` to a line beginning with TAG, and the command goes on
+// right after that TAG. Like the rest-field, it expands variables and substitutions, and `\$` is a
+// literal '$'; with a quoted tag, `<<'TAG'`, it is taken literally.
+fn expect_heredoc(s: &str, offset: usize, nest: Nest) -> Result<(Word, &str, usize), ParseError> {
+    let Some((tag, quoted, mut t)) = heredoc_start(s) else {
+        return Err(ParseError::new(offset, Msg::new("parse-heredoc-open", &[])));
+    };
+    let at = |t: &str| offset + s.len() - t.len();
+    let mut buf = Word::default();
+    loop {
+        // at the beginning of a line
+        if let Some(u) = t.strip_prefix(tag).filter(|u| !u.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-')) {
+            buf.pop_newline();
+            return Ok((buf, u, at(u)));
+        }
+        if t.is_empty() {
+            return Err(ParseError::new(at(t), Msg::new("parse-heredoc-close", &[("tag", tag.to_string().into())])));
+        }
+        while let Some(c) = t.chars().next() {
+            if !quoted && t.starts_with("\\$") {
+                buf.push_char('$');
+                t = &t[2..];
+            } else if !quoted && c == '$' {
+                let n = expect_variable(t, at(t), nest, &mut buf)?;
+                t = &t[n..];
+            } else {
+                buf.push_char(c);
+                t = &t[c.len_utf8()..];
+                if c == '\n' {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Inside a substitution, naked strings also end at ')'.
+fn expect_naked_string(s: &str, offset: usize, nest: Nest) -> Result<(Word, &str, usize), ParseError> {
     let mut end = 0usize;
-    let mut buf = String::new();
+    let mut buf = Word::default();
     let mut itr = s.chars();
     while let Some(c) = itr.next() {
-        if !c.is_whitespace() && c != '=' && c != '\'' && c != '"' && c != '/' && c != '\\' {
+        if c == '$' {
+            end += expect_variable(&s[end..], offset + end, nest, &mut buf)?;
+            itr = s[end..].chars();
+        } else if !c.is_whitespace() && c != '=' && c != '\'' && c != '"' && c != '/' && c != '\\' && !nest.at_end(&s[end..]) {
             end += c.len_utf8();
-            buf.push(c);
+            buf.push_char(c);
         } else if c == '\\' {
             end += c.len_utf8();
             if let Some(d) = itr.next() {
                 end += d.len_utf8();
-                if d.is_whitespace() || d == '=' || d == '\'' || d == '"' || d == '/' || d == '\\' {
-                    buf.push(d);
+                if d.is_whitespace() || d == '=' || d == '\'' || d == '"' || d == '/' || d == '\\' || d == '$' || d == '(' || d == ')' {
+                    buf.push_char(d);
                 } else if d == 'n' || d == 't' {
-                    return Err(ParseError::new(offset + end, "\\n and \\t are only available in quoted strings".into()));
+                    return Err(ParseError::new(offset + end, Msg::new("parse-quoted-escape", &[])));
                 } else {
-                    return Err(ParseError::new(offset + end, format!("unsupported escape sequence \\{}", d)));
+                    return Err(ParseError::new(offset + end, Msg::new("parse-bad-escape", &[("char", d.to_string().into())])));
                 }
             } else {
-                return Err(ParseError::new(offset + end, "missing operand after escape character '\\'".into()));
+                return Err(ParseError::new(offset + end, Msg::new("parse-escape-end", &[])));
             }
         } else {
             if end == 0 {
-                return Err(ParseError::new(offset + end, format!("invalid character to appear in a naked string ({:?})", c)));
+                return Err(ParseError::new(offset + end, Msg::new("parse-naked-char", &[("char", format!("{c:?}").into())])));
             } else {
                 break;
             }
@@ -208,29 +844,62 @@ fn expect_naked_string(s: &str, offset: usize) -> Result<(String, &str, usize),
     Ok((buf, &s[end..], offset + end))
 }
 
+// Given `s` beginning with '$', push a variable reference, a command substitution or, if neither
+// follows, a literal '$' onto `buf`. Returns the number of bytes consumed.
+fn expect_variable(s: &str, offset: usize, nest: Nest, buf: &mut Word) -> Result<usize, ParseError> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let t = &s[1..];
+    if let Some(t) = t.strip_prefix('(') {
+        if nest.depth >= MAX_SUBSTITUTION_DEPTH {
+            return Err(ParseError::new(offset, Msg::new("parse-substitution-depth", &[("max", MAX_SUBSTITUTION_DEPTH.into())])));
+        }
+        let (cmd, t, o) = expect_command(t, offset + 2, nest.inner())?;
+        if !t.starts_with(')') {
+            return Err(ParseError::new(o, Msg::new("parse-substitution-close", &[])));
+        }
+        buf.0.push(Segment::Subst { cmd: Box::new(cmd), loc: offset });
+        Ok(o + 1 - offset)
+    } else if let Some(t) = t.strip_prefix('{') {
+        let len: usize = t.chars().take_while(|&c| is_name_char(c)).map(char::len_utf8).sum();
+        if len == 0 || !t[len..].starts_with('}') {
+            return Err(ParseError::new(offset + 2 + len, Msg::new("parse-variable-brace", &[])));
+        }
+        buf.0.push(Segment::Var { name: t[..len].into(), loc: offset });
+        Ok(len + 3)
+    } else if t.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        let len: usize = t.chars().take_while(|&c| is_name_char(c)).map(char::len_utf8).sum();
+        buf.0.push(Segment::Var { name: t[..len].into(), loc: offset });
+        Ok(len + 1)
+    } else {
+        buf.push_char('$');
+        Ok(1)
+    }
+}
+
 fn expect_ident(s: &str, offset: usize) -> Result<(String, &str, usize), ParseError> {
     let end = s.chars()
         .take_while(
-            |&c| ('0' <= c && c <= '9')
-                || ('A' <= c && c <= 'Z')
-                || ('a' <= c && c <= 'z')
+            |&c| c.is_ascii_alphanumeric()
                 || c == '_'
                 || c == '-'
         )
         .map(char::len_utf8).sum();
 
     if end == 0 {
-        Err(ParseError::new(offset, "expected an identifier, which begins with [0-9A-Za-z_-]".into()))
+        Err(ParseError::new(offset, Msg::new("parse-ident", &[])))
     } else {
         Ok(((&s[..end]).into(), &s[end..], offset + end))
     }
 }
 
-fn expect_spaces(s: &str, offset: usize) -> Result<(&str, usize), ParseError> {
-    let end = s.chars().take_while(|&c| c.is_whitespace()).map(char::len_utf8).sum();
+// Newlines separate the commands of a script, so they do not count as spaces there.
+fn expect_spaces(s: &str, offset: usize, nest: Nest) -> Result<(&str, usize), ParseError> {
+    let end = s.chars()
+        .take_while(|&c| c.is_whitespace() && !(c == '\n' && nest.at_end("\n")))
+        .map(char::len_utf8).sum();
 
     if end == 0 {
-        Err(ParseError::new(offset, "expected whitespace".into()))
+        Err(ParseError::new(offset, Msg::new("parse-spaces", &[])))
     } else {
         Ok((&s[end..], offset + end))
     }
@@ -266,4 +935,306 @@ mod test {
             )),
         );
     }
+
+    #[test]
+    fn test_variables() {
+        let vars = |name: &str| match name {
+            "who" => Some("world".into()),
+            "n" => Some("3".into()),
+            _ => None,
+        };
+        let expand = |s: &str| parse_template(s).and_then(|t| t.expand(&vars, &mut |_| None));
+        assert_eq!(
+            expand("echo $who x=${n}rd \"$who!\" '$who'/hello, $who, $5 \\$who"),
+            Ok(Command::new(
+                "echo".into(),
+                vec![
+                    Arg::Pos("world".into()),
+                    Arg::Kw("x".into(), "3rd".into()),
+                    Arg::Pos("world!".into()),
+                    Arg::Pos("$who".into()),
+                ],
+                "hello, world, $5 $who".into(),
+            )),
+        );
+        assert_eq!(expand("echo \\$who $ a$"), parse("echo \\$who $ a$"));
+        assert_eq!(expand("echo $nobody").err().map(|e| e.loc), Some(5));
+        assert_eq!(expand("echo/${nobody}").err().map(|e| e.loc), Some(5));
+        assert!(expand("echo ${who").is_err());
+    }
+
+    #[test]
+    fn test_substitutions() {
+        let t = parse_template("echo count=$(clac/2 3 +)/$(nom !) and \"$(echo/(a)\\))\"").unwrap();
+        let inner: Vec<_> = t.substitutions().into_iter()
+            .map(|c| c.expand(&|_| None, &mut |_| None).unwrap())
+            .collect();
+        assert_eq!(inner, vec![
+            Command::new("clac".into(), vec![], "2 3 +".into()),
+            Command::new("nom".into(), vec![Arg::Pos("!".into())], "".into()),
+            // quotes are literal in the rest-field
+            Command::new("echo".into(), vec![], "(a))".into()),
+        ]);
+        let mut outputs = vec!["5".to_string(), "nommers!".into(), "(a))".into()].into_iter();
+        assert_eq!(
+            t.expand(&|_| None, &mut |_| outputs.next()),
+            Ok(Command::new("echo".into(), vec![Arg::Kw("count".into(), "5".into())], "nommers! and \"(a))\"".into())),
+        );
+
+        let t = parse_template("echo \"<$(echo/(a)\\)b)>\" x\\)").unwrap();
+        let inner = t.substitutions()[0].expand(&|_| None, &mut |_| None).unwrap();
+        assert_eq!(inner, Command::new("echo".into(), vec![], "(a))b".into()));
+        assert_eq!(t.expand(&|_| None, &mut |_| Some("!".into())), parse("echo <!> x\\)"));
+
+        assert!(parse_template("echo $(nom").is_err());
+        assert!(parse_template("echo $(echo/abc").is_err());
+        assert!(parse_template("echo $(echo $(echo $(echo $(echo))))").is_err());
+        assert!(parse_template("echo $(echo $(echo $(echo)))").is_ok());
+        assert!(parse("echo $(nom)").is_err());
+    }
+
+    #[test]
+    fn test_scripts() {
+        let run = |s: &str| parse_script(s).map(|steps| steps.into_iter()
+            .map(|Step { cmd, and }| (cmd.expand(&|_| None, &mut |_| None).unwrap(), and))
+            .collect::<Vec<_>>());
+        assert_eq!(
+            run("{nom !; echo/done && echo -s/a\\;b\\&&c \n\n  clac/1 2 +\\\n3 +\n}"),
+            Ok(vec![
+                (Command::new("nom".into(), vec![Arg::Pos("!".into())], "".into()), false),
+                (Command::new("echo".into(), vec![], "done ".into()), false),
+                (Command::new("echo".into(), vec![Arg::Pos("-s".into())], "a;b&&c ".into()), true),
+                (Command::new("clac".into(), vec![], "1 2 +\n3 +".into()), false),
+            ]),
+        );
+        assert_eq!(run("{ nom &&\n nom }").map(|v| v[1].1), Ok(true));
+        // separators inside `$(...)` belong to the substitution
+        let steps = parse_script("{echo/$(echo/a;b)}").unwrap();
+        assert_eq!(steps[0].cmd.substitutions()[0].expand(&|_| None, &mut |_| None), parse("echo/a;b"));
+        for bad in ["nom", "{nom", "{}", "{nom} x", "{&& nom}", "{nom &&}", "{nom && ; nom}", "{nom; && nom}", "{echo/x"] {
+            assert!(parse_script(bad).is_err(), "{bad:?}");
+        }
+        assert!(parse_script(&format!("{{{}}}", "nom;".repeat(MAX_SCRIPT_COMMANDS + 1))).is_err());
+        assert!(!is_script("nom {x}"));
+    }
+
+    #[test]
+    fn test_heredocs_and_code_blocks() {
+        let vars = |name: &str| (name == "who").then(|| "world".to_string());
+        let expand = |s: &str| parse_template(s).and_then(|t| t.expand(&vars, &mut |_| None));
+        assert_eq!(
+            expand("diff old=<<A\nhello,\n  $who\nA new=<<'B'\n$who\nAB\n\nB/x"),
+            Ok(Command::new("diff".into(), vec![
+                Arg::Kw("old".into(), "hello,\n  world".into()),
+                Arg::Kw("new".into(), "$who\nAB\n".into()),
+            ], "x".into())),
+        );
+        assert_eq!(expand("echo <<A\nA"), Ok(Command::new("echo".into(), vec![Arg::Pos("".into())], "".into())));
+        // without a newline, `<<` is just text
+        assert_eq!(expand("echo x=<<A"), Ok(Command::new("echo".into(), vec![Arg::Kw("x".into(), "<<A".into())], "".into())));
+        assert!(expand("echo x=<<A\nnever ends").is_err());
+
+        assert_eq!(
+            expand("re pattern=```\n(a|b)+\n``` ```rust\nfn main() {}\n```/ab"),
+            Ok(Command::new("re".into(), vec![
+                Arg::Code { key: Some("pattern".into()), lang: "".into(), code: "(a|b)+".into() },
+                Arg::Code { key: None, lang: "rust".into(), code: "fn main() {}".into() },
+            ], "ab".into())),
+        );
+        assert_eq!(
+            expand("re ```$x```"),
+            Ok(Command::new("re".into(), vec![Arg::Code { key: None, lang: "".into(), code: "$x".into() }], "".into())),
+        );
+        assert!(expand("re ```\nno end").is_err());
+        assert!(expand("re ```a```b").is_err());
+    }
+
+    #[test]
+    fn test_unparse() {
+        let cmds = [
+            Command::new("nom".into(), vec![], "".into()),
+            Command::new("echo".into(), vec![
+                Arg::Pos("-s".into()),
+                Arg::Pos("".into()),
+                Arg::Pos("a b=\"c\"\n\t\\$x 'y'/".into()),
+                Arg::Kw("k".into(), "```".into()),
+                Arg::Pos("<<A".into()),
+            ], "100$ \\$ (a) \"b\"\n/".into()),
+            Command::new("re".into(), vec![
+                Arg::Code { key: Some("p".into()), lang: "".into(), code: "\n$x\n".into() },
+                Arg::Code { key: None, lang: "c++".into(), code: "".into() },
+            ], "".into()),
+        ];
+        for c in cmds.iter() {
+            let s = unparse(c).unwrap();
+            assert_eq!(parse(&s).as_ref(), Ok(c), "{s:?}");
+        }
+        assert_eq!(unparse(&cmds[0]), Ok("nom".into()));
+        assert_eq!(
+            unparse(&Command::new("echo".into(), vec![Arg::Kw("x".into(), "1 2".into())], "$5".into())),
+            Ok("echo x=\"1 2\"/\\$5".into()),
+        );
+        assert!(unparse(&Command::new("no pe".into(), vec![], "".into())).is_err());
+        assert!(unparse(&Command::new("re".into(), vec![Arg::Kw("".into(), "".into())], "".into())).is_err());
+        assert!(unparse(&Command::new("re".into(), vec![Arg::Code { key: None, lang: "".into(), code: "```".into() }], "".into())).is_err());
+    }
+
+    #[test]
+    fn test_unicode() {
+        for prefix in ["nom/", "ñom/", "🍪/", "ノム/", "👩‍🍳/"] {
+            let content = format!("{prefix}echo dish=寿司 🍣/いただきます 😋");
+            assert_eq!(
+                strip_prefix(&content, prefix).map(parse),
+                Some(Ok(Command::new(
+                    "echo".into(),
+                    vec![Arg::Kw("dish".into(), "寿司".into()), Arg::Pos("🍣".into())],
+                    "いただきます 😋".into(),
+                ))),
+            );
+            assert_eq!(strip_prefix(&content[..prefix.len() - 1], prefix), None);
+        }
+        assert_eq!(strip_prefix("ñ", "ñom/"), None);
+        assert_eq!(strip_prefix("nom/x", "ñom/"), None);
+
+        // 寿司 is 6 bytes and 2 columns; the family emoji is 25 bytes and 1 column
+        let e = parse("echo x=寿司\"").unwrap_err();
+        assert_eq!((e.loc, e.col), (13, Some(9)));
+        let e = parse_template("echo 👨‍👩‍👧‍👦 ${").unwrap_err();
+        assert_eq!((e.loc, e.col), (33, Some(9)));
+        assert_eq!(e.tr("en"), "At column 9 (byte 33) after prefix: expected a variable name and '}' after '${'");
+        let e = parse_script("{nom; 🍪}").unwrap_err();
+        assert_eq!((e.loc, e.col), (6, Some(6)));
+    }
+
+    #[test]
+    fn test_literals() {
+        use std::time::Duration;
+        fn lit<T: Literal>(s: &str) -> Option<T> { T::from_literal(s) }
+        assert_eq!([lit::<i64>("42"), lit("-7"), lit("+3"), lit("1.0"), lit(""), lit("99999999999999999999")], [Some(42), Some(-7), Some(3), None, None, None]);
+        assert_eq!([lit::<f64>("2.5"), lit("-1"), lit(".5"), lit("inf"), lit("NaN"), lit("1e400")], [Some(2.5), Some(-1.0), Some(0.5), None, None, None]);
+        assert_eq!(["yes", "No", "TRUE", "false", "1", "0", "y", "2"].map(lit::<bool>), [Some(true), Some(false), Some(true), Some(false), Some(true), Some(false), None, None]);
+        assert_eq!(lit::<Duration>("2h30m"), Some(Duration::from_secs(9000)));
+        assert_eq!(lit::<Duration>("1w1d1h1m1s"), Some(Duration::from_secs(694861)));
+        for bad in ["", "10", "m", "30m2h", "1h1h", "1.5h", "10x", "99999999999999999999w"] {
+            assert_eq!(lit::<Duration>(bad), None, "{bad:?}");
+        }
+        assert_eq!(
+            ["<@12>", "<@!12>", "<#34>", "<@&56>", "78", "<@>", "<#x>", "<:emoji:12>", "@12"].map(lit::<Mention>),
+            [Some(Mention::User(12)), Some(Mention::User(12)), Some(Mention::Channel(34)), Some(Mention::Role(56)), Some(Mention::Id(78)), None, None, None, None],
+        );
+        assert_eq!(lit::<Vec<String>>("a, b,c"), Some(vec!["a".into(), "b".into(), "c".into()]));
+        assert_eq!(lit::<Vec<i64>>("1,2,3"), Some(vec![1, 2, 3]));
+        assert_eq!(lit::<Vec<i64>>("1,,3"), None);
+        assert_eq!(lit::<Vec<i64>>("1,x"), None);
+
+        let c = parse("remind in=2h30m ping=<@12>/tea").unwrap();
+        assert_eq!(c.args[0].literal::<Duration>(), Ok(Duration::from_secs(9000)));
+        assert_eq!(c.args[0].text(), "2h30m");
+        assert_eq!(c.args[1].literal::<Mention>(), Ok(Mention::User(12)));
+        assert_eq!(c.args[1].literal::<i64>().map_err(|e| e.tr("en")), Err("\"ping\" must be an integer, not \"<@12>\"".into()));
+        assert_eq!(c.args[0].literal::<Vec<i64>>().unwrap_err().tr("en"), "\"in\" must be a comma-separated list of an integer, not \"2h30m\"");
+    }
+
+    #[test]
+    fn test_options() {
+        const SPECS: &[OptSpec] = &[
+            OptSpec { name: "sorted", shorts: &[('s', "yes"), ('S', "no")], switch: true },
+            OptSpec { name: "scope", shorts: &[('l', "line"), ('c', "char")], switch: false },
+        ];
+        let take = |s: &str| take_options(parse(s).unwrap().args, SPECS);
+        assert_eq!(
+            take("echo --sorted --no-sorted --scope line --scope=char -Sl x -- -s --sorted"),
+            Ok((
+                vec![("sorted", "yes".into()), ("sorted", "no".into()), ("scope", "line".into()), ("scope", "char".into()), ("sorted", "no".into()), ("scope", "line".into())],
+                vec![Arg::Pos("x".into()), Arg::Pos("-s".into()), Arg::Pos("--sorted".into())],
+            )),
+        );
+        assert_eq!(take("echo -s -1 -2.5"), Ok((vec![("sorted", "yes".into())], vec![Arg::Pos("-1".into()), Arg::Pos("-2.5".into())])));
+        assert_eq!(take("echo - -- --"), Ok((vec![], vec![Arg::Pos("-".into()), Arg::Pos("--".into())])));
+        assert_eq!(take("echo --sort").unwrap_err().tr("en"), "unrecognized option --sort; available options: --sorted, --scope");
+        assert_eq!(take("echo -sx").unwrap_err().tr("en"), "unrecognized flag shorthand 'x'; available shorthands: sSlc");
+        assert!(take("echo --scope").is_err());
+        assert!(take("echo --no-scope").is_err());
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+    use super::*;
+
+    // Mostly the characters that mean something to the parser, plus some multi-byte ones.
+    const TRICKY: &str = "[a-z0-9 =/\\\\'\"$(){};&`<\n\t-]|é|ñ|語|🍪|\u{200d}";
+
+    fn tricky_string(max: usize) -> impl Strategy<Value = String> {
+        prop_oneof![
+            proptest::collection::vec(TRICKY, 0..max).prop_map(|v| v.concat()),
+            any::<String>(),
+        ]
+    }
+
+    fn ident() -> impl Strategy<Value = String> { "[a-zA-Z0-9_-]{1,8}" }
+
+    fn arg() -> impl Strategy<Value = Arg> {
+        prop_oneof![
+            tricky_string(12).prop_map(Arg::Pos),
+            (ident(), tricky_string(12)).prop_map(|(k, v)| Arg::Kw(k, v)),
+            (proptest::option::of(ident()), "[a-z+#]{0,4}", tricky_string(12))
+                .prop_filter("code blocks cannot contain ```", |(_, _, c)| !c.contains("```"))
+                .prop_map(|(key, lang, code)| Arg::Code { key, lang, code }),
+        ]
+    }
+
+    fn command() -> impl Strategy<Value = Command> {
+        (ident(), proptest::collection::vec(arg(), 0..4), tricky_string(16))
+            .prop_map(|(name, args, rest)| Command::new(name, args, rest))
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(s in tricky_string(40)) {
+            let vars = |_: &str| Some("x".to_string());
+            if let Ok(t) = parse_template(&s) {
+                let _ = t.expand(&vars, &mut |_| Some("y".into()));
+            }
+            let _ = parse_script(&s);
+            let _ = parse_script(&format!("{{{s}}}"));
+        }
+
+        #[test]
+        fn errors_are_on_char_boundaries(s in tricky_string(40)) {
+            let vars = |_: &str| None;
+            let errors = [
+                parse_template(&s).and_then(|t| t.expand(&vars, &mut |_| None)).err(),
+                parse_script(&s).err(),
+            ];
+            for e in errors.into_iter().flatten() {
+                prop_assert!(s.is_char_boundary(e.loc), "{e:?} in {s:?}");
+            }
+        }
+
+        #[test]
+        fn unparse_round_trips(c in command()) {
+            let s = unparse(&c).unwrap();
+            prop_assert_eq!(parse(&s), Ok(c), "{:?}", s);
+        }
+
+        #[test]
+        fn unterminated_quotes_are_errors(v in tricky_string(12), quo in "[\"']") {
+            let v = v.replace(&quo, "");
+            for s in [format!("echo {quo}{v}"), format!("echo x={quo}{v}")] {
+                prop_assert!(parse(&s).is_err(), "{:?}", s);
+            }
+        }
+
+        #[test]
+        fn reparse_is_stable(s in tricky_string(40)) {
+            // whatever parses, unparses into text that parses the same
+            if let Ok(c) = parse(&s) {
+                if let Ok(u) = unparse(&c) {
+                    prop_assert_eq!(parse(&u), Ok(c), "{:?} -> {:?}", s, u);
+                }
+            }
+        }
+    }
 }
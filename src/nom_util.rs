@@ -1,11 +1,150 @@
+use std::cell::RefCell;
+use std::future::Future;
+use serenity::model::id::ChannelId;
+use serenity::model::permissions::Permissions;
+use tracing::{ warn, Span };
 use crate::defn::command::{ Context, Message };
+use crate::defn::globals::{ ConfigTmk, LocalesTmk };
+use crate::nom_args::{ self, Literal, Mention };
 
+/// What a command run inside `capture` would have replied.
+#[derive(Debug, Default)]
+pub struct Captured {
+    pub replies: Vec<String>,
+    /// Whether any reply reported a failure, see `try_reply_error`.
+    pub failed: bool,
+}
+
+impl Captured {
+    /// The replies joined by newlines.
+    pub fn text(&self) -> String { self.replies.join("\n") }
+}
+
+tokio::task_local! {
+    // replies of commands run for their output, e.g. inside `$(...)`, are collected here instead
+    // of being sent
+    static CAPTURED: RefCell<Captured>;
+}
+
+/// Run `f`, collecting what it would have replied instead of sending it.
+pub async fn capture<F: Future>(f: F) -> (F::Output, Captured) {
+    CAPTURED.scope(RefCell::new(Captured::default()), async {
+        let r = f.await;
+        (r, CAPTURED.with(|c| c.take()))
+    }).await
+}
+
+/// Reply in the channel of `msg`, or add to the captured output when run inside `capture`.
 pub async fn try_reply(ctx: &Context, msg: &Message, reply: impl std::fmt::Display) -> Option<Message> {
+    if CAPTURED.try_with(|c| c.borrow_mut().replies.push(reply.to_string())).is_ok() {
+        return None;
+    }
     match msg.channel_id.say(&ctx.http, reply).await {
-        Ok(m) => Some(m),
+        Ok(m) => {
+            Span::current().record("reply", "ok");
+            Some(m)
+        }
         Err(why) => {
-            println!("Error sending message: {why:?}");
+            Span::current().record("reply", "error");
+            warn!(error = ?why, "cannot send message");
             None
         }
     }
 }
+
+/// Like `try_reply`, for replies that report a failure, so that scripts can tell, e.g. to skip
+/// `b` in `a && b`.
+pub async fn try_reply_error(ctx: &Context, msg: &Message, reply: impl std::fmt::Display) -> Option<Message> {
+    let _ = CAPTURED.try_with(|c| c.borrow_mut().failed = true);
+    try_reply(ctx, msg, reply).await
+}
+
+/// `try_reply` for `Ok`, `try_reply_error` for `Err`.
+pub async fn try_reply_result(
+    ctx: &Context,
+    msg: &Message,
+    reply: Result<impl std::fmt::Display, impl std::fmt::Display>,
+) -> Option<Message> {
+    match reply {
+        Ok(r) => try_reply(ctx, msg, r).await,
+        Err(e) => try_reply_error(ctx, msg, e).await,
+    }
+}
+
+/// The locale to reply to `msg` in, see `i18n::Locales::lookup`.
+pub async fn locale(ctx: &Context, msg: &Message) -> String {
+    let store = {
+        let data = ctx.data.read().await;
+        data.get::<LocalesTmk>().expect("Locales do not exist!").clone()
+    };
+    let locales = store.read().await;
+    locales.lookup(msg).to_string()
+}
+
+/// Whether the author of `msg` may change settings of the guild the message was sent in, i.e. owns
+/// it or has the Manage Server permission. Always false outside guilds.
+pub async fn is_guild_admin(ctx: &Context, msg: &Message) -> bool {
+    let Some(guild_id) = msg.guild_id else { return false };
+    let (guild, member) = match tokio::join!(
+        guild_id.to_partial_guild(&ctx.http),
+        guild_id.member(ctx, msg.author.id),
+    ) {
+        (Ok(g), Ok(m)) => (g, m),
+        (Err(why), _) | (_, Err(why)) => {
+            warn!(error = ?why, "cannot fetch guild permissions");
+            return false;
+        }
+    };
+    if guild.owner_id == msg.author.id {
+        return true;
+    }
+    // @everyone shares its id with the guild
+    let perms = guild.roles.values()
+        .filter(|r| r.id.0 == guild_id.0 || member.roles.contains(&r.id))
+        .fold(Permissions::empty(), |acc, r| acc | r.permissions);
+    perms.intersects(Permissions::ADMINISTRATOR | Permissions::MANAGE_GUILD)
+}
+
+/// The permissions of the author of `msg` in the channel the message was sent in, with the
+/// channel's overwrites applied. None outside guilds or if they cannot be fetched.
+pub async fn channel_permissions(ctx: &Context, msg: &Message) -> Option<Permissions> {
+    let guild_id = msg.guild_id?;
+    let (guild, member, channel) = match tokio::join!(
+        guild_id.to_partial_guild(&ctx.http),
+        guild_id.member(ctx, msg.author.id),
+        msg.channel_id.to_channel(ctx),
+    ) {
+        (Ok(g), Ok(m), Ok(c)) => (g, m, c),
+        (Err(why), _, _) | (_, Err(why), _) | (_, _, Err(why)) => {
+            warn!(error = ?why, "cannot fetch channel permissions");
+            return None;
+        }
+    };
+    guild.user_permissions_in(&channel.guild()?, &member)
+        .map_err(|why| warn!(error = ?why, "cannot compute channel permissions"))
+        .ok()
+}
+
+/// Whether replying `text` to `msg` would have the bot feed itself a command, i.e. `msg` is the
+/// bot's own and `text` begins with the command prefix. Commands that repeat text refuse then.
+pub async fn feeds_itself(ctx: &Context, msg: &Message, text: &str) -> bool {
+    if msg.author.id != ctx.cache.current_user().id {
+        return false;
+    }
+    let config = ctx.data.read().await.get::<ConfigTmk>().expect("Config does not exist!").clone();
+    nom_args::strip_prefix(text, &config.prefix).is_some()
+}
+
+/// Parse a channel given as a mention (`<#123>`) or as a bare id.
+pub fn parse_channel(s: &str) -> Option<ChannelId> {
+    match Mention::from_literal(s)? {
+        Mention::Channel(id) | Mention::Id(id) => Some(ChannelId(id)),
+        Mention::User(_) | Mention::Role(_) => None,
+    }
+}
+
+/// `s` with a zero-width space after every `@`, so that `<@id>`, `<@&id>`, `@everyone` and `@here`
+/// do not mention anyone.
+pub fn defuse(s: &str) -> String {
+    s.replace('@', "@\u{200b}")
+}
@@ -0,0 +1,294 @@
+//! Command packs compiled separately from the bot and loaded as dynamic libraries.
+//!
+//! A plugin is a `cdylib` exporting
+//!
+//! ```c
+//! const struct NombotPluginV1 *nombot_plugin_v1(void);
+//! ```
+//!
+//! which returns a pointer to a static [`PluginV1`]. Every string crossing the boundary is
+//! NUL-terminated UTF-8. Strings returned by `whatis` and `call` are owned by the plugin and handed
+//! back to its `free_string`. `call` receives a [`PluginRequest`] as JSON and returns the reply
+//! text; an empty reply sends nothing. The functions may be called from several threads at once.
+//!
+//! A minimal plugin in Rust:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub extern "C" fn nombot_plugin_v1() -> *const PluginV1 { &PLUGIN }
+//!
+//! static PLUGIN: PluginV1 = PluginV1 {
+//!     abi_version: 1,
+//!     name: c"fun".as_ptr(),
+//!     commands: &COMMANDS.0 as *const _ as *const *const c_char,
+//!     whatis, call, free_string,
+//! };
+//! static COMMANDS: Names<2> = Names([c"coin".as_ptr(), std::ptr::null()]);
+//!
+//! // raw pointers are not `Sync`, so statics holding them need a wrapper
+//! struct Names<const N: usize>([*const c_char; N]);
+//! unsafe impl<const N: usize> Sync for Names<N> {}
+//! unsafe impl Sync for PluginV1 {}
+//! ```
+//!
+//! Plugins run inside the bot process, so a misbehaving plugin can take the bot down with it.
+//! Only load plugins you trust.
+
+use std::collections::{ BTreeSet, HashSet };
+use std::ffi::{ c_char, CStr, CString };
+use std::path::{ Path, PathBuf };
+use std::sync::{ Arc, Mutex };
+
+use async_trait::async_trait;
+use libloading::Library;
+use serde::Serialize;
+
+use crate::defn::command::{ Arg, Command, Context, Message, ClientData, CommandHandler };
+use crate::defn::registry::ModuleFactory;
+use crate::nom_util as util;
+
+pub const ABI_VERSION: u32 = 1;
+const ENTRY_POINT: &[u8] = b"nombot_plugin_v1";
+
+#[repr(C)]
+pub struct PluginV1 {
+    pub abi_version: u32,
+    /// The module name.
+    pub name: *const c_char,
+    /// The command names, terminated by a null pointer.
+    pub commands: *const *const c_char,
+    pub whatis: extern "C" fn(name: *const c_char) -> *mut c_char,
+    pub call: extern "C" fn(request: *const c_char) -> *mut c_char,
+    pub free_string: extern "C" fn(s: *mut c_char),
+}
+
+/// What a plugin receives for each invocation, serialized as JSON.
+#[derive(Debug, Serialize)]
+pub struct PluginRequest<'a> {
+    pub name: &'a str,
+    /// Positional arguments have a null `key`.
+    pub args: Vec<PluginArg<'a>>,
+    pub rest: &'a str,
+    pub author: u64,
+    pub channel: u64,
+    pub guild: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginArg<'a> {
+    pub key: Option<&'a str>,
+    pub value: &'a str,
+    /// The language tag of a code block, possibly empty; absent for other arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<&'a str>,
+}
+
+impl<'a> PluginRequest<'a> {
+    pub fn new(cmd: &'a Command, msg: &Message) -> Self {
+        Self {
+            name: &cmd.name,
+            args: cmd.args.iter().map(|a| match a {
+                Arg::Pos(v) => PluginArg { key: None, value: v, lang: None },
+                Arg::Kw(k, v) => PluginArg { key: Some(k), value: v, lang: None },
+                Arg::Code { key, lang, code } => PluginArg { key: key.as_deref(), value: code, lang: Some(lang) },
+            }).collect(),
+            rest: &cmd.rest,
+            author: msg.author.id.0,
+            channel: msg.channel_id.0,
+            guild: msg.guild_id.map(|g| g.0),
+        }
+    }
+}
+
+pub struct Plugin {
+    pub name: String,
+    /// The library it was loaded from.
+    pub path: PathBuf,
+    commands: Vec<String>,
+    vtable: *const PluginV1,
+    // keeps `vtable` alive; declared last so that it is dropped last
+    _lib: Library,
+}
+
+// The ABI requires plugin functions to be callable from any thread.
+unsafe impl Send for Plugin {}
+unsafe impl Sync for Plugin {}
+
+impl Plugin {
+    /// Load the plugin at `path` and check its ABI version.
+    ///
+    /// # Safety
+    /// Loading runs the library's initializers, and the library must uphold the ABI described in
+    /// the module documentation.
+    pub unsafe fn load(path: &Path) -> Result<Self, String> {
+        let lib = Library::new(path).map_err(|e| format!("cannot load plugin {}: {e}", path.display()))?;
+        let entry: libloading::Symbol<extern "C" fn() -> *const PluginV1> = lib.get(ENTRY_POINT)
+            .map_err(|e| format!("{} is not a nombot plugin: {e}", path.display()))?;
+        let vtable = entry();
+        if vtable.is_null() || (*vtable).abi_version != ABI_VERSION {
+            return Err(format!("{} does not implement plugin ABI version {ABI_VERSION}", path.display()));
+        }
+        let v = &*vtable;
+        if v.name.is_null() || v.commands.is_null() {
+            return Err(format!("{}: the plugin has no name or no command list", path.display()));
+        }
+        let read = |p: *const c_char| CStr::from_ptr(p).to_str().map(String::from)
+            .map_err(|_| format!("{}: plugin strings must be UTF-8", path.display()));
+        let name = read(v.name)?;
+        let mut commands = vec![];
+        let mut p = v.commands;
+        while !(*p).is_null() {
+            commands.push(read(*p)?);
+            p = p.add(1);
+        }
+        Ok(Self { name, path: path.to_path_buf(), commands, vtable, _lib: lib })
+    }
+
+    fn take_string(&self, s: *mut c_char) -> String {
+        if s.is_null() {
+            return String::new();
+        }
+        let r = unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned();
+        (unsafe { &*self.vtable }.free_string)(s);
+        r
+    }
+
+    pub fn whatis(&self, name: &str) -> String {
+        let name = CString::new(name).unwrap_or_default();
+        self.take_string((unsafe { &*self.vtable }.whatis)(name.as_ptr()))
+    }
+
+    pub fn call(&self, request: &str) -> String {
+        let request = CString::new(request).unwrap_or_default();
+        self.take_string((unsafe { &*self.vtable }.call)(request.as_ptr()))
+    }
+
+    pub fn factory(self: &Arc<Self>) -> ModuleFactory {
+        let p = self.clone();
+        Arc::new(move || Box::new(PluginHandler(p.clone())))
+    }
+}
+
+/// The registry binds `&'static str` names. Plugin names are made static here, once per distinct
+/// name, however often plugins are registered or reloaded.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    match names.get(name) {
+        Some(&n) => n,
+        None => {
+            let n: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.insert(n);
+            n
+        }
+    }
+}
+
+/// Libraries that `scan` has loaded a plugin from. They are not opened again.
+static LOADED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// Load every new plugin in `dir`, i.e. every file with the platform's dynamic library extension
+/// that no earlier scan has loaded a plugin from.
+pub fn scan(dir: &Path) -> Result<Vec<Result<Arc<Plugin>, String>>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("cannot read plugin directory {}: {e}", dir.display()))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == std::env::consts::DLL_EXTENSION))
+        .collect();
+    paths.sort();
+    let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+    let loaded = loaded.get_or_insert_with(HashSet::new);
+    paths.retain(|p| !loaded.contains(p));
+    Ok(paths.into_iter().map(|p| {
+        let plugin = unsafe { Plugin::load(&p) }?;
+        loaded.insert(p);
+        Ok(Arc::new(plugin))
+    }).collect())
+}
+
+/// Let the next `scan` load the library at `path` again, e.g. after its module could not be added.
+pub fn forget(path: &Path) {
+    if let Some(loaded) = LOADED.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        loaded.remove(path);
+    }
+}
+
+pub struct PluginHandler(Arc<Plugin>);
+
+#[async_trait]
+impl CommandHandler for PluginHandler {
+    async fn whatis(&self, name: &str, _: &str) -> String { self.0.whatis(name) }
+    async fn register(&mut self, _: ClientData) -> Vec<&'static str> {
+        self.0.commands.iter().map(|n| intern(n)).collect()
+    }
+    async fn call(&self, cmd: Command, ctx: Context, msg: Message) {
+        let request = serde_json::to_string(&PluginRequest::new(&cmd, &msg)).expect("requests always serialize");
+        let p = self.0.clone();
+        // plugins may block, so keep them off the async workers
+        let reply = match tokio::task::spawn_blocking(move || p.call(&request)).await {
+            Ok(r) if r.is_empty() => return,
+            Ok(r) => Ok(r),
+            Err(why) => {
+                tracing::error!(plugin = %self.0.name, error = %why, "plugin call failed");
+                Err(format!("```\n{}: the plugin failed\n```", cmd.name))
+            }
+        };
+        let _: Option<_> = util::try_reply_result(&ctx, &msg, reply).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `cargo test` builds the examples, including examples/coin_plugin.rs, next to the deps.
+    fn example_plugin() -> PathBuf {
+        let examples = std::env::current_exe().unwrap().parent().unwrap().parent().unwrap().join("examples");
+        examples.join(format!("{}coin_plugin.{}", std::env::consts::DLL_PREFIX, std::env::consts::DLL_EXTENSION))
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = std::env::temp_dir().join(format!("nombot-plugin-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a plugin").unwrap();
+        std::fs::write(dir.join(format!("bogus.{}", std::env::consts::DLL_EXTENSION)), "not a library").unwrap();
+        let found = scan(&dir).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].is_err());
+
+        // a library that loaded once is skipped by later scans
+        std::fs::copy(example_plugin(), dir.join(format!("coin.{}", std::env::consts::DLL_EXTENSION))).unwrap();
+        let found = scan(&dir).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].as_ref().map(|p| &p.name[..]), Ok("coin"));
+        let found = scan(&dir).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].is_err());
+        // unless it is forgotten
+        forget(&dir.join(format!("coin.{}", std::env::consts::DLL_EXTENSION)));
+        assert_eq!(scan(&dir).unwrap().len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(scan(&dir).is_err());
+    }
+
+    #[test]
+    fn test_example_plugin() {
+        let p = unsafe { Plugin::load(&example_plugin()) }
+            .unwrap_or_else(|e| panic!("{e}; build it with `cargo build --example coin_plugin`"));
+        assert_eq!(p.name, "coin");
+        assert_eq!(p.commands, vec!["coin", "shout"]);
+        assert_eq!(p.whatis("shout"), "Shout the rest-field");
+
+        let request = |name, rest| serde_json::to_string(&PluginRequest {
+            name, args: vec![PluginArg { key: Some("times"), value: "2", lang: None }], rest,
+            author: 1, channel: 2, guild: None,
+        }).unwrap();
+        assert_eq!(p.call(&request("shout", "nom nom")), "NOM NOM! NOM NOM!");
+        assert!(["heads", "tails"].contains(&&p.call(&request("coin", ""))[..]));
+        assert_eq!(p.call(&request("shout", "")), "");
+
+        // every load of the plugin binds the same names
+        assert!(std::ptr::eq(intern(&p.commands[0]), intern(&String::from("coin"))));
+    }
+}
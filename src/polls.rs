@@ -0,0 +1,252 @@
+//! Polls voted on with reactions, and the task that closes them.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{ Deserialize, Serialize };
+use serenity::http::Http;
+use serenity::model::channel::{ Reaction, ReactionType };
+use serenity::model::id::{ ChannelId, MessageId };
+use tokio::sync::Notify;
+use tracing::{ debug, info, warn };
+
+use crate::defn::command::Context;
+use crate::defn::globals::PollsTmk;
+use crate::i18n;
+use crate::storage::JsonStore;
+
+/// The reactions voting for each option, in order.
+pub const EMOJI: [&str; 10] = [
+    "1\u{fe0f}\u{20e3}", "2\u{fe0f}\u{20e3}", "3\u{fe0f}\u{20e3}", "4\u{fe0f}\u{20e3}", "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}", "7\u{fe0f}\u{20e3}", "8\u{fe0f}\u{20e3}", "9\u{fe0f}\u{20e3}", "\u{1f51f}",
+];
+pub const MAX_OPEN_PER_CHANNEL: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Poll {
+    pub id: u64,
+    /// The message showing the poll, whose reactions are the votes.
+    pub message: u64,
+    pub channel: u64,
+    pub author: u64,
+    pub question: String,
+    pub options: Vec<String>,
+    /// Unix time in seconds.
+    pub closes: i64,
+    /// The option each user voted for.
+    #[serde(default)]
+    pub votes: BTreeMap<u64, usize>,
+    /// The locale to announce the results in, that of the author.
+    #[serde(default)]
+    pub locale: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Vote {
+    New,
+    /// The user had voted for this other option.
+    Changed(usize),
+    Same,
+}
+
+impl Poll {
+    pub fn vote(&mut self, user: u64, option: usize) -> Vote {
+        match self.votes.insert(user, option) {
+            None => Vote::New,
+            Some(o) if o == option => Vote::Same,
+            Some(o) => Vote::Changed(o),
+        }
+    }
+
+    /// Take back the vote of `user` if it is for `option`.
+    pub fn unvote(&mut self, user: u64, option: usize) -> bool {
+        let voted = self.votes.get(&user) == Some(&option);
+        if voted {
+            self.votes.remove(&user);
+        }
+        voted
+    }
+
+    /// The message announcing the results.
+    pub fn results(&self) -> String {
+        let mut tally = vec![0usize; self.options.len()];
+        for &o in self.votes.values() {
+            tally[o] += 1;
+        }
+        let total = self.votes.len();
+        if total == 0 {
+            return i18n::tr(&self.locale, "poll-results-none", &[("question", self.question.as_str().into())]);
+        }
+        let best = tally.iter().copied().max().unwrap_or(0);
+        let lines: Vec<_> = self.options.iter().zip(tally.iter()).map(|(o, &n)| {
+            let line = format!("{o} \u{2014} {n} ({}%)", n * 100 / total);
+            if n == best { format!("**{line}**") } else { line }
+        }).collect();
+        i18n::tr(&self.locale, "poll-results", &[
+            ("question", self.question.as_str().into()), ("total", total.into()), ("lines", lines.join("\n").into()),
+        ])
+    }
+}
+
+/// The polls still open.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OpenPolls {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub polls: Vec<Poll>,
+}
+
+impl OpenPolls {
+    pub fn in_channel(&self, channel: u64) -> impl Iterator<Item = &Poll> {
+        self.polls.iter().filter(move |p| p.channel == channel)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Poll> {
+        let i = self.polls.iter().position(|p| p.id == id)?;
+        Some(self.polls.remove(i))
+    }
+
+    fn next_close(&self) -> Option<i64> {
+        self.polls.iter().map(|p| p.closes).min()
+    }
+}
+
+/// The open polls, and a way to tell the closing task they changed.
+pub struct Polls {
+    pub store: JsonStore<OpenPolls>,
+    pub changed: Notify,
+}
+
+/// The option a reaction votes for.
+pub fn option_of(emoji: &ReactionType) -> Option<usize> {
+    match emoji {
+        ReactionType::Unicode(s) => EMOJI.iter().position(|e| e == s),
+        _ => None,
+    }
+}
+
+/// Count a reaction added to or removed from a poll. A user voting for another option has their
+/// earlier reaction removed, if the bot may.
+pub async fn on_reaction(ctx: &Context, r: &Reaction, added: bool) {
+    let (Some(user), Some(option)) = (r.user_id, option_of(&r.emoji)) else { return };
+    if user == ctx.cache.current_user().id {
+        return;
+    }
+    let polls = {
+        let data = ctx.data.read().await;
+        match data.get::<PollsTmk>() {
+            Some(p) => p.clone(),
+            None => return,
+        }
+    };
+    if !polls.store.read().await.polls.iter().any(|p| p.message == r.message_id.0) {
+        return;
+    }
+    let result = polls.store.update(|open| {
+        let poll = open.polls.iter_mut().find(|p| p.message == r.message_id.0)?;
+        if option >= poll.options.len() {
+            return None;
+        }
+        match added {
+            true => Some(poll.vote(user.0, option)),
+            false => { poll.unvote(user.0, option); None }
+        }
+    }).await;
+    match result {
+        Ok(Some(Vote::Changed(earlier))) => {
+            let emoji = ReactionType::Unicode(EMOJI[earlier].into());
+            if let Err(why) = r.channel_id.delete_reaction(&ctx.http, r.message_id, Some(user), emoji).await {
+                debug!(error = ?why, "cannot remove an earlier vote");
+            }
+        }
+        Ok(_) => {}
+        Err(why) => warn!(error = %why, "cannot save polls"),
+    }
+}
+
+/// Close polls as they are due, until the bot stops. Polls due while the bot was down are
+/// closed right away.
+pub async fn run(http: Arc<Http>, polls: Arc<Polls>) {
+    loop {
+        let now = Utc::now().timestamp();
+        let (due, next) = {
+            let open = polls.store.read().await;
+            (open.polls.iter().filter(|p| p.closes <= now).map(|p| p.id).collect::<Vec<_>>(), open.next_close())
+        };
+        for &id in due.iter() {
+            if let Some(Err(why)) = close(&http, &polls, id).await {
+                warn!(error = %why, "cannot save polls");
+            }
+        }
+        if !due.is_empty() {
+            continue;
+        }
+        let wait = next.map_or(3600, |n| (n - now).clamp(1, 3600)) as u64;
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(wait)) => {}
+            _ = polls.changed.notified() => {}
+        }
+    }
+}
+
+/// Close the poll `id`: take it out of the open polls, then post its results in reply to it.
+/// Only whoever takes it posts, so a poll closed early as it falls due is announced once.
+/// `None` if it is not open (any more); an error if it is closed but the change is not saved.
+pub async fn close(http: &Http, polls: &Polls, id: u64) -> Option<Result<(), String>> {
+    let mut taken = None;
+    let saved = polls.store.update(|open| taken = open.remove(id)).await;
+    announce(http, &taken?).await;
+    Some(saved)
+}
+
+async fn announce(http: &Http, poll: &Poll) {
+    let channel = ChannelId(poll.channel);
+    let sent = channel.send_message(http, |m| {
+        // the question and the options are the author's text, which may mention anyone
+        m.content(poll.results())
+            .reference_message((channel, MessageId(poll.message)))
+            .allowed_mentions(|am| am.empty_parse())
+    }).await;
+    match sent {
+        Ok(_) => info!(id = poll.id, votes = poll.votes.len(), "closed poll"),
+        Err(why) => warn!(id = poll.id, error = ?why, "cannot post poll results"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn poll() -> Poll {
+        Poll {
+            id: 1, message: 10, channel: 20, author: 30, question: "Lunch?".into(),
+            options: vec!["Pizza".into(), "Sushi".into(), "Tacos".into()], closes: 0, votes: BTreeMap::new(),
+            locale: "en".into(),
+        }
+    }
+
+    #[test]
+    fn test_votes() {
+        let mut p = poll();
+        assert_eq!(p.results(), "\u{1f4ca} **Lunch?** has closed without votes.");
+        assert_eq!(p.vote(1, 0), Vote::New);
+        assert_eq!(p.vote(1, 0), Vote::Same);
+        assert_eq!(p.vote(1, 1), Vote::Changed(0));
+        // removing the earlier reaction does not take back the new vote
+        assert!(!p.unvote(1, 0));
+        assert_eq!(p.results(), "\u{1f4ca} **Lunch?** has closed with 1 vote:\nPizza \u{2014} 0 (0%)\n**Sushi \u{2014} 1 (100%)**\nTacos \u{2014} 0 (0%)");
+        p.vote(2, 0);
+        p.vote(3, 0);
+        p.vote(4, 2);
+        assert!(p.unvote(4, 2));
+        assert_eq!(p.votes.len(), 3);
+        assert_eq!(p.results(), "\u{1f4ca} **Lunch?** has closed with 3 votes:\n**Pizza \u{2014} 2 (66%)**\nSushi \u{2014} 1 (33%)\nTacos \u{2014} 0 (0%)");
+
+        assert_eq!(option_of(&ReactionType::Unicode("3\u{fe0f}\u{20e3}".into())), Some(2));
+        assert_eq!(option_of(&ReactionType::Unicode("\u{1f51f}".into())), Some(9));
+        assert_eq!(option_of(&ReactionType::Unicode("\u{1f355}".into())), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Quotes saved from messages, per guild.
+
+use std::collections::BTreeMap;
+use serde::{ Deserialize, Serialize };
+use serenity::model::channel::{ Reaction, ReactionType };
+use serenity::model::id::GuildId;
+use tracing::{ info, warn };
+
+use crate::defn::command::{ Context, Message };
+use crate::defn::globals::{ LocalesTmk, QuotesTmk };
+use crate::i18n::{ self, Msg };
+use crate::nom_util::defuse;
+
+/// Reacting with this saves a message as a quote.
+pub const EMOJI: &str = "\u{1f4ac}";
+pub const MAX_PER_GUILD: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    pub id: u64,
+    pub text: String,
+    pub author: u64,
+    /// The author's name when the quote was saved.
+    pub author_name: String,
+    /// When the quoted message was sent, in Unix seconds.
+    pub timestamp: i64,
+    pub link: String,
+    /// The quoted message.
+    pub message: u64,
+    pub saved_by: u64,
+}
+
+impl Quote {
+    /// A quote of `m`, a message in `guild`; messages fetched or referenced carry no guild id.
+    pub fn from_message(m: &Message, guild: GuildId, saved_by: u64) -> Result<Self, Msg> {
+        if m.content.trim().is_empty() {
+            return Err(Msg::new("quote-no-text", &[]));
+        }
+        Ok(Quote {
+            id: 0,
+            text: m.content.clone(),
+            author: m.author.id.0,
+            author_name: m.author.name.clone(),
+            timestamp: m.timestamp.unix_timestamp(),
+            link: format!("https://discord.com/channels/{}/{}/{}", guild.0, m.channel_id.0, m.id.0),
+            message: m.id.0,
+            saved_by,
+        })
+    }
+
+    /// The quote as a reply. The author is named rather than mentioned, and mentions in the text
+    /// are broken up, so that nobody is pinged by recalling it.
+    pub fn show(&self, locale: &str) -> String {
+        let text: Vec<_> = defuse(&self.text).lines().map(|l| format!("> {l}")).collect();
+        i18n::tr(locale, "quote-shown", &[
+            ("text", text.join("\n").into()), ("name", defuse(&self.author_name).into()),
+            ("time", self.timestamp.to_string().into()), ("link", self.link.as_str().into()), ("id", self.id.to_string().into()),
+        ])
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GuildQuotes {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub quotes: Vec<Quote>,
+}
+
+/// Saved quotes, by guild id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Quotes {
+    #[serde(default)]
+    pub guilds: BTreeMap<u64, GuildQuotes>,
+}
+
+impl Quotes {
+    /// Save `q` in `guild`, giving it the next id of the guild.
+    pub fn add(&mut self, guild: u64, mut q: Quote) -> Result<u64, Msg> {
+        let g = self.guilds.entry(guild).or_default();
+        if let Some(old) = g.quotes.iter().find(|o| o.message == q.message) {
+            return Err(Msg::new("quote-already", &[("id", old.id.to_string().into())]));
+        }
+        if g.quotes.len() >= MAX_PER_GUILD {
+            return Err(Msg::new("quote-too-many", &[("max", MAX_PER_GUILD.into())]));
+        }
+        g.next_id += 1;
+        q.id = g.next_id;
+        g.quotes.push(q);
+        Ok(g.next_id)
+    }
+
+    pub fn all(&self, guild: u64) -> &[Quote] {
+        self.guilds.get(&guild).map_or(&[], |g| &g.quotes[..])
+    }
+
+    pub fn get(&self, guild: u64, id: u64) -> Option<&Quote> {
+        self.all(guild).iter().find(|q| q.id == id)
+    }
+
+    pub fn by(&self, guild: u64, author: u64) -> Vec<&Quote> {
+        self.all(guild).iter().filter(|q| q.author == author).collect()
+    }
+
+    /// Quotes containing every word of `text`, ignoring case.
+    pub fn search(&self, guild: u64, text: &str) -> Vec<&Quote> {
+        let words: Vec<_> = text.split_whitespace().map(|w| w.to_lowercase()).collect();
+        self.all(guild).iter().filter(|q| {
+            let t = q.text.to_lowercase();
+            words.iter().all(|w| t.contains(&w[..]))
+        }).collect()
+    }
+}
+
+/// Save the message reacted to with `EMOJI` as a quote.
+pub async fn on_reaction(ctx: &Context, r: &Reaction) {
+    if !matches!(&r.emoji, ReactionType::Unicode(e) if e == EMOJI) {
+        return;
+    }
+    let (Some(guild), Some(user)) = (r.guild_id, r.user_id) else { return };
+    if user == ctx.cache.current_user().id {
+        return;
+    }
+    let (store, locales) = {
+        let data = ctx.data.read().await;
+        match (data.get::<QuotesTmk>(), data.get::<LocalesTmk>()) {
+            (Some(s), Some(l)) => (s.clone(), l.clone()),
+            _ => return,
+        }
+    };
+    if store.read().await.all(guild.0).iter().any(|q| q.message == r.message_id.0) {
+        return;
+    }
+    let m = match r.message(&ctx.http).await {
+        Ok(m) => m,
+        Err(why) => {
+            warn!(error = ?why, "cannot fetch the message to quote");
+            return;
+        }
+    };
+    let Ok(q) = Quote::from_message(&m, guild, user.0) else { return };
+    match store.update(|s| s.add(guild.0, q)).await {
+        Ok(Ok(id)) => {
+            info!(id, guild = guild.0, "saved quote");
+            let locale = locales.read().await.of(user.0, Some(guild.0)).to_string();
+            let saved = i18n::tr(&locale, "quote-saved", &[("id", id.to_string().into())]);
+            if let Err(why) = m.reply(&ctx.http, saved).await {
+                warn!(error = ?why, "cannot confirm quote");
+            }
+        }
+        Ok(Err(_)) => {}
+        Err(why) => warn!(error = %why, "cannot save quotes"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn quote(message: u64, author: u64, text: &str) -> Quote {
+        Quote {
+            id: 0, text: text.into(), author, author_name: format!("user{author}"), timestamp: 1_700_000_000,
+            link: format!("https://discord.com/channels/1/2/{message}"), message, saved_by: 9,
+        }
+    }
+
+    #[test]
+    fn test_quotes() {
+        let mut s = Quotes::default();
+        assert_eq!(s.add(1, quote(10, 5, "Nom nom nom")), Ok(1));
+        assert_eq!(s.add(1, quote(11, 6, "cookies are sweet\nroses are red")), Ok(2));
+        assert_eq!(s.add(2, quote(12, 5, "elsewhere")), Ok(1));
+        assert_eq!(s.add(1, quote(10, 5, "Nom nom nom")).unwrap_err().tr("en"), "that message is already quote #1");
+
+        assert_eq!(s.get(1, 2).map(|q| q.message), Some(11));
+        assert_eq!(s.get(2, 2), None);
+        assert_eq!(s.by(1, 5).len(), 1);
+        assert_eq!(s.search(1, "RED cookies").iter().map(|q| q.id).collect::<Vec<_>>(), vec![2]);
+        assert!(s.search(1, "red pizza").is_empty());
+
+        assert_eq!(
+            s.get(1, 2).unwrap().show("en"),
+            "> cookies are sweet\n> roses are red\n\u{2014} **user6**, <t:1700000000:D> \u{b7} [jump](https://discord.com/channels/1/2/11) \u{b7} `#2`",
+        );
+        // recalling a quote pings nobody
+        s.add(1, quote(13, 7, "hey <@5> <@&6> @everyone")).unwrap();
+        assert!(s.get(1, 3).unwrap().show("en").starts_with("> hey <@\u{200b}5> <@\u{200b}&6> @\u{200b}everyone\n"));
+    }
+}
@@ -0,0 +1,82 @@
+//! The latest messages of each channel, kept in memory for commands that refer back to them.
+
+use std::collections::{ HashMap, VecDeque };
+
+/// Messages kept per channel.
+pub const PER_CHANNEL: usize = 50;
+/// Channels kept; the one written to longest ago is forgotten first.
+pub const MAX_CHANNELS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recent {
+    pub id: u64,
+    pub author: u64,
+    pub content: String,
+}
+
+#[derive(Debug, Default)]
+struct Channel {
+    messages: VecDeque<Recent>,
+    /// When the channel was last written to, as a count of `push`es.
+    touched: u64,
+}
+
+/// Ring buffers of recent messages, by channel id.
+#[derive(Debug, Default)]
+pub struct RecentMessages {
+    channels: HashMap<u64, Channel>,
+    pushes: u64,
+}
+
+impl RecentMessages {
+    pub fn push(&mut self, channel: u64, m: Recent) {
+        self.pushes += 1;
+        if !self.channels.contains_key(&channel) && self.channels.len() >= MAX_CHANNELS {
+            if let Some(&oldest) = self.channels.iter().min_by_key(|(_, c)| c.touched).map(|(k, _)| k) {
+                self.channels.remove(&oldest);
+            }
+        }
+        let c = self.channels.entry(channel).or_default();
+        c.touched = self.pushes;
+        if c.messages.len() >= PER_CHANNEL {
+            c.messages.pop_front();
+        }
+        c.messages.push_back(m);
+    }
+
+    /// The latest message of `author` in `channel`.
+    pub fn last_of(&self, channel: u64, author: u64) -> Option<&Recent> {
+        self.channels.get(&channel)?.messages.iter().rev().find(|m| m.author == author)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_recent() {
+        let m = |id, author| Recent { id, author, content: format!("m{id}") };
+        let mut r = RecentMessages::default();
+        r.push(1, m(1, 10));
+        r.push(1, m(2, 20));
+        r.push(2, m(3, 10));
+        assert_eq!(r.last_of(1, 10).map(|m| m.id), Some(1));
+        assert_eq!(r.last_of(2, 10).map(|m| m.id), Some(3));
+        assert_eq!(r.last_of(2, 20), None);
+        for id in 0..PER_CHANNEL as u64 {
+            r.push(1, m(100 + id, 20));
+        }
+        assert_eq!(r.last_of(1, 10), None);
+
+        // channel 2 was written to longest ago
+        for c in 3..=MAX_CHANNELS as u64 {
+            r.push(c, m(0, 30));
+        }
+        r.push(1, m(5, 10));
+        r.push(MAX_CHANNELS as u64 + 1, m(6, 10));
+        assert_eq!(r.last_of(2, 10), None);
+        assert_eq!(r.last_of(1, 10).map(|m| m.id), Some(5));
+        assert_eq!(r.channels.len(), MAX_CHANNELS);
+    }
+}
@@ -0,0 +1,212 @@
+//! Reminders and the task that delivers them when they are due.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{ DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc };
+use chrono_tz::Tz;
+use serde::{ Deserialize, Serialize };
+use serenity::http::Http;
+use serenity::model::id::{ ChannelId, UserId };
+use tokio::sync::Notify;
+use tracing::{ info, warn };
+
+use crate::i18n::{ self, Msg };
+use crate::storage::JsonStore;
+
+pub const MAX_PER_USER: usize = 25;
+pub const MAX_TEXT_LEN: usize = 1000;
+/// How far ahead a reminder may be set, in seconds.
+pub const MAX_AHEAD: i64 = 366 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: u64,
+    pub user: u64,
+    /// Where the reminder was set, and where it is delivered unless `dm`.
+    pub channel: u64,
+    #[serde(default)]
+    pub dm: bool,
+    /// Unix time in seconds.
+    pub due: i64,
+    pub text: String,
+    /// The locale to deliver in, that of the user when they set it.
+    #[serde(default)]
+    pub locale: String,
+}
+
+/// The reminders not delivered yet.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Pending {
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub reminders: Vec<Reminder>,
+}
+
+impl Pending {
+    /// Add a reminder, giving it the next id.
+    pub fn add(&mut self, mut r: Reminder) -> Result<u64, Msg> {
+        if self.of_user(r.user).count() >= MAX_PER_USER {
+            return Err(Msg::new("remind-too-many", &[("max", MAX_PER_USER.into())]));
+        }
+        self.next_id += 1;
+        r.id = self.next_id;
+        self.reminders.push(r);
+        Ok(self.next_id)
+    }
+
+    pub fn of_user(&self, user: u64) -> impl Iterator<Item = &Reminder> {
+        self.reminders.iter().filter(move |r| r.user == user)
+    }
+
+    /// Remove the reminder `id` of `user`.
+    pub fn cancel(&mut self, user: u64, id: u64) -> Option<Reminder> {
+        let i = self.reminders.iter().position(|r| r.id == id && r.user == user)?;
+        Some(self.reminders.remove(i))
+    }
+
+    fn next_due(&self) -> Option<i64> {
+        self.reminders.iter().map(|r| r.due).min()
+    }
+
+    fn due(&self, now: i64) -> Vec<Reminder> {
+        self.reminders.iter().filter(|r| r.due <= now).cloned().collect()
+    }
+}
+
+/// The pending reminders, and a way to tell the scheduler they changed.
+pub struct Reminders {
+    pub store: JsonStore<Pending>,
+    pub changed: Notify,
+}
+
+/// The time that `at` (`YYYY-MM-DD HH:MM[:SS]`, or `HH:MM[:SS]` for the next such time) names in
+/// the time zone `tz`, after `now`.
+pub fn parse_at(at: &str, tz: Tz, now: DateTime<Utc>) -> Result<DateTime<Utc>, Msg> {
+    let at = at.trim();
+    let time = |s: &str| NaiveTime::parse_from_str(s, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(s, "%H:%M")).ok();
+    let local = match at.split_once([' ', 'T']) {
+        Some((d, t)) => match (NaiveDate::parse_from_str(d, "%Y-%m-%d"), time(t)) {
+            (Ok(d), Some(t)) => NaiveDateTime::new(d, t),
+            _ => return Err(Msg::new("remind-bad-time", &[("at", format!("{at:?}").into())])),
+        },
+        None => match time(at) {
+            Some(t) => {
+                let today = now.with_timezone(&tz).date_naive();
+                let dt = NaiveDateTime::new(today, t);
+                match resolve(dt, tz) {
+                    Ok(d) if d <= now => NaiveDateTime::new(today.succ_opt().ok_or_else(|| Msg::new("remind-too-far-ahead", &[]))?, t),
+                    _ => dt,
+                }
+            }
+            None => return Err(Msg::new("remind-bad-time", &[("at", format!("{at:?}").into())])),
+        },
+    };
+    let due = resolve(local, tz)?;
+    if due <= now {
+        return Err(Msg::new("remind-passed", &[("at", at.to_string().into())]));
+    }
+    Ok(due)
+}
+
+fn resolve(local: NaiveDateTime, tz: Tz) -> Result<DateTime<Utc>, Msg> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(d) => Ok(d.with_timezone(&Utc)),
+        // the earlier of the two when the clocks go back
+        LocalResult::Ambiguous(d, _) => Ok(d.with_timezone(&Utc)),
+        LocalResult::None => Err(Msg::new("remind-no-such-time", &[("time", local.to_string().into()), ("tz", tz.name().into())])),
+    }
+}
+
+/// Deliver reminders as they fall due, until the bot stops. Reminders that fell due while the
+/// bot was down are delivered right away.
+pub async fn run(http: Arc<Http>, reminders: Arc<Reminders>) {
+    loop {
+        let now = Utc::now().timestamp();
+        let (due, next) = {
+            let pending = reminders.store.read().await;
+            (pending.due(now), pending.next_due())
+        };
+        for r in due.iter() {
+            deliver(&http, r, now).await;
+        }
+        if !due.is_empty() {
+            let result = reminders.store.update(|p| p.reminders.retain(|r| !due.iter().any(|d| d.id == r.id))).await;
+            if let Err(why) = result {
+                warn!(error = %why, "cannot save reminders");
+            }
+            continue;
+        }
+        // wake up when the next one is due, or when one is added; at least hourly, to bound clock drift
+        let wait = next.map_or(3600, |n| (n - now).clamp(1, 3600)) as u64;
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(wait)) => {}
+            _ = reminders.changed.notified() => {}
+        }
+    }
+}
+
+async fn deliver(http: &Http, r: &Reminder, now: i64) {
+    let args = [("user", r.user.to_string().into()), ("due", r.due.to_string().into()), ("text", r.text.as_str().into())];
+    let text = match now - r.due > 60 {
+        true => i18n::tr(&r.locale, "remind-delivery-late", &args),
+        false => i18n::tr(&r.locale, "remind-delivery", &args),
+    };
+    let channel = match r.dm {
+        false => Ok(ChannelId(r.channel)),
+        true => UserId(r.user).create_dm_channel(http).await.map(|c| c.id),
+    };
+    // a reminder that cannot be delivered, e.g. to a deleted channel, is dropped
+    match channel {
+        // the text is the user's, so only they are pinged
+        Ok(c) => match c.send_message(http, |m| m.content(text).allowed_mentions(|am| am.empty_parse().users([UserId(r.user)]))).await {
+            Ok(_) => info!(id = r.id, user = r.user, "delivered reminder"),
+            Err(why) => warn!(id = r.id, error = ?why, "cannot deliver reminder"),
+        },
+        Err(why) => warn!(id = r.id, error = ?why, "cannot open DM for reminder"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_at() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        assert_eq!(parse_at("2026-11-01 09:00", berlin, now), Ok(Utc.with_ymd_and_hms(2026, 11, 1, 8, 0, 0).unwrap()));
+        assert_eq!(parse_at("2026-10-18T13:30:15", Tz::UTC, now), Ok(Utc.with_ymd_and_hms(2026, 10, 18, 13, 30, 15).unwrap()));
+        // the next such time
+        assert_eq!(parse_at("15:00", berlin, now), Ok(Utc.with_ymd_and_hms(2026, 10, 18, 13, 0, 0).unwrap()));
+        assert_eq!(parse_at("09:00", berlin, now), Ok(Utc.with_ymd_and_hms(2026, 10, 19, 7, 0, 0).unwrap()));
+        // the clocks go back on 2026-10-25 at 03:00 in Berlin
+        assert_eq!(parse_at("2026-10-25 02:30", berlin, now), Ok(Utc.with_ymd_and_hms(2026, 10, 25, 0, 30, 0).unwrap()));
+        assert!(parse_at("2027-03-28 02:30", berlin, now).unwrap_err().tr("en").contains("does not exist"));
+        assert_eq!(parse_at("2026-10-18 11:00", Tz::UTC, now).unwrap_err().tr("en"), "2026-10-18 11:00 has already passed");
+        assert!(parse_at("tomorrow", Tz::UTC, now).is_err());
+        assert!(parse_at("2026-13-01 09:00", Tz::UTC, now).is_err());
+    }
+
+    #[test]
+    fn test_pending() {
+        let r = |user, due| Reminder { id: 0, user, channel: 1, dm: false, due, text: "x".into(), locale: "en".into() };
+        let mut p = Pending::default();
+        assert_eq!(p.add(r(1, 30)), Ok(1));
+        assert_eq!(p.add(r(2, 10)), Ok(2));
+        assert_eq!(p.add(r(1, 20)), Ok(3));
+        assert_eq!(p.next_due(), Some(10));
+        assert_eq!(p.due(20).iter().map(|r| r.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(p.cancel(2, 1), None);
+        assert_eq!(p.cancel(1, 1).map(|r| r.id), Some(1));
+        assert_eq!(p.of_user(1).count(), 1);
+        for _ in 1..MAX_PER_USER {
+            p.add(r(1, 40)).unwrap();
+        }
+        assert!(p.add(r(1, 40)).is_err());
+        // ids are never reused
+        p.cancel(1, 3);
+        assert_eq!(p.add(r(3, 40)), Ok(MAX_PER_USER as u64 + 3));
+    }
+}
@@ -0,0 +1,170 @@
+//! User-scripted commands, written in Rhai and run with bounded operations, time and memory.
+
+use std::collections::BTreeMap;
+use std::time::{ Duration, Instant };
+
+use rhai::{ Dynamic, Engine, Map, Scope };
+use serde::{ Deserialize, Serialize };
+
+use crate::defn::command::{ Arg, Command };
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Script {
+    pub source: String,
+    /// The user who last set the script.
+    pub author: u64,
+}
+
+/// Scripts by guild id, then by command name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GuildScripts {
+    #[serde(default)]
+    pub scripts: BTreeMap<u64, BTreeMap<String, Script>>,
+}
+
+impl GuildScripts {
+    pub fn get(&self, guild: u64, name: &str) -> Option<&Script> {
+        self.scripts.get(&guild).and_then(|s| s.get(name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limits {
+    pub max_operations: u64,
+    pub timeout: Duration,
+    /// Bounds the size of every string, array and map, which in turn bounds memory use.
+    pub max_size: usize,
+    pub max_call_depth: usize,
+    pub max_source_len: usize,
+    pub max_reply_len: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            timeout: Duration::from_millis(250),
+            max_size: 10_000,
+            max_call_depth: 16,
+            max_source_len: 4_000,
+            max_reply_len: 2_000,
+        }
+    }
+}
+
+impl Limits {
+    /// Read the limits from the `[commands.settings.script]` table, falling back to the defaults.
+    pub fn from_settings(t: Option<&toml::Table>) -> Result<Self, String> {
+        let mut l = Self::default();
+        let Some(t) = t else { return Ok(l) };
+        for (k, v) in t.iter() {
+            let n = v.as_integer().filter(|&n| n > 0)
+                .ok_or_else(|| format!("commands.settings.script.{k} must be a positive integer"))?;
+            match &k[..] {
+                "max-operations" => l.max_operations = n as u64,
+                "timeout-ms" => l.timeout = Duration::from_millis(n as u64),
+                "max-size" => l.max_size = n as usize,
+                "max-call-depth" => l.max_call_depth = n as usize,
+                "max-source-length" => l.max_source_len = n as usize,
+                "max-reply-length" => l.max_reply_len = n as usize,
+                _ => return Err(format!("commands.settings.script: unknown setting {k:?}")),
+            }
+        }
+        Ok(l)
+    }
+
+    fn engine(&self) -> Engine {
+        let mut e = Engine::new();
+        e.set_max_operations(self.max_operations)
+            .set_max_string_size(self.max_size)
+            .set_max_array_size(self.max_size)
+            .set_max_map_size(self.max_size)
+            .set_max_call_levels(self.max_call_depth)
+            .set_max_expr_depths(64, 32);
+        e.disable_symbol("eval");
+        e.on_print(|_| ());
+        e.on_debug(|_, _, _| ());
+        let deadline = Instant::now() + self.timeout;
+        e.on_progress(move |_| if Instant::now() > deadline { Some("timed out".into()) } else { None });
+        e
+    }
+}
+
+/// Check that `source` is a script worth storing.
+pub fn compile(source: &str, limits: &Limits) -> Result<(), String> {
+    if source.len() > limits.max_source_len {
+        return Err(format!("the script is longer than {} bytes", limits.max_source_len));
+    }
+    limits.engine().compile(source).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Run a script for `cmd`. The script sees a constant `cmd` map with `name`, `args` (positional
+/// arguments), `kw` (keyword arguments), `rest`, `author` and `channel`, and its value is the
+/// reply.
+pub fn run(source: &str, cmd: &Command, author: u64, channel: u64, limits: &Limits) -> Result<String, String> {
+    let mut args = rhai::Array::new();
+    let mut kw = Map::new();
+    for a in cmd.args.iter() {
+        match a {
+            Arg::Pos(v) => args.push(v.clone().into()),
+            Arg::Kw(k, v) => { kw.insert(k.into(), v.clone().into()); }
+            // scripts see code blocks as their code
+            Arg::Code { key: None, code, .. } => args.push(code.clone().into()),
+            Arg::Code { key: Some(k), code, .. } => { kw.insert(k.into(), code.clone().into()); }
+        }
+    }
+    let mut m = Map::new();
+    m.insert("name".into(), cmd.name.clone().into());
+    m.insert("args".into(), args.into());
+    m.insert("kw".into(), kw.into());
+    m.insert("rest".into(), cmd.rest.clone().into());
+    m.insert("author".into(), author.to_string().into());
+    m.insert("channel".into(), channel.to_string().into());
+    let mut scope = Scope::new();
+    scope.push_constant("cmd", m);
+
+    let v: Dynamic = limits.engine().eval_with_scope(&mut scope, source).map_err(|e| e.to_string())?;
+    let reply = if v.is_unit() { String::new() } else { v.to_string() };
+    if reply.len() > limits.max_reply_len {
+        return Err(format!("the reply is longer than {} bytes", limits.max_reply_len));
+    }
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cmd(args: Vec<Arg>, rest: &str) -> Command { Command::new("s".into(), args, rest.into()) }
+
+    #[test]
+    fn test_run() {
+        let l = Limits::default();
+        assert_eq!(
+            run(r#"`${cmd.kw.greeting}, ${cmd.rest}!` + cmd.args.len()"#,
+                &cmd(vec![Arg::Pos("x".into()), Arg::Kw("greeting".into(), "hi".into())], "nom"), 1, 2, &l),
+            Ok("hi, nom!1".into()),
+        );
+        assert_eq!(run("let x = 1;", &cmd(vec![], ""), 1, 2, &l), Ok("".into()));
+    }
+
+    #[test]
+    fn test_limits() {
+        let l = Limits { timeout: Duration::from_secs(10), ..Limits::default() };
+        assert!(run("loop {}", &cmd(vec![], ""), 1, 2, &l).is_err());
+        assert!(run(r#"let s = "a"; loop { s += s; }"#, &cmd(vec![], ""), 1, 2, &l).is_err());
+        let l = Limits { max_operations: u64::MAX, timeout: Duration::from_millis(10), ..Limits::default() };
+        assert!(run("loop {}", &cmd(vec![], ""), 1, 2, &l).is_err());
+        assert!(compile("eval(\"1\")", &l).is_err());
+        assert!(compile("fn f(", &l).is_err());
+    }
+
+    #[test]
+    fn test_settings() {
+        let t: toml::Table = toml::from_str("timeout-ms = 50\nmax-size = 10").unwrap();
+        let l = Limits::from_settings(Some(&t)).unwrap();
+        assert_eq!((l.timeout, l.max_size), (Duration::from_millis(50), 10));
+        let t: toml::Table = toml::from_str("speed = 1").unwrap();
+        assert!(Limits::from_settings(Some(&t)).is_err());
+    }
+}
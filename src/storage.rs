@@ -0,0 +1,97 @@
+use std::path::{ Path, PathBuf };
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::{ Mutex, RwLock, RwLockReadGuard };
+
+/// A value persisted as a single JSON file under the storage directory. Reads are served from
+/// memory; every `update` rewrites the whole file.
+pub struct JsonStore<T> {
+    path: PathBuf,
+    value: RwLock<T>,
+    /// The number of updates so far, taken under the value's write lock.
+    updates: AtomicU64,
+    /// Held while writing the file, with the number of the update last written, so that an
+    /// update whose snapshot a later one has already written is not written over it.
+    file: Mutex<u64>,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> JsonStore<T> {
+    /// Load the store from `path`, starting from `T::default()` if the file does not exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let value = match std::fs::read_to_string(&path) {
+            Ok(s) => serde_json::from_str(&s).map_err(|e| format!("cannot parse {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(e) => return Err(format!("cannot read {}: {e}", path.display())),
+        };
+        Ok(Self { path, value: RwLock::new(value), updates: AtomicU64::new(0), file: Mutex::new(0) })
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().await
+    }
+
+    /// Mutate the value and write it back to disk. The in-memory value is updated even if
+    /// writing fails. Readers are not held up by the write.
+    pub async fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, String> {
+        let (r, s, n) = {
+            let mut value = self.value.write().await;
+            let r = f(&mut value);
+            let s = serde_json::to_string_pretty(&*value).map_err(|e| format!("cannot serialize: {e}"))?;
+            (r, s, self.updates.fetch_add(1, Ordering::Relaxed) + 1)
+        };
+        let mut written = self.file.lock().await;
+        if *written > n {
+            // a later snapshot, which has this update too, is on disk already
+            return Ok(r);
+        }
+        // write to a sibling file first so that a crash never leaves a truncated store behind
+        let tmp = self.path.with_extension("json.tmp");
+        let result = match tokio::fs::write(&tmp, s).await {
+            Ok(()) => tokio::fs::rename(&tmp, &self.path).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => { *written = n; Ok(r) }
+            Err(e) => Err(format!("cannot write {}: {e}", self.path.display())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("nombot-storage-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store: JsonStore<BTreeMap<String, u32>> = JsonStore::open(&path).unwrap();
+        assert!(store.read().await.is_empty());
+        store.update(|m| m.insert("nom".into(), 3)).await.unwrap();
+        let store: JsonStore<BTreeMap<String, u32>> = JsonStore::open(&path).unwrap();
+        assert_eq!(store.read().await.get("nom"), Some(&3));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_updates() {
+        let path = std::env::temp_dir().join(format!("nombot-storage-test-concurrent-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store: std::sync::Arc<JsonStore<Vec<u32>>> = std::sync::Arc::new(JsonStore::open(&path).unwrap());
+        let tasks: Vec<_> = (0..20).map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move { store.update(|v| v.push(i)).await.unwrap() })
+        }).collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+        // the file has the last update, not an earlier one written late
+        let reopened: JsonStore<Vec<u32>> = JsonStore::open(&path).unwrap();
+        assert_eq!(reopened.read().await.len(), 20);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,442 @@
+//! Text transforms, for `nom/tr` and `nom/echo`.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use icu_collator::{ Collator, CollatorOptions, Numeric, Strength };
+use icu_locid::Locale;
+use rand::{ Rng, SeedableRng };
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::i18n::Msg;
+
+/// The units that `reverse`, `sort`, `shuffle` and `dedupe` rearrange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// What reads as one character, e.g. a flag or an emoji with a skin tone.
+    Grapheme,
+    /// A Unicode scalar value.
+    Char,
+    Word,
+    Sentence,
+    Line,
+}
+
+impl Scope {
+    pub const NAMES: &'static [&'static str] = &["grapheme", "char", "word", "sentence", "line"];
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "grapheme" => Some(Scope::Grapheme),
+            "char" => Some(Scope::Char),
+            "word" => Some(Scope::Word),
+            "sentence" => Some(Scope::Sentence),
+            "line" => Some(Scope::Line),
+            _ => None,
+        }
+    }
+}
+
+/// How `sort` orders units.
+pub enum Collation {
+    Codepoint,
+    Locale(Box<Collator>),
+}
+
+impl Collation {
+    /// The collation named by `name`, `codepoint` or a locale like `de` or `sv`; with `numeric` or
+    /// `ignore_case` and no name, that of `fallback`, the locale of the user.
+    pub fn new(name: Option<&str>, numeric: bool, ignore_case: bool, fallback: &str) -> Result<Self, Msg> {
+        let tag = match name {
+            Some("codepoint") if numeric || ignore_case => return Err(Msg::new("transform-codepoint-options", &[])),
+            Some("codepoint") => return Ok(Collation::Codepoint),
+            Some(tag) => tag,
+            None if numeric || ignore_case => fallback,
+            None => return Ok(Collation::Codepoint),
+        };
+        let locale: Locale = tag.parse().map_err(|_| Msg::new("transform-bad-locale", &[("tag", format!("{tag:?}").into())]))?;
+        let mut options = CollatorOptions::new();
+        if numeric {
+            options.numeric = Some(Numeric::On);
+        }
+        if ignore_case {
+            options.strength = Some(Strength::Secondary);
+        }
+        Collator::try_new(&(&locale).into(), options)
+            .map(|c| Collation::Locale(Box::new(c)))
+            .map_err(|e| Msg::new("transform-no-collator", &[("tag", tag.to_string().into()), ("error", e.to_string().into())]))
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Codepoint => a.cmp(b),
+            Collation::Locale(c) => c.compare(a, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Reverse,
+    Sort,
+    Shuffle,
+    Dedupe,
+    Upper,
+    Lower,
+    Title,
+    Mock,
+    Leet,
+    Zalgo,
+    Rot13,
+    Morse,
+    Unmorse,
+    Base64,
+    Unbase64,
+    Hex,
+    Unhex,
+    Url,
+    Unurl,
+}
+
+/// Every transform by name, with the message saying what it does.
+pub const TRANSFORMS: &[(&str, Transform, &str)] = &[
+    ("reverse", Transform::Reverse, "transform-reverse"),
+    ("sort", Transform::Sort, "transform-sort"),
+    ("shuffle", Transform::Shuffle, "transform-shuffle"),
+    ("dedupe", Transform::Dedupe, "transform-dedupe"),
+    ("upper", Transform::Upper, "transform-upper"),
+    ("lower", Transform::Lower, "transform-lower"),
+    ("title", Transform::Title, "transform-title"),
+    ("mock", Transform::Mock, "transform-mock"),
+    ("leet", Transform::Leet, "transform-leet"),
+    ("zalgo", Transform::Zalgo, "transform-zalgo"),
+    ("rot13", Transform::Rot13, "transform-rot13"),
+    ("morse", Transform::Morse, "transform-morse"),
+    ("unmorse", Transform::Unmorse, "transform-unmorse"),
+    ("base64", Transform::Base64, "transform-base64"),
+    ("unbase64", Transform::Unbase64, "transform-unbase64"),
+    ("hex", Transform::Hex, "transform-hex"),
+    ("unhex", Transform::Unhex, "transform-unhex"),
+    ("url", Transform::Url, "transform-url"),
+    ("unurl", Transform::Unurl, "transform-unurl"),
+];
+
+impl Transform {
+    pub fn from_name(s: &str) -> Option<Self> {
+        TRANSFORMS.iter().find(|(n, _, _)| *n == s).map(|(_, t, _)| *t)
+    }
+
+    /// Transform `text`; `scope` is the unit of the rearranging transforms, `collation` orders
+    /// `sort`, and `rng` is the source of randomness of `shuffle` and `zalgo`.
+    pub fn apply(self, text: &str, scope: Scope, collation: &Collation, rng: &mut StdRng) -> Result<String, Msg> {
+        Ok(match self {
+            Transform::Reverse => rearrange(text, scope, |u| (0..u.len()).rev().collect()),
+            Transform::Sort => rearrange(text, scope, |u| {
+                let mut i: Vec<_> = (0..u.len()).collect();
+                i.sort_by(|&a, &b| collation.compare(u[a], u[b]));
+                i
+            }),
+            Transform::Shuffle => rearrange(text, scope, |u| {
+                let mut i: Vec<_> = (0..u.len()).collect();
+                i.shuffle(rng);
+                i
+            }),
+            Transform::Dedupe => rearrange(text, scope, |u| {
+                let mut seen = HashSet::new();
+                (0..u.len()).filter(|&i| seen.insert(u[i])).collect()
+            }),
+            Transform::Upper => text.to_uppercase(),
+            Transform::Lower => text.to_lowercase(),
+            Transform::Title => text.split_word_bounds().map(|w| {
+                let mut cs = w.chars();
+                match cs.next() {
+                    Some(c) => c.to_uppercase().chain(cs.as_str().to_lowercase().chars()).collect(),
+                    None => String::new(),
+                }
+            }).collect(),
+            Transform::Mock => {
+                let mut upper = false;
+                text.chars().flat_map(|c| {
+                    let cs: Vec<_> = match c.is_alphabetic() {
+                        true if upper => c.to_uppercase().collect(),
+                        true => c.to_lowercase().collect(),
+                        false => vec![c],
+                    };
+                    upper ^= c.is_alphabetic();
+                    cs
+                }).collect()
+            }
+            Transform::Leet => text.chars().map(|c| match c.to_ascii_lowercase() {
+                'a' => '4', 'b' => '8', 'e' => '3', 'g' => '9', 'i' => '1', 'o' => '0', 's' => '5', 't' => '7',
+                _ => c,
+            }).collect(),
+            Transform::Zalgo => text.chars().flat_map(|c| {
+                let n = if c.is_whitespace() { 0 } else { rng.gen_range(1..=3) };
+                std::iter::once(c).chain((0..n).map(|_| char::from_u32(rng.gen_range(0x300..=0x36f)).unwrap()))
+                    .collect::<Vec<_>>()
+            }).collect(),
+            Transform::Rot13 => text.chars().map(|c| match c {
+                'a'..='z' => ((c as u8 - b'a' + 13) % 26 + b'a') as char,
+                'A'..='Z' => ((c as u8 - b'A' + 13) % 26 + b'A') as char,
+                c => c,
+            }).collect(),
+            Transform::Morse => to_morse(text)?,
+            Transform::Unmorse => from_morse(text)?,
+            Transform::Base64 => to_base64(text.as_bytes()),
+            Transform::Unbase64 => utf8(from_base64(text)?)?,
+            Transform::Hex => text.bytes().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" "),
+            Transform::Unhex => utf8(from_hex(text)?)?,
+            Transform::Url => text.bytes().map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+                b => format!("%{b:02X}"),
+            }).collect(),
+            Transform::Unurl => utf8(from_url(text)?)?,
+        })
+    }
+}
+
+/// A generator for `apply`, from `seed` if given.
+pub fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    }
+}
+
+// Put the units of `text` in the order, and keep only the ones, that `order` picks by index. In
+// word and sentence scope, the spaces and punctuation between the units stay in place.
+fn rearrange(text: &str, scope: Scope, order: impl FnOnce(&[&str]) -> Vec<usize>) -> String {
+    // the pieces of `text`, and whether each is a unit
+    let pieces: Vec<(&str, bool)> = match scope {
+        Scope::Grapheme => text.graphemes(true).map(|g| (g, true)).collect(),
+        Scope::Char => text.char_indices().map(|(i, c)| (&text[i..i + c.len_utf8()], true)).collect(),
+        Scope::Word => text.split_word_bounds().map(|w| (w, w.chars().any(char::is_alphanumeric))).collect(),
+        Scope::Sentence => text.split_sentence_bounds().flat_map(|s| {
+            let t = s.trim_end();
+            [(t, !t.is_empty()), (&s[t.len()..], false)]
+        }).filter(|(p, _)| !p.is_empty()).collect(),
+        Scope::Line => {
+            let lines: Vec<_> = text.split('\n').collect();
+            return order(&lines).into_iter().map(|i| lines[i]).collect::<Vec<_>>().join("\n");
+        }
+    };
+    let units: Vec<_> = pieces.iter().filter(|(_, u)| *u).map(|(p, _)| *p).collect();
+    let mut picked = order(&units).into_iter().map(|i| units[i]);
+    let mut out: Vec<&str> = vec![];
+    // the separators since the last unit, dropped along with a dropped unit
+    let mut since_unit = 0;
+    for (p, unit) in pieces {
+        if !unit {
+            out.push(p);
+            since_unit += 1;
+        } else if let Some(u) = picked.next() {
+            out.push(u);
+            since_unit = 0;
+        } else if out.len() > since_unit {
+            out.truncate(out.len() - since_unit);
+            since_unit = 0;
+        }
+    }
+    out.concat()
+}
+
+fn utf8(bytes: Vec<u8>) -> Result<String, Msg> {
+    String::from_utf8(bytes).map_err(|_| Msg::new("transform-not-utf8", &[]))
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            out.push(match i <= chunk.len() {
+                true => BASE64[(n >> (18 - 6 * i) & 63) as usize] as char,
+                false => '=',
+            });
+        }
+    }
+    out
+}
+
+// Padding is optional, and whitespace is ignored.
+fn from_base64(text: &str) -> Result<Vec<u8>, Msg> {
+    let digits = text.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    let digits = digits.trim_end_matches('=');
+    let mut out = vec![];
+    let (mut n, mut bits) = (0u32, 0);
+    for c in digits.chars() {
+        let d = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            c => return Err(Msg::new("transform-base64-digit", &[("char", format!("{c:?}").into())])),
+        };
+        n = n << 6 | d;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+            n &= (1 << bits) - 1;
+        }
+    }
+    if bits >= 6 {
+        return Err(Msg::new("transform-base64-end", &[]));
+    }
+    Ok(out)
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, Msg> {
+    let digits: Vec<_> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() % 2 == 1 {
+        return Err(Msg::new("transform-hex-pairs", &[]));
+    }
+    digits.chunks(2).map(|p| {
+        let (h, l) = (p[0].to_digit(16), p[1].to_digit(16));
+        match (h, l) {
+            (Some(h), Some(l)) => Ok((h * 16 + l) as u8),
+            _ => Err(Msg::new("transform-hex-byte", &[("pair", format!("{:?}", p.iter().collect::<String>()).into())])),
+        }
+    }).collect()
+}
+
+fn from_url(text: &str) -> Result<Vec<u8>, Msg> {
+    let bytes = text.as_bytes();
+    let mut out = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3).ok_or_else(|| Msg::new("transform-url-digits", &[]))?;
+            // `from_str_radix` alone would take a sign, as in `%+1`
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(Msg::new("transform-url-byte", &[("hex", hex.to_string().into())]));
+            }
+            out.push(u8::from_str_radix(hex, 16).unwrap());
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+const MORSE: &[(char, &str)] = &[
+    ('a', ".-"), ('b', "-..."), ('c', "-.-."), ('d', "-.."), ('e', "."), ('f', "..-."), ('g', "--."),
+    ('h', "...."), ('i', ".."), ('j', ".---"), ('k', "-.-"), ('l', ".-.."), ('m', "--"), ('n', "-."),
+    ('o', "---"), ('p', ".--."), ('q', "--.-"), ('r', ".-."), ('s', "..."), ('t', "-"), ('u', "..-"),
+    ('v', "...-"), ('w', ".--"), ('x', "-..-"), ('y', "-.--"), ('z', "--.."),
+    ('0', "-----"), ('1', ".----"), ('2', "..---"), ('3', "...--"), ('4', "....-"), ('5', "....."),
+    ('6', "-...."), ('7', "--..."), ('8', "---.."), ('9', "----."),
+    ('.', ".-.-.-"), (',', "--..--"), ('?', "..--.."), ('\'', ".----."), ('!', "-.-.--"), ('/', "-..-."),
+    ('(', "-.--."), (')', "-.--.-"), ('&', ".-..."), (':', "---..."), (';', "-.-.-."), ('=', "-...-"),
+    ('+', ".-.-."), ('-', "-....-"), ('"', ".-..-."), ('@', ".--.-."),
+];
+
+// Letters are separated by spaces and words by " / ".
+fn to_morse(text: &str) -> Result<String, Msg> {
+    let words: Result<Vec<_>, Msg> = text.split_whitespace().map(|w| {
+        let letters: Result<Vec<_>, Msg> = w.chars().map(|c| {
+            let c = c.to_ascii_lowercase();
+            MORSE.iter().find(|(l, _)| *l == c).map(|(_, m)| *m)
+                .ok_or_else(|| Msg::new("transform-no-morse", &[("char", format!("{c:?}").into())]))
+        }).collect();
+        letters.map(|l| l.join(" "))
+    }).collect();
+    Ok(words?.join(" / "))
+}
+
+fn from_morse(text: &str) -> Result<String, Msg> {
+    let words: Result<Vec<_>, Msg> = text.split('/').map(|w| {
+        w.split_whitespace().map(|m| {
+            MORSE.iter().find(|(_, c)| *c == m).map(|(l, _)| *l)
+                .ok_or_else(|| Msg::new("transform-not-morse", &[("code", format!("{m:?}").into())]))
+        }).collect::<Result<String, Msg>>()
+    }).collect();
+    Ok(words?.into_iter().filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tr(t: &str, scope: Scope, text: &str) -> Result<String, String> {
+        Transform::from_name(t).unwrap().apply(text, scope, &Collation::Codepoint, &mut rng(Some(42))).map_err(|e| e.tr("en"))
+    }
+
+    #[test]
+    fn test_rearrange() {
+        assert_eq!(tr("reverse", Scope::Char, "abc"), Ok("cba".into()));
+        assert_eq!(tr("sort", Scope::Line, "b\na\nc"), Ok("a\nb\nc".into()));
+        assert_eq!(tr("reverse", Scope::Word, "one, two three!"), Ok("three, two one!".into()));
+        assert_eq!(tr("dedupe", Scope::Word, "a b a c b"), Ok("a b c".into()));
+        assert_eq!(tr("dedupe", Scope::Word, "x, x; y"), Ok("x, y".into()));
+        assert_eq!(tr("dedupe", Scope::Char, "banana"), Ok("ban".into()));
+        let shuffled = tr("shuffle", Scope::Word, "a b c d e f g h").unwrap();
+        // the same seed shuffles the same way
+        assert_eq!(tr("shuffle", Scope::Word, "a b c d e f g h"), Ok(shuffled.clone()));
+        let mut words: Vec<_> = shuffled.split(' ').collect();
+        words.sort();
+        assert_eq!(words.join(" "), "a b c d e f g h");
+
+        // a flag, a thumbs up with a skin tone, and an e with an acute accent
+        let text = "\u{1f1ef}\u{1f1f5}\u{1f44d}\u{1f3fd}e\u{301}";
+        assert_eq!(tr("reverse", Scope::Grapheme, text), Ok("e\u{301}\u{1f44d}\u{1f3fd}\u{1f1ef}\u{1f1f5}".into()));
+        assert_eq!(tr("reverse", Scope::Char, "e\u{301}"), Ok("\u{301}e".into()));
+        assert_eq!(tr("reverse", Scope::Sentence, "One. Two?  Three!"), Ok("Three! Two?  One.".into()));
+    }
+
+    #[test]
+    fn test_collation() {
+        let sort = |c: &Collation, text: &str| Transform::Sort.apply(text, Scope::Word, c, &mut rng(None)).unwrap();
+        let text = "zebra \u{e4}pple Apple a10 a9";
+        assert_eq!(sort(&Collation::Codepoint, text), "Apple a10 a9 zebra \u{e4}pple");
+        assert_eq!(sort(&Collation::new(Some("en"), false, false, "ja").unwrap(), text), "a10 a9 Apple \u{e4}pple zebra");
+        assert_eq!(sort(&Collation::new(None, true, false, "en").unwrap(), text), "a9 a10 Apple \u{e4}pple zebra");
+        // in Swedish, \u{e4} comes after z
+        assert_eq!(sort(&Collation::new(Some("sv"), false, false, "en").unwrap(), text), "a10 a9 Apple zebra \u{e4}pple");
+        assert!(Collation::new(Some("codepoint"), true, false, "en").is_err());
+        assert!(Collation::new(Some("not a locale"), false, false, "en").is_err());
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(tr("upper", Scope::Char, "straße"), Ok("STRASSE".into()));
+        assert_eq!(tr("title", Scope::Char, "hELLO wide world"), Ok("Hello Wide World".into()));
+        assert_eq!(tr("mock", Scope::Char, "hello, world"), Ok("hElLo, WoRlD".into()));
+        assert_eq!(tr("leet", Scope::Char, "Leet speak"), Ok("L337 5p34k".into()));
+        assert_eq!(tr("rot13", Scope::Char, "Hello, World!"), Ok("Uryyb, Jbeyq!".into()));
+        let z = tr("zalgo", Scope::Char, "hi there").unwrap();
+        assert_eq!(z.chars().filter(|c| ('\u{300}'..='\u{36f}').contains(c)).count() + 8, z.chars().count());
+    }
+
+    #[test]
+    fn test_encodings() {
+        for (text, b64) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("nom nom \u{1f36a}", "bm9tIG5vbSDwn42q")] {
+            assert_eq!(tr("base64", Scope::Char, text), Ok(b64.into()));
+            assert_eq!(tr("unbase64", Scope::Char, b64), Ok(text.into()));
+        }
+        assert_eq!(tr("unbase64", Scope::Char, "Zm8"), Ok("fo".into()));
+        assert!(tr("unbase64", Scope::Char, "Z").is_err());
+        assert_eq!(tr("unbase64", Scope::Char, "/w==").unwrap_err(), "the decoded bytes are not UTF-8 text");
+
+        assert_eq!(tr("hex", Scope::Char, "hi\u{e9}"), Ok("68 69 c3 a9".into()));
+        assert_eq!(tr("unhex", Scope::Char, "6869C3A9"), Ok("hi\u{e9}".into()));
+        assert!(tr("unhex", Scope::Char, "686").is_err());
+
+        assert_eq!(tr("url", Scope::Char, "a b&c=d/\u{e9}"), Ok("a%20b%26c%3Dd%2F%C3%A9".into()));
+        assert_eq!(tr("unurl", Scope::Char, "a%20b%26c%3dd%2F%C3%A9"), Ok("a b&c=d/\u{e9}".into()));
+        assert!(tr("unurl", Scope::Char, "100%").is_err());
+        assert_eq!(tr("unurl", Scope::Char, "%+1").unwrap_err(), "%+1 is not a percent-encoded byte");
+        assert_eq!(tr("unurl", Scope::Char, "%\u{e9}").unwrap_err(), "%\u{e9} is not a percent-encoded byte");
+
+        assert_eq!(tr("morse", Scope::Char, "SOS, help"), Ok("... --- ... --..-- / .... . .-.. .--.".into()));
+        assert_eq!(tr("unmorse", Scope::Char, "... --- ... / .... .."), Ok("sos hi".into()));
+        assert!(tr("morse", Scope::Char, "\u{e9}").is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+use serde::{ Deserialize, Serialize };
+use serenity::model::Timestamp;
+use crate::defn::command::Message;
+
+pub const BUILTINS: &[&str] = &["author", "channel", "now"];
+pub const MAX_PER_SCOPE: usize = 64;
+pub const MAX_VALUE_LEN: usize = 1000;
+
+/// User-defined variables for `$name` expansion, by user id and by channel id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Variables {
+    #[serde(default)]
+    pub users: BTreeMap<u64, BTreeMap<String, String>>,
+    #[serde(default)]
+    pub channels: BTreeMap<u64, BTreeMap<String, String>>,
+}
+
+impl Variables {
+    /// Look a variable up for `msg`: built-ins first, then the author's variables, then the
+    /// channel's.
+    pub fn lookup(&self, msg: &Message, name: &str) -> Option<String> {
+        builtin(msg, name)
+            .or_else(|| self.users.get(&msg.author.id.0).and_then(|v| v.get(name)).cloned())
+            .or_else(|| self.channels.get(&msg.channel_id.0).and_then(|v| v.get(name)).cloned())
+    }
+}
+
+pub fn builtin(msg: &Message, name: &str) -> Option<String> {
+    match name {
+        "author" => Some(msg.author.name.clone()),
+        "channel" => Some(format!("<#{}>", msg.channel_id.0)),
+        "now" => Some(Timestamp::now().to_string()),
+        _ => None,
+    }
+}
+
+/// Whether `s` can be referred to as `$s`.
+pub fn is_name(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}